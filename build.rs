@@ -12,13 +12,49 @@ use graphql_client_codegen::{
 use syn::Token;
 
 fn main() {
-    // download it from https://docs.github.com/public/schema.docs.graphql
+    // downloaded by scripts/update-schema.sh
     let schema_path = "schema.docs.graphql".to_string();
     for file_name in [
+        "add_comment",
+        "add_discussion_comment",
+        "add_draft_item",
+        "add_item",
+        "close_issue",
+        "close_pull_request",
+        "comment_subject",
+        "content_id",
+        "convert_draft_item",
+        "create_field",
+        "create_project",
+        "delete_field",
         "delete_item",
+        "items_updated_at",
+        "list_assignable_users",
+        "list_content_assignees",
+        "list_content_labels",
+        "list_issues",
+        "list_item_bodies",
+        "list_item_comments",
+        "list_item_timelines",
         "list_items",
+        "list_labels",
+        "list_reactions",
+        "list_content_reactions",
+        "list_item_events",
+        "list_content_events",
+        "list_linked_repositories",
+        "list_pull_requests",
         "list_fields",
+        "list_views",
+        "owner_id",
+        "reopen_issue",
+        "reopen_pull_request",
+        "repository_id",
+        "update_field",
+        "update_field_options",
         "update_item_field",
+        "update_project",
+        "viewer_login",
     ] {
         let mut options = GraphQLClientCodegenOptions::new(CodegenMode::Cli);
         options.set_module_visibility(
@@ -27,6 +63,10 @@ fn main() {
             }
             .into(),
         );
+        // the pages of items are saved to resume an interrupted scan
+        if file_name == "list_items" {
+            options.set_response_derives("Serialize".to_string());
+        }
         let gen = generate_module_token_stream(
             format!("src/{file_name}.graphql").into(),
             Path::new(&schema_path),
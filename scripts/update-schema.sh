@@ -0,0 +1,8 @@
+#!/bin/bash
+# Replace schema.docs.graphql with the current public schema of the GitHub GraphQL API,
+# which src/*.graphql are checked and generated against by build.rs
+
+set -euo pipefail
+
+cd "$(dirname "$0")/.."
+curl -fsSL https://docs.github.com/public/fpt/schema.docs.graphql -o schema.docs.graphql
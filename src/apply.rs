@@ -0,0 +1,138 @@
+use std::{
+    io::{self, Read},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, Context, Error, Result};
+use gluesql::core::prelude::Value;
+use serde::Deserialize;
+
+use crate::{
+    csv, output,
+    storage::{FieldUpdate, PartialFailure, ProjectNextStorage},
+};
+
+#[derive(Debug)]
+pub enum InputFormat {
+    Csv,
+    Json,
+}
+
+impl FromStr for InputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            _ => Err(anyhow!("Unknown input format: {}", s)),
+        }
+    }
+}
+
+pub struct Opt {
+    pub format: InputFormat,
+    pub batch_size: usize,
+}
+
+pub struct Apply {
+    opt: Opt,
+    storage: ProjectNextStorage,
+}
+
+impl Apply {
+    pub fn new(opt: Opt, storage: ProjectNextStorage) -> Self {
+        Self { opt, storage }
+    }
+
+    /// read updates from stdin, apply them and report the ones which failed
+    pub fn run(&mut self) -> Result<()> {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .context("Failed to read stdin")?;
+        let updates = match self.opt.format {
+            InputFormat::Csv => parse_csv(&input)?,
+            InputFormat::Json => parse_json(&input)?,
+        };
+        let results = self.storage.apply_updates(&updates, self.opt.batch_size)?;
+        let mut failures = vec![];
+        for (update, result) in updates.iter().zip(results) {
+            if let Some(e) = result {
+                if output::error_format() == output::ErrorFormat::Text {
+                    eprintln!("Failed: {} {}: {}", update.id, update.field, e);
+                }
+                failures.push((update.id.clone(), update.field.clone(), e));
+            }
+        }
+        let applied = updates.len() - failures.len();
+        eprintln!("Updated {} of {} field value(s)", applied, updates.len());
+        if !failures.is_empty() {
+            return Err(PartialFailure { applied, failures }.into());
+        }
+        Ok(())
+    }
+}
+
+/// rows of `id,field,value`, with an optional header of the same names
+fn parse_csv(input: &str) -> Result<Vec<FieldUpdate>> {
+    let mut records = csv::parse(input)?;
+    let is_header = |record: &Vec<String>| {
+        record
+            .iter()
+            .map(|s| s.to_lowercase())
+            .eq(["id", "field", "value"])
+    };
+    if records.first().is_some_and(is_header) {
+        records.remove(0);
+    }
+    records
+        .into_iter()
+        .enumerate()
+        .map(|(i, record)| match <[String; 3]>::try_from(record) {
+            Ok([id, field, value]) => Ok(FieldUpdate {
+                id,
+                field,
+                value: if value.is_empty() {
+                    Value::Null
+                } else {
+                    Value::Str(value)
+                },
+            }),
+            Err(record) => Err(anyhow!(
+                "Record {} has {} fields, expected `id,field,value`",
+                i + 1,
+                record.len()
+            )),
+        })
+        .collect()
+}
+
+/// an array of `{"id": ..., "field": ..., "value": ...}`
+fn parse_json(input: &str) -> Result<Vec<FieldUpdate>> {
+    #[derive(Deserialize)]
+    struct Update {
+        id: String,
+        field: String,
+        value: serde_json::Value,
+    }
+    let updates: Vec<Update> = serde_json::from_str(input).context("Failed to parse JSON")?;
+    updates
+        .into_iter()
+        .map(|update| {
+            let value = match update.value {
+                serde_json::Value::Null => Value::Null,
+                serde_json::Value::String(s) => Value::Str(s),
+                serde_json::Value::Number(n) => {
+                    Value::F64(n.as_f64().ok_or_else(|| anyhow!("Invalid number: {}", n))?)
+                }
+                other => return Err(anyhow!("Unsupported value: {}", other)),
+            };
+            Ok(FieldUpdate {
+                id: update.id,
+                field: update.field,
+                value,
+            })
+        })
+        .collect()
+}
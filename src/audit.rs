@@ -0,0 +1,98 @@
+use std::{fs::OpenOptions, io::Write, path::PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use gluesql::core::prelude::Value;
+
+use crate::{functions, output, schema::identifier};
+
+/// a SQL file the mutations sent to GitHub are appended to, each as an equivalent statement
+/// with the time and the user in a comment
+#[derive(Debug, Clone)]
+pub struct Audit {
+    path: PathBuf,
+}
+
+impl Audit {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn record(&self, statements: Vec<String>) -> Result<()> {
+        if statements.is_empty() {
+            return Ok(());
+        }
+        let actor = functions::viewer_login()?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open {}", self.path.display()))?;
+        let at = Local::now().to_rfc3339();
+        for statement in statements {
+            writeln!(file, "-- {} by {}\n{};", at, actor, statement)?;
+        }
+        Ok(())
+    }
+}
+
+/// `INSERT INTO table (column, ...) VALUES (value, ...)`
+pub fn insert(table: &str, values: &[(&str, &Value)]) -> String {
+    let columns: Vec<_> = values
+        .iter()
+        .map(|(column, _)| identifier(column))
+        .collect();
+    let values: Vec<_> = values.iter().map(|(_, value)| literal(value)).collect();
+    format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        identifier(table),
+        columns.join(", "),
+        values.join(", ")
+    )
+}
+
+/// `UPDATE table SET column = value, ... [WHERE condition]`
+pub fn update(table: &str, values: &[(&str, &Value)], condition: Option<&str>) -> String {
+    let assignments: Vec<_> = values
+        .iter()
+        .map(|(column, value)| format!("{} = {}", identifier(column), literal(value)))
+        .collect();
+    let mut statement = format!(
+        "UPDATE {} SET {}",
+        identifier(table),
+        assignments.join(", ")
+    );
+    if let Some(condition) = condition {
+        statement.push_str(&format!(" WHERE {}", condition));
+    }
+    statement
+}
+
+/// `DELETE FROM table WHERE condition`
+pub fn delete(table: &str, condition: &str) -> String {
+    format!("DELETE FROM {} WHERE {}", identifier(table), condition)
+}
+
+/// `column = value`, or `column IS NULL`
+pub fn eq(column: &str, value: &Value) -> String {
+    match value {
+        Value::Null => format!("{} IS NULL", identifier(column)),
+        value => format!("{} = {}", identifier(column), literal(value)),
+    }
+}
+
+fn literal(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Bool(b) => b.to_string().to_uppercase(),
+        Value::I64(n) => n.to_string(),
+        Value::F64(n) => n.to_string(),
+        Value::Str(s) => format!("'{}'", s.replace('\'', "''")),
+        Value::Date(date) => format!("'{}'", date),
+        Value::List(values) => {
+            let values: Vec<_> = values.iter().map(literal).collect();
+            format!("[{}]", values.join(", "))
+        }
+        value => format!("'{}'", output::value_to_string(value).replace('\'', "''")),
+    }
+}
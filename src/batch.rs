@@ -1,56 +1,120 @@
-use std::{
-    fmt::Debug,
-    io::{BufWriter, Write},
-};
+use std::io::{BufWriter, Write};
 
 use anyhow::Result;
-use gluesql::{
+use gluesql::core::{
     executor::Payload,
     prelude::Glue,
-    store::{GStore, GStoreMut},
+    store::{GStore, GStoreMut, Planner},
 };
 
-use crate::output::{error_to_string, Format};
+use crate::{
+    browser, clipboard, comment,
+    output::{report_sql_error, Format, Reported, Stats, StatsFormat},
+    prompt::Session,
+    sql, tui,
+};
 
 pub struct Opt {
     pub format: Format,
     pub statement: String,
+    pub comment_to: Option<comment::Target>,
+    pub stats: Option<StatsFormat>,
+    /// open the URLs of the rows of the results in the browser
+    pub open: bool,
+    /// open several URLs without confirmation
+    pub yes: bool,
+    /// place the results on the clipboard instead of printing them
+    pub clip: bool,
+    /// show the results with [`tui::browse`] instead of printing them
+    pub tui: bool,
 }
 
-pub struct Batch<K, S>
+pub struct Batch<S>
 where
-    K: Debug,
-    S: GStore<K> + GStoreMut<K>,
+    S: GStore + GStoreMut + Planner + Session,
 {
     opt: Opt,
-    glue: Glue<K, S>,
+    glue: Glue<S>,
 }
 
-impl<K, S> Batch<K, S>
+impl<S> Batch<S>
 where
-    K: Debug,
-    S: GStore<K> + GStoreMut<K>,
+    S: GStore + GStoreMut + Planner + Session,
 {
-    pub fn new(opt: Opt, glue: Glue<K, S>) -> Self {
+    pub fn new(opt: Opt, glue: Glue<S>) -> Self {
         Self { opt, glue }
     }
 
+    /// execute every statement in order, stopping at the first error, which decides the
+    /// exit code
     pub fn run(&mut self) -> Result<()> {
-        let output = self.glue.execute(&self.opt.statement);
-        match output {
-            Ok(Payload::Select { labels, rows }) => {
-                let stdout = std::io::stdout();
-                let stdout = stdout.lock();
-                let mut stdout = BufWriter::new(stdout);
-                self.opt.format.print(&mut stdout, labels, rows)?;
-                stdout.flush()?;
-                drop(stdout);
+        let statements = match sql::parse_all(&self.opt.statement) {
+            Ok(statements) => statements,
+            Err(err) => return Err(Reported(report_sql_error(err)).into()),
+        };
+        let mut body = vec![];
+        let mut urls = vec![];
+        let mut clipped = vec![];
+        let mut clipped_rows = 0;
+        let mut failure = None;
+        for statement in &statements {
+            #[allow(clippy::result_large_err)]
+            let (output, stats) = Stats::measure(|| sql::execute(&mut self.glue, statement));
+            if let (true, Ok(Payload::Select { labels, rows })) = (self.opt.open, &output) {
+                urls.extend(browser::urls(labels, rows));
+            }
+            match output {
+                Ok(Payload::Select { labels, rows }) if self.opt.comment_to.is_some() => {
+                    if !body.is_empty() {
+                        body.push(b'\n');
+                    }
+                    Format::Markdown.print(&mut body, labels, rows, &stats)?;
+                }
+                Ok(Payload::Select { labels, rows }) if self.opt.clip => {
+                    if !clipped.is_empty() {
+                        clipped.push(b'\n');
+                    }
+                    clipped_rows += rows.len();
+                    self.opt.format.print(&mut clipped, labels, rows, &stats)?;
+                }
+                Ok(Payload::Select { labels, rows }) if self.opt.tui => {
+                    tui::browse(labels, rows, &mut self.glue)?
+                }
+                Ok(Payload::Select { labels, rows }) => {
+                    let stdout = std::io::stdout();
+                    let stdout = stdout.lock();
+                    let mut stdout = BufWriter::new(stdout);
+                    self.opt.format.print(&mut stdout, labels, rows, &stats)?;
+                    stdout.flush()?;
+                    drop(stdout);
+                }
+                Ok(_) => {}
+                Err(err) => failure = Some(report_sql_error(err)),
             }
-            Ok(_) => {}
-            Err(err) => {
-                eprintln!("SQL execution error: {}", error_to_string(err));
+            if let Some(format) = self.opt.stats {
+                stats.report(format, &statement.to_string());
             }
+            if failure.is_some() {
+                break;
+            }
+        }
+        if let Some(target) = &self.opt.comment_to {
+            if !body.is_empty() {
+                let body = String::from_utf8(body)?;
+                let url = comment::post(target, &body)?;
+                eprintln!("Posted: {}", url);
+            }
+        }
+        if self.opt.clip && !clipped.is_empty() {
+            clipboard::copy(&clipped)?;
+            eprintln!("Copied {} row(s) to the clipboard", clipped_rows);
+        }
+        if self.opt.open && failure.is_none() {
+            browser::open(&urls, self.opt.yes)?;
+        }
+        match failure {
+            Some(failure) => Err(Reported(failure).into()),
+            None => Ok(()),
         }
-        Ok(())
     }
 }
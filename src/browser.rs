@@ -0,0 +1,128 @@
+use std::{
+    env,
+    io::{self, IsTerminal},
+    process::Command,
+};
+
+use anyhow::{anyhow, Context, Result};
+use gluesql::core::prelude::Value;
+
+use crate::gh;
+
+/// URLs opened at most at once, the rest are printed
+const MAX_URLS: usize = 10;
+
+/// the URL of every row: of a `url` column, or of the issue or pull request of `Repository`
+/// and `Issue` (`repo` and `number` of `issues` and `pull_requests`). Rows of draft
+/// issues have none, nor have those whose `url` isn't `http` or `https`, which could open
+/// local files or programs
+pub fn urls(labels: &[String], rows: &[Vec<Value>]) -> Vec<String> {
+    let column = |names: &[&str]| {
+        labels
+            .iter()
+            .position(|label| names.iter().any(|name| label.eq_ignore_ascii_case(name)))
+    };
+    let url = column(&["url"]);
+    let repository = column(&["Repository", "repo"]);
+    let number = column(&["Issue", "number"]);
+    rows.iter()
+        .filter_map(|row| {
+            if let Some(Value::Str(url)) = url.map(|idx| &row[idx]) {
+                return is_web_url(url).then(|| url.clone());
+            }
+            match (&row[repository?], &row[number?]) {
+                (Value::Str(repository), Value::I64(number)) => {
+                    // GitHub redirects to the pull request of the number
+                    Some(gh::web_url(&format!("{}/issues/{}", repository, number)))
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+fn is_web_url(url: &str) -> bool {
+    let scheme = url.split_once("://").map(|(scheme, _)| scheme);
+    scheme.is_some_and(|scheme| {
+        scheme.eq_ignore_ascii_case("https") || scheme.eq_ignore_ascii_case("http")
+    })
+}
+
+/// open the first [`MAX_URLS`] of `urls` in the browser, asking first if there are several
+/// unless `yes`
+pub fn open(urls: &[String], yes: bool) -> Result<()> {
+    let (opened, rest) = urls.split_at(urls.len().min(MAX_URLS));
+    match opened.len() {
+        0 => {
+            return Err(anyhow!(
+                "No URL to open, select Repository and Issue or a url column"
+            ))
+        }
+        1 => {}
+        _ if yes => {}
+        n => confirm(n)?,
+    }
+    for url in opened {
+        if !is_web_url(url) {
+            return Err(anyhow!(
+                "Refusing to open {}, which isn't an http(s) URL",
+                url
+            ));
+        }
+        let mut command = browser();
+        let status = command
+            .arg(url)
+            .status()
+            .with_context(|| format!("Failed to open {}", url))?;
+        if !status.success() {
+            return Err(anyhow!("Failed to open {}: {}", url, status));
+        }
+    }
+    if !rest.is_empty() {
+        eprintln!("{} more URL(s) not opened:", rest.len());
+        for url in rest {
+            eprintln!("{}", url);
+        }
+    }
+    Ok(())
+}
+
+fn confirm(n: usize) -> Result<()> {
+    if !io::stdin().is_terminal() {
+        return Err(anyhow!(
+            "Refusing to open {} URLs without confirmation, pass --yes",
+            n
+        ));
+    }
+    eprint!("Open {} URLs in the browser? [y/N] ", n);
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    if matches!(answer.trim(), "y" | "Y" | "yes") {
+        Ok(())
+    } else {
+        Err(anyhow!("Canceled"))
+    }
+}
+
+/// `GH_BROWSER` or `BROWSER` like `gh browse`, then the opener of the system
+fn browser() -> Command {
+    let browser = ["GH_BROWSER", "BROWSER"]
+        .into_iter()
+        .find_map(|name| env::var(name).ok().filter(|value| !value.trim().is_empty()));
+    if let Some(browser) = browser {
+        let mut words = browser.split_whitespace();
+        let mut command = Command::new(words.next().expect("not blank"));
+        command.args(words);
+        return command;
+    }
+    if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        // unlike `cmd /C start`, which would run what follows a `&` of the URL
+        let mut command = Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    } else {
+        Command::new("xdg-open")
+    }
+}
@@ -0,0 +1,131 @@
+use std::{collections::HashMap, fs, path::PathBuf, sync::Mutex};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// whether a [`Cassette`] saves the exchanges with GitHub or serves them back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Record,
+    Replay,
+}
+
+/// a directory of GraphQL request/response pairs, one file per exchange named after the
+/// operation, a hash of the request and how many times the same request was sent before
+#[derive(Debug)]
+pub struct Cassette {
+    dir: PathBuf,
+    pub mode: Mode,
+    /// times each request has been sent so far, as identical requests may get different
+    /// responses, e.g. `listItems` before and after an `UPDATE`
+    sent: Mutex<HashMap<u64, usize>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Exchange {
+    request: serde_json::Value,
+    scopes: Option<String>,
+    response: serde_json::Value,
+}
+
+impl Cassette {
+    pub fn new(dir: PathBuf, mode: Mode) -> Result<Self> {
+        match mode {
+            Mode::Record => fs::create_dir_all(&dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?,
+            Mode::Replay if !dir.is_dir() => {
+                return Err(anyhow!("No recording found at {}", dir.display()))
+            }
+            Mode::Replay => {}
+        }
+        Ok(Self {
+            dir,
+            mode,
+            sent: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// save the exchange, `scopes` is the `X-OAuth-Scopes` header of the response
+    pub fn record(&self, request: &[u8], scopes: Option<&str>, response: &[u8]) -> Result<()> {
+        let path = self.path(request, self.next(request));
+        let exchange = Exchange {
+            request: serde_json::from_slice(request)?,
+            scopes: scopes.map(str::to_string),
+            response: serde_json::from_slice(response).unwrap_or_else(|_| {
+                serde_json::Value::String(String::from_utf8_lossy(response).into_owned())
+            }),
+        };
+        fs::write(&path, serde_json::to_vec_pretty(&exchange)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// the recorded scopes and response for the request, the last one recorded once the
+    /// request has been sent more times than it was while recording
+    pub fn replay(&self, request: &[u8]) -> Result<(Option<String>, Vec<u8>)> {
+        let n = self.next(request);
+        let path = (0..=n)
+            .rev()
+            .map(|n| self.path(request, n))
+            .find(|path| path.exists())
+            .ok_or_else(|| {
+                anyhow!(
+                    "No recorded response for this request in {} (expected {}); record it again with --record",
+                    self.dir.display(),
+                    self.path(request, n).display()
+                )
+            })?;
+        let exchange: Exchange = serde_json::from_slice(
+            &fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?,
+        )
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+        let response = match exchange.response {
+            serde_json::Value::String(raw) => raw.into_bytes(),
+            response => serde_json::to_vec(&response)?,
+        };
+        Ok((exchange.scopes, response))
+    }
+
+    /// how many times the request was sent before this one
+    fn next(&self, request: &[u8]) -> usize {
+        let mut sent = self.sent.lock().expect("not poisoned");
+        let count = sent.entry(hash(request)).or_default();
+        *count += 1;
+        *count - 1
+    }
+
+    fn path(&self, request: &[u8], n: usize) -> PathBuf {
+        self.dir.join(format!(
+            "{}-{:016x}-{}.json",
+            operation(request),
+            hash(request),
+            n
+        ))
+    }
+}
+
+/// FNV-1a, which unlike `DefaultHasher` is the same across Rust versions
+fn hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// the operation name of the query in the request, e.g. `listItems`
+fn operation(request: &[u8]) -> String {
+    #[derive(Deserialize)]
+    struct Request {
+        query: String,
+    }
+    let Ok(request) = serde_json::from_slice::<Request>(request) else {
+        return "request".to_string();
+    };
+    let mut words = request
+        .query
+        .split(|c: char| c.is_whitespace() || c == '(' || c == '{')
+        .filter(|word| !word.is_empty());
+    match (words.next(), words.next()) {
+        (Some("query" | "mutation"), Some(name)) => name.to_string(),
+        (Some(kind @ ("query" | "mutation")), None) => kind.to_string(),
+        _ => "query".to_string(),
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Local};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// hours during which the pages of an interrupted scan are resumed from, later they are
+/// likely stale
+const MAX_AGE_HOURS: i64 = 1;
+
+/// `gh-sql/scans` in the local data directory, e.g. `~/.local/share` on Linux
+pub fn default_dir() -> Option<PathBuf> {
+    Some(dirs_next::data_local_dir()?.join("gh-sql").join("scans"))
+}
+
+/// a page of a scan and the cursor after it, a line of the checkpoint
+#[derive(Debug, Serialize, Deserialize)]
+struct Page<N> {
+    at: String,
+    after: String,
+    nodes: N,
+}
+
+/// a JSON Lines file of the pages fetched by a scan of the items of a project, so that a
+/// scan which failed or was interrupted resumes after them. Removed once a scan completes
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    path: PathBuf,
+}
+
+impl Checkpoint {
+    /// the checkpoint of the project on the host, as the ids of two hosts may collide
+    pub fn new(dir: &Path, host: &str, project_id: &str) -> Self {
+        Self {
+            path: dir.join(host).join(format!("{}.jsonl", project_id)),
+        }
+    }
+
+    /// the nodes of the saved pages and the cursor after them, unless there are none or
+    /// the first is older than [`MAX_AGE_HOURS`]
+    pub fn load<T: DeserializeOwned>(&self) -> Result<Option<(Vec<T>, String)>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", self.path.display()))
+            }
+        };
+        let mut nodes = vec![];
+        let mut after = None;
+        for (n, line) in content.lines().enumerate() {
+            // the line being written when the process was killed
+            let Ok(page) = serde_json::from_str::<Page<Vec<T>>>(line) else {
+                break;
+            };
+            if n == 0 {
+                let at = DateTime::parse_from_rfc3339(&page.at)
+                    .with_context(|| format!("{}:{}", self.path.display(), n + 1))?;
+                if Local::now().signed_duration_since(at) > Duration::hours(MAX_AGE_HOURS) {
+                    self.clear()?;
+                    return Ok(None);
+                }
+            }
+            nodes.extend(page.nodes);
+            after = Some(page.after);
+        }
+        Ok(after.map(|after| (nodes, after)))
+    }
+
+    /// append a page fetched before `after`
+    pub fn save<T: Serialize>(&self, after: &str, nodes: &[T]) -> Result<()> {
+        let page = Page {
+            at: Local::now().to_rfc3339(),
+            after: after.to_string(),
+            nodes,
+        };
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open {}", self.path.display()))?;
+        writeln!(file, "{}", serde_json::to_string(&page)?)?;
+        Ok(())
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Failed to remove {}", self.path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
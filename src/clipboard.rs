@@ -0,0 +1,11 @@
+use anyhow::{Context, Result};
+use arboard::Clipboard;
+
+/// place `text` on the clipboard of the system. On Linux the clipboard is handed over to the
+/// clipboard manager when it is dropped, as its content would be gone once the process exits
+pub fn copy(text: &[u8]) -> Result<()> {
+    let mut clipboard = Clipboard::new().context("Failed to open the clipboard")?;
+    clipboard
+        .set_text(String::from_utf8_lossy(text))
+        .context("Failed to copy to the clipboard")
+}
@@ -0,0 +1,321 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use anyhow::{anyhow, Context, Result};
+use gluesql::core::{
+    sqlparser::{
+        ast::{
+            Expr, Ident, Insert, SelectItem, SetExpr, Statement, TableFactor, Value as SqlValue,
+        },
+        dialect::GenericDialect,
+        parser::Parser,
+        tokenizer::{Token, Tokenizer},
+    },
+    store::Store,
+};
+
+/// the columns `SELECT *` lists for tables whose order or visibility is set, e.g. by
+/// `--column-order view`, other columns can still be selected by name
+static WILDCARDS: Mutex<BTreeMap<String, Vec<String>>> = Mutex::new(BTreeMap::new());
+
+/// columns left out of `SELECT *` by `--hide-columns`, by table
+static HIDDEN: Mutex<BTreeMap<String, Vec<String>>> = Mutex::new(BTreeMap::new());
+
+/// columns only fetched for statements which name them, like `Body`, by table, which are
+/// also left out of `SELECT *`
+static LAZY: Mutex<BTreeMap<String, Vec<String>>> = Mutex::new(BTreeMap::new());
+
+/// expressions `SELECT *` lists after the columns under their names, by table, see
+/// [`load_computed`]
+static COMPUTED: Mutex<BTreeMap<String, Vec<(String, Expr)>>> = Mutex::new(BTreeMap::new());
+
+thread_local! {
+    /// the words of the statement being executed on the thread, see [`is_named`]. Reports of
+    /// `schedule run` are executed on threads of their own
+    static NAMED: RefCell<BTreeSet<String>> = const { RefCell::new(BTreeSet::new()) };
+}
+
+/// hide the columns of `--hide-columns id,Repository,options.id`, which are of `items`
+/// unless qualified by another table
+pub fn hide(spec: &str) {
+    let mut hidden = HIDDEN.lock().unwrap();
+    for column in spec.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+        let (table, column) = column.split_once('.').unwrap_or(("items", column));
+        hidden
+            .entry(table.to_string())
+            .or_default()
+            .push(column.to_string());
+    }
+}
+
+/// `gh-sql/columns` in the config directory, e.g. `~/.config` on Linux
+fn computed_dir() -> Option<PathBuf> {
+    Some(dirs_next::config_dir()?.join("gh-sql").join("columns"))
+}
+
+/// `OWNER/PROJECT_NUMBER.sql` in [`computed_dir`], where the computed columns of a project
+/// are read from by default
+pub fn computed_path(owner: &str, project_number: i64) -> Option<PathBuf> {
+    Some(
+        computed_dir()?
+            .join(owner)
+            .join(format!("{}.sql", project_number)),
+    )
+}
+
+/// the owners and numbers of the projects which have a file in [`computed_dir`], sorted
+pub fn configured_projects() -> Vec<(String, i64)> {
+    let Some(Ok(owners)) = computed_dir().map(fs::read_dir) else {
+        return vec![];
+    };
+    let mut projects = vec![];
+    for owner in owners.flatten() {
+        let Ok(files) = fs::read_dir(owner.path()) else {
+            continue;
+        };
+        let owner = owner.file_name().to_string_lossy().into_owned();
+        for file in files.flatten() {
+            let path = file.path();
+            let number = path
+                .extension()
+                .filter(|ext| *ext == "sql")
+                .and(path.file_stem())
+                .and_then(|stem| stem.to_str()?.parse().ok());
+            if let Some(number) = number {
+                projects.push((owner.clone(), number));
+            }
+        }
+    }
+    projects.sort();
+    projects
+}
+
+/// read the computed columns of `path`, a line `age_days = TODAY() - CreatedAt` per column
+/// of items, or `table.name = ...` of another table. Lines starting with `--` are comments
+pub fn load_computed(path: &Path) -> Result<()> {
+    let text =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut computed = COMPUTED.lock().unwrap();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("--") {
+            continue;
+        }
+        let context = || format!("{}:{}", path.display(), n + 1);
+        let (name, expr) = line
+            .split_once('=')
+            .ok_or_else(|| anyhow!("expected `name = expression`, got: {}", line))
+            .with_context(context)?;
+        let tokens = Tokenizer::new(&GenericDialect {}, expr)
+            .tokenize()
+            .map_err(|e| anyhow!("{:?}", e))
+            .with_context(context)?;
+        let expr = Parser::new(&GenericDialect {})
+            .with_tokens(tokens)
+            .parse_expr()
+            .with_context(context)?;
+        let (table, name) = name
+            .trim()
+            .split_once('.')
+            .unwrap_or(("items", name.trim()));
+        computed
+            .entry(table.to_string())
+            .or_default()
+            .push((name.to_string(), expr));
+    }
+    Ok(())
+}
+
+/// set the columns `SELECT * FROM table` lists in order, `None` lists all of them
+pub fn set_wildcard(table: &str, columns: Option<Vec<String>>) {
+    let mut wildcards = WILDCARDS.lock().unwrap();
+    match columns {
+        Some(columns) => wildcards.insert(table.to_string(), columns),
+        None => wildcards.remove(table),
+    };
+}
+
+/// set the lazy columns of the table, which the storage fetches once [`is_named`]
+pub fn set_lazy(table: &str, columns: Vec<String>) {
+    LAZY.lock().unwrap().insert(table.to_string(), columns);
+}
+
+/// remember the words of the statement about to be executed
+pub fn set_statement(statement: &Statement) {
+    let sql = statement.to_string();
+    let words = match Tokenizer::new(&GenericDialect {}, &sql).tokenize() {
+        Ok(tokens) => tokens
+            .into_iter()
+            .filter_map(|token| match token {
+                Token::Word(word) => Some(word.value),
+                _ => None,
+            })
+            .collect(),
+        Err(_) => BTreeSet::new(),
+    };
+    NAMED.with(|named| *named.borrow_mut() = words);
+}
+
+/// whether the statement being executed names the column, which may be of another table
+/// but then the column is only fetched needlessly
+pub fn is_named(column: &str) -> bool {
+    NAMED.with(|named| named.borrow().contains(column))
+}
+
+/// replace `*` and `table.*` of a `SELECT` from a single table with its columns set by
+/// [`set_wildcard`] but the [`hide`]den and lazy ones, then its computed ones, only at the top
+/// level so that subqueries and `INSERT ... SELECT` see all. A computed column selected by
+/// name is replaced with its expression as well
+pub fn expand_wildcard<S: Store>(storage: &S, statement: &mut Statement) {
+    let Statement::Query(query) = statement else {
+        return;
+    };
+    let SetExpr::Select(select) = query.body.as_mut() else {
+        return;
+    };
+    let [from] = select.from.as_slice() else {
+        return;
+    };
+    let TableFactor::Table { name, alias, .. } = &from.relation else {
+        return;
+    };
+    if !from.joins.is_empty() {
+        return;
+    }
+    let table = name.to_string();
+    // the storage sets the columns when it fetches the table, which may not have happened yet
+    let Ok(Some(schema)) = storage.fetch_schema(&table) else {
+        return;
+    };
+    let mut hidden = HIDDEN
+        .lock()
+        .unwrap()
+        .get(&table)
+        .cloned()
+        .unwrap_or_default();
+    hidden.extend(
+        LAZY.lock()
+            .unwrap()
+            .get(&table)
+            .cloned()
+            .unwrap_or_default(),
+    );
+    let wildcard = WILDCARDS.lock().unwrap().get(&table).cloned();
+    let computed = COMPUTED
+        .lock()
+        .unwrap()
+        .get(&table)
+        .cloned()
+        .unwrap_or_default();
+    if wildcard.is_none() && hidden.is_empty() && computed.is_empty() {
+        return;
+    }
+    let columns: Vec<_> = wildcard
+        .unwrap_or_else(|| {
+            let column_defs = schema.column_defs.unwrap_or_default();
+            column_defs.into_iter().map(|def| def.name).collect()
+        })
+        .into_iter()
+        .filter(|column| !hidden.contains(column))
+        .collect();
+    let qualifiers = [
+        Some(table),
+        alias.as_ref().map(|alias| alias.name.value.clone()),
+    ];
+    let computed_item = |(name, expr): &(String, Expr)| SelectItem::ExprWithAlias {
+        expr: expr.clone(),
+        alias: Ident::new(name),
+    };
+    let projection = std::mem::take(&mut select.projection);
+    for item in projection {
+        if let SelectItem::UnnamedExpr(Expr::Identifier(ident)) = &item {
+            if let Some(column) = computed.iter().find(|(name, _)| *name == ident.value) {
+                select.projection.push(computed_item(column));
+                continue;
+            }
+        }
+        let expand = match &item {
+            SelectItem::Wildcard(_) => true,
+            SelectItem::QualifiedWildcard(name, _) => qualifiers.contains(&Some(name.to_string())),
+            _ => false,
+        };
+        if !expand {
+            select.projection.push(item);
+            continue;
+        }
+        // unquoted, as GlueSQL reads quoted identifiers as strings, which is fine for
+        // names with spaces as the statement isn't parsed again
+        select.projection.extend(
+            columns
+                .iter()
+                .map(|column| SelectItem::UnnamedExpr(Expr::Identifier(Ident::new(column)))),
+        );
+        select.projection.extend(computed.iter().map(computed_item));
+    }
+}
+
+/// GlueSQL inserts the rows of `INSERT INTO table (a, b) SELECT x, y ...` as if they had
+/// every column of the table in order, so the projection is rewritten to list them all with
+/// `NULL` for the columns not named
+pub fn expand_insert_columns<S: Store>(storage: &S, statement: &mut Statement) -> Result<()> {
+    let Statement::Insert(Insert {
+        table_name,
+        columns,
+        source: Some(source),
+        ..
+    }) = statement
+    else {
+        return Ok(());
+    };
+    let SetExpr::Select(select) = source.body.as_mut() else {
+        return Ok(());
+    };
+    if columns.is_empty() {
+        return Ok(());
+    }
+    let Ok(Some(schema)) = storage.fetch_schema(&table_name.to_string()) else {
+        return Ok(());
+    };
+    let exprs = select
+        .projection
+        .iter()
+        .map(|item| match item {
+            SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => Ok(expr),
+            _ => Err(anyhow!(
+                "INSERT ... SELECT with a column list needs the selected columns named, got: {}",
+                item
+            )),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if exprs.len() != columns.len() {
+        return Err(anyhow!(
+            "INSERT names {} column(s) but the SELECT has {}",
+            columns.len(),
+            exprs.len()
+        ));
+    }
+    let column_defs = schema.column_defs.unwrap_or_default();
+    let mut named = BTreeMap::new();
+    for (column, expr) in columns.iter().zip(exprs) {
+        if !column_defs.iter().any(|def| def.name == column.value) {
+            return Err(anyhow!("Column not found: {}", column.value));
+        }
+        named.insert(column.value.clone(), expr.clone());
+    }
+    select.projection = column_defs
+        .iter()
+        .map(|def| {
+            let expr = named
+                .remove(&def.name)
+                .unwrap_or(Expr::Value(SqlValue::Null));
+            SelectItem::UnnamedExpr(expr)
+        })
+        .collect();
+    columns.clear();
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Error, Result};
+
+use crate::gh::{self, GraphQLResponse};
+
+#[allow(warnings)]
+mod generated {
+    type URI = String;
+    include!(concat!(env!("OUT_DIR"), "/comment_subject.rs"));
+    include!(concat!(env!("OUT_DIR"), "/add_comment.rs"));
+    include!(concat!(env!("OUT_DIR"), "/add_discussion_comment.rs"));
+}
+
+/// issue, pull request or discussion in the form of `owner/repo#123`
+#[derive(Debug)]
+pub struct Target {
+    owner: String,
+    name: String,
+    number: i64,
+}
+
+impl FromStr for Target {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = || {
+            let (repo, number) = s.split_once('#')?;
+            let (owner, name) = repo.split_once('/')?;
+            let number = number.parse().ok()?;
+            if owner.is_empty() || name.is_empty() {
+                return None;
+            }
+            Some(Self {
+                owner: owner.to_string(),
+                name: name.to_string(),
+                number,
+            })
+        };
+        parse().ok_or_else(|| anyhow!("Expected `owner/repo#number`: {}", s))
+    }
+}
+
+enum Subject {
+    IssueOrPullRequest(String),
+    Discussion(String),
+}
+
+/// post `body` as a comment and return the URL of the new comment
+pub fn post(target: &Target, body: &str) -> Result<String> {
+    match find_subject(target)? {
+        Subject::IssueOrPullRequest(subject_id) => add_comment(subject_id, body),
+        Subject::Discussion(discussion_id) => add_discussion_comment(discussion_id, body),
+    }
+}
+
+fn find_subject(target: &Target) -> Result<Subject> {
+    use generated::comment_subject::*;
+    let query = include_str!("comment_subject.graphql");
+    let variables = Variables {
+        owner: target.owner.clone(),
+        name: target.name.clone(),
+        number: target.number,
+    };
+    let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+    let repository = resp.data.repository.ok_or_else(|| {
+        anyhow!("{}", resp.errors.error_msgs()).context("failed to fetch repository")
+    })?;
+    if let Some(subject) = repository.issue_or_pull_request {
+        let id = match subject {
+            CommentSubjectRepositoryIssueOrPullRequest::Issue(issue) => issue.id,
+            CommentSubjectRepositoryIssueOrPullRequest::PullRequest(pr) => pr.id,
+        };
+        return Ok(Subject::IssueOrPullRequest(id));
+    }
+    if let Some(discussion) = repository.discussion {
+        return Ok(Subject::Discussion(discussion.id));
+    }
+    Err(anyhow!(
+        "{}/{}#{} is not an issue, pull request or discussion",
+        target.owner,
+        target.name,
+        target.number
+    ))
+}
+
+fn add_comment(subject_id: String, body: &str) -> Result<String> {
+    use generated::add_comment::*;
+    let query = include_str!("add_comment.graphql");
+    let variables = Variables {
+        subject_id,
+        body: body.to_string(),
+    };
+    let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+    resp.data
+        .add_comment
+        .and_then(|payload| payload.comment_edge)
+        .and_then(|edge| edge.node)
+        .map(|node| node.url)
+        .ok_or_else(|| anyhow!("{}", resp.errors.error_msgs()))
+        .context("failed to add comment")
+}
+
+fn add_discussion_comment(discussion_id: String, body: &str) -> Result<String> {
+    use generated::add_discussion_comment::*;
+    let query = include_str!("add_discussion_comment.graphql");
+    let variables = Variables {
+        discussion_id,
+        body: body.to_string(),
+    };
+    let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+    resp.data
+        .add_discussion_comment
+        .and_then(|payload| payload.comment)
+        .map(|comment| comment.url)
+        .ok_or_else(|| anyhow!("{}", resp.errors.error_msgs()))
+        .context("failed to add discussion comment")
+}
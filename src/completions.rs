@@ -0,0 +1,82 @@
+use std::io::{self, Write};
+
+use anyhow::Result;
+use structopt::clap::{App, Shell};
+
+use crate::columns;
+
+/// completes OWNER and PROJECT_NUMBER, then the rest as the generated `_gh-sql` does
+const BASH_HOOK: &str = r#"
+# OWNER and PROJECT_NUMBER of the projects `gh-sql completions --projects` lists
+_gh-sql_projects() {
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    COMPREPLY=()
+    if [[ ${COMP_CWORD} -eq 1 && ${cur} != -* ]]; then
+        COMPREPLY=( $(compgen -W "$(gh-sql completions --projects | cut -d ' ' -f 1 | uniq)" -- "${cur}") )
+    elif [[ ${COMP_CWORD} -eq 2 && ${cur} != -* ]]; then
+        COMPREPLY=( $(compgen -W "$(gh-sql completions --projects | awk -v owner="${COMP_WORDS[1]}" '$1 == owner { print $2 }')" -- "${cur}") )
+    fi
+    if [[ ${#COMPREPLY[@]} -eq 0 ]]; then
+        _gh-sql "$@"
+    fi
+}
+
+complete -F _gh-sql_projects -o bashdefault -o default gh-sql
+"#;
+
+/// replaces the call of `_gh-sql` the generated script ends with
+const ZSH_HOOK: &str = r#"
+# OWNER and PROJECT_NUMBER of the projects `gh-sql completions --projects` lists
+_gh-sql_projects() {
+    local -a projects
+    projects=(${(f)"$(gh-sql completions --projects)"})
+    if (( CURRENT == 2 )) && [[ $PREFIX != -* ]]; then
+        compadd -- ${(u)projects%% *}
+    elif (( CURRENT == 3 )) && [[ $PREFIX != -* ]]; then
+        compadd -- ${${(M)projects:#${(b)words[2]} *}#* }
+    fi
+    _gh-sql "$@"
+}
+
+compdef _gh-sql_projects gh-sql
+_gh-sql_projects "$@"
+"#;
+
+const FISH_HOOK: &str = r#"
+# OWNER and PROJECT_NUMBER of the projects `gh-sql completions --projects` lists
+complete -c gh-sql -n "test (count (commandline -opc)) -eq 1" -f -a "(gh-sql completions --projects | string split -f 1 ' ')"
+complete -c gh-sql -n "test (count (commandline -opc)) -eq 2" -f -a "(gh-sql completions --projects | string match -- (commandline -opc)[2]' *' | string split -f 2 ' ')"
+"#;
+
+/// print the completion script of `app` for `shell`, which for bash, zsh and fish completes
+/// OWNER and PROJECT_NUMBER with the projects [`print_projects`] lists when it runs
+pub fn print(mut app: App, shell: Shell) -> Result<()> {
+    let mut script = vec![];
+    app.gen_completions_to("gh-sql", shell, &mut script);
+    let hook = match shell {
+        Shell::Bash => BASH_HOOK,
+        Shell::Zsh => {
+            let call = b"_gh-sql \"$@\"";
+            if script.trim_ascii_end().ends_with(call) {
+                script.truncate(script.trim_ascii_end().len() - call.len());
+            }
+            ZSH_HOOK
+        }
+        Shell::Fish => FISH_HOOK,
+        _ => "",
+    };
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(&script)?;
+    stdout.write_all(hook.as_bytes())?;
+    Ok(())
+}
+
+/// print a line of `OWNER PROJECT_NUMBER` per project with computed columns in the config
+/// directory, the projects a user works on from this machine
+pub fn print_projects() -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    for (owner, number) in columns::configured_projects() {
+        writeln!(stdout, "{} {}", owner, number)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,387 @@
+use std::{
+    fs::{self, File},
+    io::{BufWriter, ErrorKind, Write},
+    path::PathBuf,
+};
+
+use gluesql::core::{
+    ast::DataType,
+    error::{Error as SqlError, Result as SqlResult},
+    executor::Payload,
+    prelude::Glue,
+    sqlparser::{
+        ast::{
+            DataType as SqlDataType, Expr, Ident, Insert, ObjectName, Query, SelectItem, SetExpr,
+            Statement, TableFactor, Value as SqlValue, Values,
+        },
+        dialect::{keywords::Keyword, GenericDialect},
+        parser::{Parser, ParserError},
+        tokenizer::{Token, Word},
+    },
+    store::{GStore, GStoreMut, Planner},
+};
+
+use crate::{csv, output, sql, storage::glue_error};
+
+/// `COPY { table [(column, ...)] | (query) } { TO | FROM } 'path' [[WITH] (option, ...)]`
+///
+/// Only CSV is supported. Unlike Postgres, `HEADER` defaults to true so that
+/// files round-trip with spreadsheets, and `TO` refuses to replace an existing file
+/// without the `OVERWRITE` option.
+#[derive(Debug)]
+pub enum Copy {
+    To {
+        query: Box<Statement>,
+        path: PathBuf,
+        header: bool,
+        overwrite: bool,
+    },
+    From {
+        table_name: String,
+        columns: Vec<String>,
+        path: PathBuf,
+        header: bool,
+    },
+}
+
+pub fn is_copy(tokens: &[Token]) -> bool {
+    matches!(
+        tokens.iter().find(|t| !matches!(t, Token::Whitespace(_))),
+        Some(Token::Word(Word {
+            keyword: Keyword::COPY,
+            ..
+        }))
+    )
+}
+
+pub fn parse(tokens: Vec<Token>) -> Result<Copy, ParserError> {
+    let mut tokens = tokens
+        .into_iter()
+        .filter(|t| !matches!(t, Token::Whitespace(_)))
+        .peekable();
+    let error = |msg: &str| ParserError::ParserError(msg.to_string());
+    if !tokens.next().is_some_and(|t| is_keyword(&t, Keyword::COPY)) {
+        return Err(error("Expected COPY"));
+    }
+
+    enum Source {
+        Table(String, Vec<String>),
+        Query(Box<Statement>),
+    }
+    let source = match tokens.next() {
+        Some(Token::LParen) => {
+            let mut depth = 1;
+            let mut query = vec![];
+            for token in tokens.by_ref() {
+                match token {
+                    Token::LParen => depth += 1,
+                    Token::RParen => depth -= 1,
+                    _ => {}
+                }
+                if depth == 0 {
+                    break;
+                }
+                query.push(token);
+            }
+            let statement = Parser::new(&GenericDialect {})
+                .with_tokens(query)
+                .parse_statement()?;
+            // checked before anything runs, as the statement would be executed to be copied
+            if !matches!(statement, Statement::Query(_)) {
+                return Err(error("COPY TO expects a query"));
+            }
+            Source::Query(Box::new(statement))
+        }
+        Some(Token::Word(table)) => {
+            let mut columns = vec![];
+            if tokens.next_if_eq(&Token::LParen).is_some() {
+                loop {
+                    match tokens.next() {
+                        Some(Token::Word(column)) => columns.push(column.value),
+                        _ => return Err(error("Expected a column name")),
+                    }
+                    match tokens.next() {
+                        Some(Token::Comma) => continue,
+                        Some(Token::RParen) => break,
+                        _ => return Err(error("Expected `,` or `)` after a column name")),
+                    }
+                }
+            }
+            Source::Table(table.value, columns)
+        }
+        _ => return Err(error("Expected a table name or a query after COPY")),
+    };
+    let to = match tokens.next() {
+        Some(t) if is_keyword(&t, Keyword::TO) => true,
+        Some(t) if is_keyword(&t, Keyword::FROM) => false,
+        _ => return Err(error("Expected TO or FROM")),
+    };
+    let path = match tokens.next() {
+        Some(Token::SingleQuotedString(path)) => PathBuf::from(path),
+        _ => return Err(error("Expected a quoted file path")),
+    };
+
+    let mut header = true;
+    let mut overwrite = false;
+    tokens.next_if(|t| is_keyword(t, Keyword::WITH));
+    let parenthesized = tokens.next_if_eq(&Token::LParen).is_some();
+    loop {
+        match tokens.next() {
+            Some(Token::Word(option)) if option.keyword == Keyword::FORMAT => match tokens.next() {
+                Some(Token::Word(format)) if format.value.eq_ignore_ascii_case("csv") => {}
+                _ => return Err(error("Only FORMAT csv is supported")),
+            },
+            Some(Token::Word(option)) if option.keyword == Keyword::CSV => {}
+            Some(Token::Word(option)) if option.keyword == Keyword::HEADER => {
+                header = match tokens.next_if(|t| matches!(t, Token::Word(_))) {
+                    Some(Token::Word(value)) => match value.keyword {
+                        Keyword::TRUE => true,
+                        Keyword::FALSE => false,
+                        _ => return Err(error("Expected true or false after HEADER")),
+                    },
+                    _ => true,
+                };
+            }
+            Some(Token::Word(option)) if option.keyword == Keyword::OVERWRITE && to => {
+                overwrite = true;
+            }
+            Some(Token::Comma) if parenthesized => {}
+            Some(Token::RParen) if parenthesized => break,
+            Some(Token::SemiColon) | None if !parenthesized => break,
+            Some(token) => return Err(error(&format!("Unexpected COPY option: {}", token))),
+            None => return Err(error("Expected `)` after COPY options")),
+        }
+    }
+    tokens.next_if_eq(&Token::SemiColon);
+    if let Some(token) = tokens.next() {
+        return Err(error(&format!("Unexpected token after COPY: {}", token)));
+    }
+
+    Ok(match (source, to) {
+        (Source::Query(query), true) => Copy::To {
+            query,
+            path,
+            header,
+            overwrite,
+        },
+        (Source::Table(table_name, columns), true) => {
+            let mut query = Parser::parse_sql(&GenericDialect {}, "SELECT * FROM t")?.remove(0);
+            if let Statement::Query(query) = &mut query {
+                if let SetExpr::Select(select) = query.body.as_mut() {
+                    if let TableFactor::Table { name, .. } = &mut select.from[0].relation {
+                        *name = ObjectName(vec![Ident::new(table_name)]);
+                    }
+                    if !columns.is_empty() {
+                        select.projection = columns
+                            .into_iter()
+                            .map(|column| {
+                                SelectItem::UnnamedExpr(Expr::Identifier(Ident::new(column)))
+                            })
+                            .collect();
+                    }
+                }
+            }
+            Copy::To {
+                query: Box::new(query),
+                path,
+                header,
+                overwrite,
+            }
+        }
+        (Source::Table(table_name, columns), false) => Copy::From {
+            table_name,
+            columns,
+            path,
+            header,
+        },
+        (Source::Query(_), false) => return Err(error("COPY FROM expects a table")),
+    })
+}
+
+fn is_keyword(token: &Token, keyword: Keyword) -> bool {
+    matches!(token, Token::Word(word) if word.keyword == keyword)
+}
+
+#[allow(clippy::result_large_err)]
+pub fn execute<S>(glue: &mut Glue<S>, copy: &Copy) -> SqlResult<Payload>
+where
+    S: GStore + GStoreMut + Planner,
+{
+    match copy {
+        Copy::To {
+            query,
+            path,
+            header,
+            overwrite,
+        } => {
+            let (labels, rows) = match sql::execute_sql(glue, query)? {
+                Payload::Select { labels, rows } => (labels, rows),
+                _ => return Err(SqlError::StorageMsg("COPY TO expects a query".to_string())),
+            };
+            let n = rows.len();
+            let write = || -> anyhow::Result<()> {
+                let file = File::options()
+                    .write(true)
+                    .create(true)
+                    .truncate(true)
+                    .create_new(!overwrite)
+                    .open(path);
+                let file = match file {
+                    Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                        return Err(anyhow::anyhow!(
+                            "The file exists, add the OVERWRITE option to replace it"
+                        ))
+                    }
+                    file => file?,
+                };
+                let mut w = BufWriter::new(file);
+                let labels = if *header {
+                    Some(labels.as_slice())
+                } else {
+                    None
+                };
+                output::print_as_csv(&mut w, labels, &rows)?;
+                w.flush()?;
+                Ok(())
+            };
+            write().map_err(|e| {
+                glue_error(e.context(format!("Failed to write {}", path.display())))
+            })?;
+            Ok(Payload::Select {
+                labels: vec!["copied".to_string()],
+                rows: vec![vec![gluesql::core::prelude::Value::I64(n as i64)]],
+            })
+        }
+        Copy::From {
+            table_name,
+            columns,
+            path,
+            header,
+        } => {
+            let insert = insert_statement(glue, table_name, columns, path, *header)?;
+            sql::execute_sql(glue, &insert)
+        }
+    }
+}
+
+/// read the file into `INSERT INTO table_name (columns) VALUES ...`
+#[allow(clippy::result_large_err)]
+fn insert_statement<S>(
+    glue: &Glue<S>,
+    table_name: &str,
+    columns: &[String],
+    path: &PathBuf,
+    header: bool,
+) -> SqlResult<Statement>
+where
+    S: GStore + GStoreMut + Planner,
+{
+    let text = fs::read_to_string(path)
+        .map_err(|e| SqlError::StorageMsg(format!("Failed to read {}: {}", path.display(), e)))?;
+    let mut records = csv::parse(&text).map_err(glue_error)?;
+    let names = if header && !records.is_empty() {
+        records.remove(0)
+    } else {
+        vec![]
+    };
+    let schema = glue
+        .storage
+        .fetch_schema(table_name)?
+        .ok_or_else(|| SqlError::StorageMsg(format!("table not found: {}", table_name)))?;
+    let column_defs = schema.column_defs.unwrap_or_default();
+    let columns: Vec<String> = if !columns.is_empty() {
+        columns.to_vec()
+    } else if !names.is_empty() {
+        names
+    } else {
+        column_defs.iter().map(|def| def.name.clone()).collect()
+    };
+    let data_types = columns
+        .iter()
+        .map(|column| {
+            column_defs
+                .iter()
+                .find(|def| &def.name == column)
+                .map(|def| def.data_type.clone())
+                .ok_or_else(|| {
+                    SqlError::StorageMsg(format!("column not found: {}.{}", table_name, column))
+                })
+        })
+        .collect::<SqlResult<Vec<_>>>()?;
+    let rows = records
+        .into_iter()
+        .enumerate()
+        .map(|(i, record)| {
+            if record.len() != columns.len() {
+                return Err(SqlError::StorageMsg(format!(
+                    "{}: record {} has {} fields but {} columns are copied",
+                    path.display(),
+                    i + 1,
+                    record.len(),
+                    columns.len()
+                )));
+            }
+            record
+                .iter()
+                .zip(&data_types)
+                .map(|(field, data_type)| to_literal(field, data_type))
+                .collect()
+        })
+        .collect::<SqlResult<Vec<_>>>()?;
+    Ok(Statement::Insert(Insert {
+        or: None,
+        ignore: false,
+        into: true,
+        table_name: ObjectName(vec![Ident::new(table_name)]),
+        table_alias: None,
+        columns: columns.iter().map(Ident::new).collect(),
+        overwrite: false,
+        source: Some(Box::new(Query {
+            with: None,
+            body: Box::new(SetExpr::Values(Values {
+                explicit_row: false,
+                rows,
+            })),
+            order_by: None,
+            limit: None,
+            limit_by: vec![],
+            offset: None,
+            fetch: None,
+            locks: vec![],
+            for_clause: None,
+            settings: None,
+            format_clause: None,
+        })),
+        partitioned: None,
+        after_columns: vec![],
+        table: false,
+        on: None,
+        returning: None,
+        replace_into: false,
+        priority: None,
+        insert_alias: None,
+    }))
+}
+
+/// an empty field is NULL, anything else is a literal of the column type
+#[allow(clippy::result_large_err)]
+fn to_literal(field: &str, data_type: &DataType) -> SqlResult<Expr> {
+    if field.is_empty() {
+        return Ok(Expr::Value(SqlValue::Null));
+    }
+    let invalid = || SqlError::StorageMsg(format!("invalid {:?} value: {}", data_type, field));
+    Ok(match data_type {
+        DataType::Int | DataType::Float => Expr::Value(SqlValue::Number(
+            field.parse().map_err(|_| invalid())?,
+            false,
+        )),
+        DataType::Boolean => Expr::Value(SqlValue::Boolean(
+            field.to_lowercase().parse().map_err(|_| invalid())?,
+        )),
+        DataType::Date => Expr::TypedString {
+            data_type: SqlDataType::Date,
+            value: field.to_string(),
+        },
+        _ => Expr::Value(SqlValue::SingleQuotedString(field.to_string())),
+    })
+}
@@ -0,0 +1,145 @@
+use std::{fs, path::PathBuf, str::FromStr};
+
+use anyhow::{anyhow, Context, Error, Result};
+use chrono::NaiveDate;
+use gluesql::core::{
+    ast::{ColumnDef, DataType},
+    data::{Schema, Value},
+};
+
+/// `name=path.csv` given to `--mount-csv`
+#[derive(Debug, Clone)]
+pub struct Mount {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl FromStr for Mount {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((name, path)) if !name.is_empty() && !path.is_empty() => Ok(Self {
+                name: name.to_string(),
+                path: PathBuf::from(path),
+            }),
+            _ => Err(anyhow!("Expected `name=path.csv`: {}", s)),
+        }
+    }
+}
+
+impl Mount {
+    /// read the file into a table whose columns are named by the header record
+    pub fn load(&self) -> Result<(Schema, Vec<Vec<Value>>)> {
+        let text = fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read {}", self.path.display()))?;
+        let mut records = parse(&text)
+            .with_context(|| format!("Failed to parse {}", self.path.display()))?
+            .into_iter();
+        let header = records
+            .next()
+            .ok_or_else(|| anyhow!("{} has no header", self.path.display()))?;
+        let records: Vec<_> = records.collect();
+        let data_types: Vec<_> = (0..header.len())
+            .map(|i| infer_type(records.iter().filter_map(|record| record.get(i))))
+            .collect();
+        let column_defs = header
+            .into_iter()
+            .zip(&data_types)
+            .map(|(name, data_type)| ColumnDef {
+                name,
+                data_type: data_type.clone(),
+                nullable: true,
+                default: None,
+                unique: None,
+                comment: None,
+            })
+            .collect();
+        let rows = records
+            .into_iter()
+            .map(|record| {
+                data_types
+                    .iter()
+                    .enumerate()
+                    .map(|(i, data_type)| match record.get(i) {
+                        Some(s) => to_value(s, data_type),
+                        None => Value::Null,
+                    })
+                    .collect()
+            })
+            .collect();
+        let schema = Schema {
+            table_name: self.name.clone(),
+            column_defs: Some(column_defs),
+            indexes: vec![],
+            engine: None,
+            foreign_keys: vec![],
+            comment: None,
+        };
+        Ok((schema, rows))
+    }
+}
+
+/// the narrowest of INTEGER, FLOAT, DATE and TEXT that every non-empty value fits
+fn infer_type<'a>(values: impl Iterator<Item = &'a String> + Clone) -> DataType {
+    let mut values = values.filter(|s| !s.is_empty());
+    if values.clone().all(|s| s.parse::<i64>().is_ok()) {
+        DataType::Int
+    } else if values.clone().all(|s| s.parse::<f64>().is_ok()) {
+        DataType::Float
+    } else if values.all(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok()) {
+        DataType::Date
+    } else {
+        DataType::Text
+    }
+}
+
+fn to_value(s: &str, data_type: &DataType) -> Value {
+    if s.is_empty() {
+        return Value::Null;
+    }
+    match data_type {
+        DataType::Int => s.parse().map(Value::I64).unwrap_or(Value::Null),
+        DataType::Float => s.parse().map(Value::F64).unwrap_or(Value::Null),
+        DataType::Date => NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .map(Value::Date)
+            .unwrap_or(Value::Null),
+        _ => Value::Str(s.to_string()),
+    }
+}
+
+/// parse RFC 4180 CSV: fields may be quoted, `""` escapes a quote inside quotes
+pub fn parse(text: &str) -> Result<Vec<Vec<String>>> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut chars = text.chars().peekable();
+    let mut in_quotes = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes => {
+                if chars.next_if_eq(&'"').is_some() {
+                    field.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            }
+            '"' if field.is_empty() => in_quotes = true,
+            ',' if !in_quotes => record.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes && chars.peek() == Some(&'\n') => {}
+            '\n' if !in_quotes => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(anyhow!("Unterminated quoted field"));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
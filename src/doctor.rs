@@ -0,0 +1,175 @@
+use std::process::Command;
+
+use anyhow::{anyhow, Result};
+
+use crate::{gh, storage::ProjectNextStorage};
+
+pub struct Opt {
+    pub owner: String,
+    /// the project to check access to, if given
+    pub project_number: Option<i64>,
+}
+
+/// the result of a check, with what to do about a failure
+enum Outcome {
+    Ok(String),
+    Warn(String, &'static str),
+    Fail(String, String),
+}
+
+/// check gh, the authentication, the API and the project, printing a line per check and
+/// how to fix the failed ones
+pub fn run(opt: Opt, storage: impl FnOnce(i64) -> Result<ProjectNextStorage>) -> Result<()> {
+    let mut failed = 0;
+    match gh::token_variable() {
+        Some(variable) => {
+            let detail = format!("not used, requests are sent with {}", variable);
+            report("gh", Outcome::Ok(detail));
+        }
+        None => {
+            failed += report("gh", check_gh_version());
+            failed += report("auth", check_gh_auth());
+            // the following checks can't pass without gh
+            if failed > 0 {
+                return Err(anyhow!("{} check(s) failed", failed));
+            }
+        }
+    }
+
+    let probe = match gh::probe() {
+        Ok(probe) => probe,
+        Err(e) => {
+            report(
+                "api",
+                Outcome::Fail(
+                    format!("{:#}", e),
+                    "check the network, HTTPS_PROXY, --ca-cert and that the token is valid"
+                        .to_string(),
+                ),
+            );
+            return Err(anyhow!("{} check(s) failed", failed + 1));
+        }
+    };
+    let detail = format!(
+        "logged in as {} ({} ms)",
+        probe.login,
+        probe.elapsed.as_millis()
+    );
+    report("api", Outcome::Ok(detail));
+    let scopes = match &probe.scopes {
+        None => Outcome::Warn(
+            "not reported by the token".to_string(),
+            "fine-grained and app tokens need read (and write) access to projects",
+        ),
+        Some(scopes) if probe.has_project_scope => Outcome::Ok(scopes.clone()),
+        Some(scopes) => Outcome::Fail(format!("no project scope in {}", scopes), gh::scope_hint()),
+    };
+    failed += report("scopes", scopes);
+    failed += report("schema", check_schema());
+
+    if let Some(project_number) = opt.project_number {
+        let name = format!("project {}/{}", opt.owner, project_number);
+        let outcome = match storage(project_number).and_then(|storage| storage.check_access()) {
+            Ok((title, fields)) => Outcome::Ok(format!("\"{}\" with {} field(s)", title, fields)),
+            Err(e) => Outcome::Fail(
+                format!("{:#}", e),
+                "check OWNER and PROJECT_NUMBER, and that the token can see the project (authorize it for SSO if the organization requires)".to_string(),
+            ),
+        };
+        failed += report(&name, outcome);
+    }
+
+    if failed > 0 {
+        return Err(anyhow!("{} check(s) failed", failed));
+    }
+    Ok(())
+}
+
+/// print the outcome, returning 1 if it failed
+fn report(name: &str, outcome: Outcome) -> usize {
+    match outcome {
+        Outcome::Ok(detail) => {
+            println!("ok    {}: {}", name, detail);
+            0
+        }
+        Outcome::Warn(detail, fix) => {
+            println!("warn  {}: {}", name, detail);
+            println!("      {}", fix);
+            0
+        }
+        Outcome::Fail(detail, fix) => {
+            println!("FAIL  {}: {}", name, detail);
+            println!("      {}", fix);
+            1
+        }
+    }
+}
+
+/// whether GitHub has kinds of fields newer than those gh-sql queries
+fn check_schema() -> Outcome {
+    match gh::unknown_kinds() {
+        Ok(kinds) if kinds.is_empty() => Outcome::Ok("every kind of field is known".to_string()),
+        Ok(kinds) => Outcome::Warn(
+            format!("unknown kind(s) of field: {}", kinds.join(", ")),
+            "their values are read as raw text, update gh-sql to read them as they are",
+        ),
+        Err(e) => Outcome::Warn(
+            format!("failed to introspect: {:#}", e),
+            "the kinds of fields newer than gh-sql are read as raw text",
+        ),
+    }
+}
+
+fn check_gh_version() -> Outcome {
+    match Command::new("gh").arg("--version").output() {
+        Ok(output) if output.status.success() => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            Outcome::Ok(stdout.lines().next().unwrap_or_default().to_string())
+        }
+        Ok(output) => Outcome::Fail(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            "reinstall gh from https://cli.github.com".to_string(),
+        ),
+        Err(e) => Outcome::Fail(
+            e.to_string(),
+            "install gh from https://cli.github.com, or set GH_TOKEN to use gh-sql without it"
+                .to_string(),
+        ),
+    }
+}
+
+fn check_gh_auth() -> Outcome {
+    let mut command = Command::new("gh");
+    command.args(["auth", "status"]);
+    if let Some(hostname) = gh::hostname() {
+        command.args(["--hostname", hostname]);
+    }
+    let output = match command.output() {
+        Ok(output) => output,
+        Err(e) => return Outcome::Fail(e.to_string(), "install gh first".to_string()),
+    };
+    // older versions print the status to stderr
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    // `gh` lists every account of the host, the active one first
+    let account = text
+        .lines()
+        .map(str::trim)
+        .find(|line| {
+            line.contains("Logged in to")
+                && gh::account()
+                    .is_none_or(|account| line.contains(&format!("account {} ", account)))
+        })
+        .map(|line| line.trim_start_matches(|c: char| !c.is_alphanumeric()));
+    match (output.status.success(), account) {
+        (true, Some(account)) => Outcome::Ok(account.to_string()),
+        (true, None) => Outcome::Ok("logged in".to_string()),
+        (false, _) => Outcome::Fail(
+            text.lines().next().unwrap_or_default().trim().to_string(),
+            "run `gh auth login -s project`".to_string(),
+        ),
+    }
+}
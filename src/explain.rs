@@ -0,0 +1,253 @@
+use anyhow::Result;
+use gluesql::core::{
+    data::Value,
+    executor::Payload,
+    prelude::Glue,
+    sqlparser::ast::{
+        Delete, Expr, Insert, Query, SetExpr, Statement, TableFactor, TableWithJoins,
+    },
+    store::{GStore, GStoreMut, Planner},
+};
+
+use crate::{functions, output::value_to_string, sql, storage};
+
+/// rows of `step` and `detail` telling what `statement` would do;
+/// only the reads needed to find the affected rows are executed
+pub fn explain<S>(glue: &mut Glue<S>, statement: &Statement) -> Result<Payload>
+where
+    S: GStore + GStoreMut + Planner,
+{
+    let mut rows = vec![];
+    let mut step = |step: &str, detail: String| {
+        rows.push(vec![Value::Str(step.to_string()), Value::Str(detail)]);
+    };
+
+    let mut tables = vec![];
+    let mut filtered = false;
+    let mut limited = false;
+    match statement {
+        Statement::Query(query) => {
+            limited = query.limit.is_some();
+            query_tables(query, &mut tables, &mut filtered);
+        }
+        Statement::Insert(Insert {
+            source: Some(source),
+            ..
+        }) => query_tables(source, &mut tables, &mut filtered),
+        Statement::Update { selection, .. } | Statement::Delete(Delete { selection, .. }) => {
+            filtered = selection.is_some();
+        }
+        _ => {}
+    }
+    let mut statement_copy = statement.clone();
+    functions::visit_exprs(&mut statement_copy, &mut |expr| {
+        if let Expr::Subquery(query)
+        | Expr::Exists {
+            subquery: query, ..
+        }
+        | Expr::InSubquery {
+            subquery: query, ..
+        } = expr
+        {
+            query_tables(query, &mut tables, &mut filtered);
+        }
+        Ok(())
+    })?;
+    let table_name = sql::target_table(statement).unwrap_or_default();
+    if !table_name.is_empty() {
+        tables.push(table_name.clone());
+    }
+    let tables = tables
+        .iter()
+        .enumerate()
+        .filter(|(i, table)| !tables[..*i].contains(table))
+        .map(|(_, table)| table);
+    for table in tables {
+        step("scan", format!("{}: {}", table, storage::fetch_plan(table)));
+    }
+    if filtered {
+        step(
+            "filter",
+            "evaluated locally after the scan, nothing is pushed down to GitHub".to_string(),
+        );
+    }
+    if limited {
+        step("limit", "applied locally after the scan".to_string());
+    }
+
+    match statement {
+        Statement::Update {
+            assignments,
+            selection,
+            ..
+        } => {
+            let columns: Vec<_> = assignments
+                .iter()
+                .map(|assignment| format!("{}, {}", assignment.target, assignment.value))
+                .collect();
+            let (_, matched) = sql::select(
+                glue,
+                &format!(
+                    "SELECT {}{} FROM {}{}",
+                    key_column(&table_name),
+                    columns.join(", "),
+                    table_name,
+                    where_clause(selection)
+                ),
+            )?;
+            let mut changes = 0;
+            for (i, row) in matched.iter().enumerate() {
+                let (key, values) = split_key(&table_name, i, row);
+                for (assignment, pair) in assignments.iter().zip(values.chunks(2)) {
+                    if let [old, new] = pair {
+                        if old == new || (old.is_null() && new.is_null()) {
+                            continue;
+                        }
+                        changes += 1;
+                        step(
+                            "update",
+                            format!(
+                                "{}: {}: {} -> {}",
+                                key,
+                                assignment.target,
+                                value_to_string(old),
+                                value_to_string(new)
+                            ),
+                        );
+                    }
+                }
+            }
+            step("requests", requests(&table_name, changes));
+        }
+        Statement::Delete(Delete { selection, .. }) => {
+            let (_, matched) = sql::select(
+                glue,
+                &format!(
+                    "SELECT {}* FROM {}{}",
+                    key_column(&table_name),
+                    table_name,
+                    where_clause(selection)
+                ),
+            )?;
+            for (i, row) in matched.iter().enumerate() {
+                let (key, _) = split_key(&table_name, i, row);
+                step("delete", key);
+            }
+            step("requests", requests(&table_name, matched.len()));
+        }
+        Statement::Insert(Insert {
+            source: Some(source),
+            ..
+        }) => {
+            let inserted: Vec<_> = match &*source.body {
+                SetExpr::Values(values) => values
+                    .rows
+                    .iter()
+                    .map(|row| {
+                        let row: Vec<_> = row.iter().map(ToString::to_string).collect();
+                        row.join(", ")
+                    })
+                    .collect(),
+                _ => {
+                    let (_, rows) = sql::select(glue, &source.to_string())?;
+                    rows.iter()
+                        .map(|row| {
+                            let row: Vec<_> = row.iter().map(value_to_string).collect();
+                            row.join(", ")
+                        })
+                        .collect()
+                }
+            };
+            let count = inserted.len();
+            for row in inserted {
+                step("insert", row);
+            }
+            step("requests", requests(&table_name, count));
+        }
+        Statement::Query(_) => {}
+        _ => step(
+            "execute",
+            "not explained for this kind of statement".to_string(),
+        ),
+    }
+
+    Ok(Payload::Select {
+        labels: vec!["step".to_string(), "detail".to_string()],
+        rows,
+    })
+}
+
+/// rows of project tables are told by their `id`, other rows by their position
+fn key_column(table_name: &str) -> &'static str {
+    if storage::is_project_table(table_name) {
+        "id, "
+    } else {
+        ""
+    }
+}
+
+fn split_key<'a>(table_name: &str, i: usize, row: &'a [Value]) -> (String, &'a [Value]) {
+    if storage::is_project_table(table_name) {
+        (value_to_string(&row[0]), &row[1..])
+    } else {
+        (format!("row {}", i + 1), row)
+    }
+}
+
+fn where_clause(selection: &Option<Expr>) -> String {
+    selection
+        .as_ref()
+        .map_or_else(String::new, |selection| format!(" WHERE {}", selection))
+}
+
+/// estimate of the mutations sent for `count` changed values or rows
+fn requests(table_name: &str, count: usize) -> String {
+    match table_name {
+        "items" => format!("{} mutation(s), one per changed value or item", count),
+        "options" | "project" => format!(
+            "up to {} mutation(s), one per single select field or the project",
+            count
+        ),
+        _ => "none, the table lives in memory".to_string(),
+    }
+}
+
+/// names of the tables in `FROM`, including joins and derived tables
+fn query_tables(query: &Query, tables: &mut Vec<String>, filtered: &mut bool) {
+    fn set_expr_tables(body: &SetExpr, tables: &mut Vec<String>, filtered: &mut bool) {
+        match body {
+            SetExpr::Select(select) => {
+                *filtered |= select.selection.is_some() || select.having.is_some();
+                for TableWithJoins { relation, joins } in &select.from {
+                    factor_tables(relation, tables, filtered);
+                    for join in joins {
+                        factor_tables(&join.relation, tables, filtered);
+                    }
+                }
+            }
+            SetExpr::Query(query) => set_expr_tables(&query.body, tables, filtered),
+            SetExpr::SetOperation { left, right, .. } => {
+                set_expr_tables(left, tables, filtered);
+                set_expr_tables(right, tables, filtered);
+            }
+            _ => {}
+        }
+    }
+    fn factor_tables(relation: &TableFactor, tables: &mut Vec<String>, filtered: &mut bool) {
+        match relation {
+            TableFactor::Table { name, .. } => tables.push(name.to_string()),
+            TableFactor::Derived { subquery, .. } => query_tables(subquery, tables, filtered),
+            TableFactor::NestedJoin {
+                table_with_joins: table,
+                ..
+            } => {
+                factor_tables(&table.relation, tables, filtered);
+                for join in &table.joins {
+                    factor_tables(&join.relation, tables, filtered);
+                }
+            }
+            _ => {}
+        }
+    }
+    set_expr_tables(&query.body, tables, filtered)
+}
@@ -0,0 +1,286 @@
+use anyhow::{anyhow, Result};
+use gluesql::core::{
+    ast::{ColumnDef, DataType},
+    sqlparser::ast::{
+        BinaryOperator, Expr, Function, FunctionArg, FunctionArgExpr, FunctionArgumentList,
+        FunctionArguments, Ident, ObjectName, UnaryOperator, Value as SqlValue,
+    },
+};
+
+/// one qualifier of a filter like `-status:Done,Todo`
+#[derive(Debug)]
+struct Qualifier {
+    negated: bool,
+    key: Option<String>,
+    values: Vec<String>,
+}
+
+/// translate GitHub's Projects filter syntax (eg. `assignee:@me label:bug -status:Done`)
+/// into a condition over the columns of `items`
+///
+/// `@me`, `@current` and `@today` are left as `USER()`, `CURRENT_ITERATION(...)` and `TODAY()` calls.
+/// `list_len` gives the length of the longest list of a list column, to compare each element.
+pub fn parse(
+    filter: &str,
+    columns: &[ColumnDef],
+    list_len: &mut dyn FnMut(&str) -> Result<usize>,
+) -> Result<Expr> {
+    let conditions = tokenize(filter)?
+        .into_iter()
+        .map(|qualifier| {
+            let condition = qualifier_to_expr(&qualifier, columns, list_len)?;
+            Ok(if qualifier.negated {
+                not(condition)
+            } else {
+                condition
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(conditions
+        .into_iter()
+        .reduce(|l, r| binary(l, BinaryOperator::And, r))
+        .unwrap_or(Expr::Value(SqlValue::Boolean(true))))
+}
+
+fn tokenize(filter: &str) -> Result<Vec<Qualifier>> {
+    let mut qualifiers = vec![];
+    let mut chars = filter.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            return Ok(qualifiers);
+        }
+        let negated = chars.next_if_eq(&'-').is_some();
+        let mut key = None;
+        let mut values = vec![String::new()];
+        let mut in_quotes = false;
+        while let Some(c) = chars.next_if(|c| in_quotes || !c.is_whitespace()) {
+            match c {
+                '"' => in_quotes = !in_quotes,
+                ':' if !in_quotes && key.is_none() => key = values.pop(),
+                ',' if !in_quotes && key.is_some() => values.push(String::new()),
+                c => values.last_mut().unwrap().push(c),
+            }
+            if values.is_empty() {
+                values.push(String::new());
+            }
+        }
+        if in_quotes {
+            return Err(anyhow!("Unterminated quote in filter: {}", filter));
+        }
+        values.retain(|value| !value.is_empty());
+        if values.is_empty() {
+            return Err(anyhow!("Missing value in filter: {}", filter));
+        }
+        qualifiers.push(Qualifier {
+            negated,
+            key,
+            values,
+        });
+    }
+}
+
+fn qualifier_to_expr(
+    qualifier: &Qualifier,
+    columns: &[ColumnDef],
+    list_len: &mut dyn FnMut(&str) -> Result<usize>,
+) -> Result<Expr> {
+    let key = match &qualifier.key {
+        Some(key) => key.to_lowercase(),
+        None => {
+            // free text matches titles
+            let text = qualifier.values.join(",");
+            return Ok(Expr::ILike {
+                negated: false,
+                any: false,
+                expr: Box::new(ident("Title")),
+                pattern: Box::new(string(format!("%{}%", text))),
+                escape_char: None,
+            });
+        }
+    };
+    let alternatives = qualifier
+        .values
+        .iter()
+        .map(|value| match key.as_str() {
+            "no" => is_empty(find_column(columns, value)?),
+            "has" => Ok(not(is_empty(find_column(columns, value)?)?)),
+            "is" => match value.to_lowercase().as_str() {
+                "draft" => Ok(Expr::IsNull(Box::new(ident("Repository")))),
+                state @ ("open" | "closed" | "merged") => Ok(binary(
+                    ident("State"),
+                    BinaryOperator::Eq,
+                    string(state.to_uppercase()),
+                )),
+                other => Err(anyhow!("Unsupported filter: is:{}", other)),
+            },
+            "repo" => matches(find_column(columns, "Repository")?, value, list_len),
+            key => matches(find_column(columns, key)?, value, list_len),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(any(alternatives))
+}
+
+/// find the column which `key` refers to, ignoring case, spaces, `-`, `_` and a plural `s`
+fn find_column<'a>(columns: &'a [ColumnDef], key: &str) -> Result<&'a ColumnDef> {
+    fn normalize(s: &str) -> String {
+        s.chars()
+            .filter(|c| !matches!(c, ' ' | '-' | '_'))
+            .flat_map(char::to_lowercase)
+            .collect()
+    }
+    let key = normalize(key);
+    let plural = format!("{}s", key);
+    columns
+        .iter()
+        .find(|column| normalize(&column.name) == key)
+        .or_else(|| {
+            columns
+                .iter()
+                .find(|column| normalize(&column.name) == plural)
+        })
+        .ok_or_else(|| anyhow!("Unknown field in filter: {}", key))
+}
+
+fn is_empty(column: &ColumnDef) -> Result<Expr> {
+    let expr = ident(&column.name);
+    Ok(match column.data_type {
+        DataType::List => Expr::IsNull(Box::new(element(expr, 0))),
+        _ => Expr::IsNull(Box::new(expr)),
+    })
+}
+
+fn matches(
+    column: &ColumnDef,
+    value: &str,
+    list_len: &mut dyn FnMut(&str) -> Result<usize>,
+) -> Result<Expr> {
+    let expr = ident(&column.name);
+    match column.data_type {
+        DataType::List => {
+            let value = placeholder(column, value);
+            Ok(any_element(&expr, list_len(&column.name)?, |element| {
+                lower_eq(element, value.clone())
+            }))
+        }
+        DataType::Float | DataType::Int | DataType::Date => {
+            let literal = |value: &str| -> Result<Expr> {
+                match column.data_type {
+                    DataType::Date => Ok(placeholder(column, value)),
+                    _ => value
+                        .parse()
+                        .map(|number| Expr::Value(SqlValue::Number(number, false)))
+                        .map_err(|_| anyhow!("{} expects a number: {}", column.name, value)),
+                }
+            };
+            if let Some((low, high)) = value.split_once("..") {
+                return Ok(Expr::Between {
+                    expr: Box::new(expr),
+                    negated: false,
+                    low: Box::new(literal(low)?),
+                    high: Box::new(literal(high)?),
+                });
+            }
+            let (op, value) = [
+                (">=", BinaryOperator::GtEq),
+                ("<=", BinaryOperator::LtEq),
+                (">", BinaryOperator::Gt),
+                ("<", BinaryOperator::Lt),
+            ]
+            .into_iter()
+            .find_map(|(prefix, op)| value.strip_prefix(prefix).map(|value| (op, value)))
+            .unwrap_or((BinaryOperator::Eq, value));
+            Ok(binary(expr, op, literal(value)?))
+        }
+        _ => Ok(lower_eq(expr, placeholder(column, value))),
+    }
+}
+
+/// expand `@me`, `@current` and `@today` to functions, anything else is a string literal
+fn placeholder(column: &ColumnDef, value: &str) -> Expr {
+    match value.to_lowercase().as_str() {
+        "@me" => call("USER", vec![]),
+        "@current" => call("CURRENT_ITERATION", vec![string(&column.name)]),
+        "@today" => call("TODAY", vec![]),
+        _ => string(value),
+    }
+}
+
+/// `f(list[0]) OR ... OR f(list[len - 1])`, elements out of range are NULL but the
+/// condition is false rather than NULL if none matches, so that it can be negated
+pub fn any_element(list: &Expr, len: usize, f: impl Fn(Expr) -> Expr) -> Expr {
+    any((0..len).map(|i| f(element(list.clone(), i))).collect())
+}
+
+pub fn element(list: Expr, i: usize) -> Expr {
+    call("UNWRAP", vec![list, string(i.to_string())])
+}
+
+fn lower_eq(left: Expr, right: Expr) -> Expr {
+    binary(
+        call("LOWER", vec![left]),
+        BinaryOperator::Eq,
+        call("LOWER", vec![right]),
+    )
+}
+
+/// `OR` of the conditions, each of which is false where it is NULL, as GlueSQL makes
+/// `TRUE OR NULL` NULL
+fn any(exprs: Vec<Expr>) -> Expr {
+    exprs
+        .into_iter()
+        .map(or_false)
+        .reduce(|l, r| binary(l, BinaryOperator::Or, r))
+        .unwrap_or(Expr::Value(SqlValue::Boolean(false)))
+}
+
+/// `NOT condition`, which holds for the items whose column is empty as well
+fn not(expr: Expr) -> Expr {
+    Expr::UnaryOp {
+        op: UnaryOperator::Not,
+        expr: Box::new(or_false(expr)),
+    }
+}
+
+/// the condition, or false where it is NULL, e.g. compares a NULL column
+fn or_false(expr: Expr) -> Expr {
+    call(
+        "COALESCE",
+        vec![expr, Expr::Value(SqlValue::Boolean(false))],
+    )
+}
+
+pub fn binary(left: Expr, op: BinaryOperator, right: Expr) -> Expr {
+    Expr::BinaryOp {
+        left: Box::new(left),
+        op,
+        right: Box::new(right),
+    }
+}
+
+fn call(name: &str, args: Vec<Expr>) -> Expr {
+    Expr::Function(Function {
+        name: ObjectName(vec![Ident::new(name)]),
+        parameters: FunctionArguments::None,
+        args: FunctionArguments::List(FunctionArgumentList {
+            duplicate_treatment: None,
+            args: args
+                .into_iter()
+                .map(|arg| FunctionArg::Unnamed(FunctionArgExpr::Expr(arg)))
+                .collect(),
+            clauses: vec![],
+        }),
+        filter: None,
+        null_treatment: None,
+        over: None,
+        within_group: vec![],
+    })
+}
+
+fn ident(name: &str) -> Expr {
+    Expr::Identifier(Ident::new(name))
+}
+
+pub fn string(s: impl Into<String>) -> Expr {
+    Expr::Value(SqlValue::SingleQuotedString(s.into()))
+}
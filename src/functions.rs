@@ -0,0 +1,347 @@
+use std::{collections::BTreeSet, ops::ControlFlow, sync::OnceLock};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use gluesql::core::{
+    ast::DataType as ColumnType,
+    data::Value,
+    sqlparser::ast::{
+        visit_expressions_mut, visit_relations, BinaryOperator, DataType, Expr, Function,
+        FunctionArg, FunctionArgExpr, FunctionArgumentList, FunctionArguments, Statement,
+        Value as SqlValue, VisitMut,
+    },
+    store::Store,
+};
+use regex::Regex;
+
+use crate::{
+    filter::{self, any_element, binary, element, string},
+    gh::{self, GraphQLResponse},
+};
+
+#[allow(warnings)]
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/viewer_login.rs"));
+}
+
+/// GlueSQL has no user-defined functions, so the functions gh-sql provides are
+/// expanded into plain SQL before the statement is translated
+pub fn expand<S: Store>(storage: &S, statement: &mut Statement) -> Result<()> {
+    let tables = table_names(statement);
+    visit_exprs(statement, &mut |expr| expand_expr(storage, &tables, expr))
+}
+
+fn expand_expr<S: Store>(storage: &S, tables: &BTreeSet<String>, expr: &mut Expr) -> Result<()> {
+    let function = match expr {
+        Expr::Function(function) => function,
+        _ => return Ok(()),
+    };
+    let name = function.name.to_string().to_uppercase();
+    let expanded = match name.as_str() {
+        "CURRENT_ITERATION" => {
+            let [field] = string_args(&name, function)?;
+            let title = current_iteration(storage, &field)?;
+            Expr::Value(SqlValue::SingleQuotedString(title))
+        }
+        "GH_FILTER" => {
+            let [filter] = string_args(&name, function)?;
+            let schema = storage
+                .fetch_schema("items")
+                .map_err(|e| anyhow!("{}", e))?
+                .ok_or_else(|| anyhow!("Table not found: items"))?;
+            let items = BTreeSet::from(["items".to_string()]);
+            let mut list_len = |column: &str| max_list_len(storage, &items, column);
+            let column_defs = schema.column_defs.unwrap_or_default();
+            let mut condition = filter::parse(&filter, &column_defs, &mut list_len)
+                .with_context(|| format!("failed to parse filter: {}", filter))?;
+            visit_exprs(&mut condition, &mut |expr| {
+                expand_expr(storage, tables, expr)
+            })?;
+            Expr::Nested(Box::new(condition))
+        }
+        "HAS" => {
+            let [list, value] = exact_args(&name, function)?;
+            let len = max_list_len(storage, tables, list_column(&name, &list)?)?;
+            any_element(&list, len, |element| {
+                binary(element, BinaryOperator::Eq, value.clone())
+            })
+        }
+        "LIST_LEN" => {
+            let [list] = exact_args(&name, function)?;
+            let len = max_list_len(storage, tables, list_column(&name, &list)?)?;
+            list_len(list, len)
+        }
+        "ANY_MATCH" => {
+            let [list, pattern] = exact_args(&name, function)?;
+            let pattern = pattern_literal(&name, pattern)?;
+            let column = list_column(&name, &list)?;
+            let matched = matching_elements(storage, tables, column, &pattern)?;
+            if matched.is_empty() {
+                Expr::Value(SqlValue::Boolean(false))
+            } else {
+                let len = max_list_len(storage, tables, column)?;
+                any_element(&list, len, |element| Expr::InList {
+                    expr: Box::new(element),
+                    list: matched.iter().map(string).collect(),
+                    negated: false,
+                })
+            }
+        }
+        "TODAY" => {
+            let [] = exact_args(&name, function)?;
+            date(Local::now().naive_local().date())
+        }
+        "START_OF_WEEK" => {
+            let [] = exact_args(&name, function)?;
+            let today = Local::now().naive_local().date();
+            date(today - Duration::days(today.weekday().num_days_from_monday().into()))
+        }
+        "DAYS_AGO" => {
+            let [days] = exact_args(&name, function)?;
+            let days = match &days {
+                Expr::Value(SqlValue::Number(n, _)) => n.to_string().parse::<i64>().ok(),
+                _ => None,
+            }
+            .ok_or_else(|| anyhow!("{} expects an integer literal, got: {}", name, days))?;
+            date(Local::now().naive_local().date() - Duration::days(days))
+        }
+        "USER" => {
+            let [] = string_args(&name, function)?;
+            Expr::Value(SqlValue::SingleQuotedString(viewer_login()?))
+        }
+        _ => return Ok(()),
+    };
+    *expr = expanded;
+    Ok(())
+}
+
+/// title of the iteration of `field` which contains today
+fn current_iteration<S: Store>(storage: &S, field: &str) -> Result<String> {
+    let rows = scan_table(
+        storage,
+        "iterations",
+        &["field_name", "title", "is_current"],
+    )?;
+    rows.into_iter()
+        .find_map(|row| match row.as_slice() {
+            [Value::Str(field_name), Value::Str(title), Value::Bool(true)]
+                if field_name == field =>
+            {
+                Some(title.clone())
+            }
+            _ => None,
+        })
+        .ok_or_else(|| anyhow!("No current iteration for field: {}", field))
+}
+
+fn date(date: NaiveDate) -> Expr {
+    Expr::TypedString {
+        data_type: DataType::Date,
+        value: date.format("%Y-%m-%d").to_string(),
+    }
+}
+
+/// `CASE WHEN list[max_len - 1] IS NOT NULL THEN max_len ... ELSE 0 END`
+fn list_len(list: Expr, max_len: usize) -> Expr {
+    if max_len == 0 {
+        return Expr::Value(SqlValue::Number(0i64.into(), false));
+    }
+    let (conditions, results) = (0..max_len)
+        .rev()
+        .map(|i| {
+            let condition = Expr::IsNotNull(Box::new(element(list.clone(), i)));
+            let result = Expr::Value(SqlValue::Number((i as i64 + 1).into(), false));
+            (condition, result)
+        })
+        .unzip();
+    Expr::Case {
+        operand: None,
+        conditions,
+        results,
+        else_result: Some(Box::new(Expr::Value(SqlValue::Number(0i64.into(), false)))),
+    }
+}
+
+/// the name of the column `list` refers to. GlueSQL can't take the length of a list, so
+/// the functions of lists compare as many elements as the longest list of the column has
+fn list_column<'a>(name: &str, list: &'a Expr) -> Result<&'a str> {
+    match list {
+        Expr::Identifier(ident) => Ok(&ident.value),
+        Expr::CompoundIdentifier(idents) if !idents.is_empty() => Ok(&idents.last().unwrap().value),
+        other => Err(anyhow!("{} expects a list column, got: {}", name, other)),
+    }
+}
+
+/// the length of the longest list of `column` in those of `tables` which have it
+fn max_list_len<S: Store>(storage: &S, tables: &BTreeSet<String>, column: &str) -> Result<usize> {
+    let lists = scan_lists(storage, tables, column)?;
+    Ok(lists.iter().map(Vec::len).max().unwrap_or(0))
+}
+
+/// every list of `column` in those of `tables` which have it as a list column
+fn scan_lists<S: Store>(
+    storage: &S,
+    tables: &BTreeSet<String>,
+    column: &str,
+) -> Result<Vec<Vec<Value>>> {
+    let mut lists = vec![];
+    for table_name in tables {
+        let schema = storage
+            .fetch_schema(table_name)
+            .map_err(|e| anyhow!("{}", e))?;
+        let is_list = schema.is_some_and(|schema| {
+            schema
+                .column_defs
+                .iter()
+                .flatten()
+                .any(|def| def.name == column && def.data_type == ColumnType::List)
+        });
+        if !is_list {
+            continue;
+        }
+        for row in scan_table(storage, table_name, &[column])? {
+            if let [Value::List(elements)] = row.as_slice() {
+                lists.push(elements.clone());
+            }
+        }
+    }
+    Ok(lists)
+}
+
+/// the tables `statement` reads or writes, including those of its subqueries
+fn table_names(statement: &Statement) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    let _ = visit_relations(statement, |name| {
+        names.insert(name.to_string());
+        ControlFlow::<()>::Continue(())
+    });
+    names
+}
+
+fn pattern_literal(name: &str, pattern: Expr) -> Result<String> {
+    match pattern {
+        Expr::Value(SqlValue::SingleQuotedString(pattern)) => Ok(pattern),
+        other => Err(anyhow!(
+            "{} expects a string literal pattern, got: {}",
+            name,
+            other
+        )),
+    }
+}
+
+/// GlueSQL has no regular expressions, so the distinct elements of the lists of `column`
+/// which match `pattern` are looked up in advance
+fn matching_elements<S: Store>(
+    storage: &S,
+    tables: &BTreeSet<String>,
+    column: &str,
+    pattern: &str,
+) -> Result<Vec<String>> {
+    let regex = Regex::new(pattern).with_context(|| format!("Invalid pattern: {}", pattern))?;
+    let elements: BTreeSet<_> = scan_lists(storage, tables, column)?
+        .into_iter()
+        .flatten()
+        .filter_map(|element| match element {
+            Value::Str(s) if regex.is_match(&s) => Some(s),
+            _ => None,
+        })
+        .collect();
+    Ok(elements.into_iter().collect())
+}
+
+/// login of the authenticated user, fetched once per process
+pub fn viewer_login() -> Result<String> {
+    use generated::viewer_login::*;
+    static LOGIN: OnceLock<String> = OnceLock::new();
+    if let Some(login) = LOGIN.get() {
+        return Ok(login.clone());
+    }
+    let query = include_str!("viewer_login.graphql");
+    let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &Variables {})?;
+    if !resp.errors.errors.is_empty() {
+        return Err(anyhow!("{}", resp.errors.error_msgs()).context("failed to fetch viewer"));
+    }
+    Ok(LOGIN.get_or_init(|| resp.data.viewer.login).clone())
+}
+
+/// scan `table_name` and pick `columns` from every row
+fn scan_table<S: Store>(
+    storage: &S,
+    table_name: &str,
+    columns: &[&str],
+) -> Result<Vec<Vec<Value>>> {
+    let schema = storage
+        .fetch_schema(table_name)
+        .map_err(|e| anyhow!("{}", e))?
+        .ok_or_else(|| anyhow!("Table not found: {}", table_name))?;
+    let indexes = columns
+        .iter()
+        .map(|column| {
+            schema
+                .column_defs
+                .iter()
+                .flatten()
+                .position(|def| &def.name == column)
+                .with_context(|| format!("Column not found: {}.{}", table_name, column))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let rows = storage
+        .scan_data(table_name)
+        .map_err(|e| anyhow!("{}", e))?;
+    rows.map(|row| {
+        let (_, values) = row.map_err(|e| anyhow!("{}", e))?;
+        Ok(indexes.iter().map(|&i| values[i].clone()).collect())
+    })
+    .collect()
+}
+
+fn args(name: &str, function: &Function) -> Result<Vec<Expr>> {
+    let args = match &function.args {
+        FunctionArguments::None => return Ok(vec![]),
+        FunctionArguments::Subquery(_) => {
+            return Err(anyhow!("{} does not take a subquery", name));
+        }
+        FunctionArguments::List(FunctionArgumentList { args, .. }) => args,
+    };
+    args.iter()
+        .map(|arg| match arg {
+            FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)) => Ok(expr.clone()),
+            FunctionArg::Unnamed(_) => Err(anyhow!("{} does not take wildcards", name)),
+            FunctionArg::Named { .. } => Err(anyhow!("{} does not take named arguments", name)),
+        })
+        .collect()
+}
+
+fn exact_args<const N: usize>(name: &str, function: &Function) -> Result<[Expr; N]> {
+    let args = args(name, function)?;
+    let len = args.len();
+    args.try_into()
+        .map_err(|_| anyhow!("{} takes {} argument(s) but {} given", name, N, len))
+}
+
+fn string_args<const N: usize>(name: &str, function: &Function) -> Result<[String; N]> {
+    let args: [Expr; N] = exact_args(name, function)?;
+    let strings = args
+        .into_iter()
+        .map(|arg| match arg {
+            Expr::Value(SqlValue::SingleQuotedString(s)) => Ok(s),
+            other => Err(anyhow!("{} expects string literals, got: {}", name, other)),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(strings.try_into().unwrap())
+}
+
+pub type Visitor<'a> = dyn FnMut(&mut Expr) -> Result<()> + 'a;
+
+/// call `f` on every expression of `node`, including those in subqueries. Children are
+/// visited first so that `f` sees already expanded arguments
+pub fn visit_exprs<V: VisitMut>(node: &mut V, f: &mut Visitor) -> Result<()> {
+    let flow = visit_expressions_mut(node, |expr| match f(expr) {
+        Ok(()) => ControlFlow::Continue(()),
+        Err(e) => ControlFlow::Break(e),
+    });
+    match flow {
+        ControlFlow::Continue(()) => Ok(()),
+        ControlFlow::Break(e) => Err(e),
+    }
+}
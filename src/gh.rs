@@ -1,11 +1,844 @@
 use anyhow::{anyhow, Context, Result};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
+use crate::cassette::{self, Cassette};
+
 use std::{
-    io::Write,
-    process::{Command, Stdio},
+    cell::Cell,
+    env, fmt, fs,
+    io::{Read, Write},
+    ops::Sub,
+    path::{Path, PathBuf},
+    process::{Child, Command, Output, Stdio},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex, OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
+/// set by Ctrl-C once [`trap_interrupt`] is called, checked around every request
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// make Ctrl-C abort the running statement at its next request instead of exiting
+#[cfg(unix)]
+pub fn trap_interrupt() {
+    extern "C" fn on_interrupt(_: libc::c_int) {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+    }
+    // SAFETY: the handler only stores to an atomic, which is async-signal-safe
+    unsafe {
+        libc::signal(
+            libc::SIGINT,
+            on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        );
+    }
+}
+
+#[cfg(windows)]
+pub fn trap_interrupt() {
+    // the console control handler runs on a thread of its own; a second call fails to set
+    // it again, which leaves the first one in place
+    let _ = ctrlc::set_handler(|| INTERRUPTED.store(true, Ordering::SeqCst));
+}
+
+pub fn clear_interrupt() {
+    INTERRUPTED.store(false, Ordering::SeqCst);
+}
+
+/// the time a statement may take, e.g. `90s`, `5m`, `1h` or `500ms`, and seconds without a
+/// unit
+#[derive(Debug, Clone, Copy)]
+pub struct Timeout(Duration);
+
+impl FromStr for Timeout {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let number: u64 = number
+            .parse()
+            .map_err(|_| anyhow!("Invalid timeout: {}, expected e.g. 60s", s))?;
+        let secs = |per_unit: u64| {
+            number
+                .checked_mul(per_unit)
+                .map(Duration::from_secs)
+                .ok_or_else(|| anyhow!("Timeout too long: {}", s))
+        };
+        let duration = match unit {
+            "ms" => Duration::from_millis(number),
+            "" | "s" => Duration::from_secs(number),
+            "m" => secs(60)?,
+            "h" => secs(60 * 60)?,
+            _ => {
+                return Err(anyhow!(
+                    "Unknown unit of timeout: {}, expected ms, s, m or h",
+                    unit
+                ))
+            }
+        };
+        // the deadline of a statement is this long after its start
+        if Instant::now().checked_add(duration).is_none() {
+            return Err(anyhow!("Timeout too long: {}", s));
+        }
+        if duration.is_zero() {
+            return Err(anyhow!("The timeout must be longer than 0"));
+        }
+        Ok(Self(duration))
+    }
+}
+
+impl fmt::Display for Timeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let millis = self.0.as_millis();
+        let (unit, per_unit) = [("h", 3_600_000), ("m", 60_000), ("s", 1_000)]
+            .into_iter()
+            .find(|(_, per_unit)| millis.is_multiple_of(*per_unit))
+            .unwrap_or(("ms", 1));
+        write!(f, "{}{}", millis / per_unit, unit)
+    }
+}
+
+/// `--statement-timeout`, set once by [`set_statement_timeout`]
+static STATEMENT_TIMEOUT: OnceLock<Timeout> = OnceLock::new();
+
+thread_local! {
+    /// when the statement being executed on the thread times out, checked around every
+    /// request like [`INTERRUPTED`]
+    static DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+pub fn set_statement_timeout(timeout: Timeout) {
+    let _ = STATEMENT_TIMEOUT.set(timeout);
+}
+
+/// run a statement whose requests fail once `--statement-timeout` has elapsed, the
+/// statements it runs itself, like the query of `COPY`, share its deadline
+pub fn with_statement_timeout<T>(f: impl FnOnce() -> T) -> T {
+    let Some(Timeout(timeout)) = STATEMENT_TIMEOUT.get() else {
+        return f();
+    };
+    if DEADLINE.get().is_some() {
+        return f();
+    }
+    DEADLINE.set(Some(Instant::now() + *timeout));
+    let _reset = ResetDeadline;
+    f()
+}
+
+/// clears [`DEADLINE`] when the statement ends, even by a panic, so that it doesn't
+/// time out the next statements of the thread
+struct ResetDeadline;
+
+impl Drop for ResetDeadline {
+    fn drop(&mut self) {
+        DEADLINE.set(None);
+    }
+}
+
+fn timed_out() -> anyhow::Error {
+    let timeout = STATEMENT_TIMEOUT
+        .get()
+        .expect("a deadline is set with the timeout");
+    let message = format!("The statement timed out after {}", timeout);
+    RequestFailure::new(FailureKind::Timeout, message).into()
+}
+
+static REQUESTS: AtomicUsize = AtomicUsize::new(0);
+static COST: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+static CACHE_HITS: AtomicUsize = AtomicUsize::new(0);
+
+/// GitHub API usage of this process so far
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Usage {
+    pub requests: usize,
+    /// rate limit points, the reported `rateLimit.cost` or 1 for requests without it
+    pub cost: usize,
+    /// sent and received
+    pub bytes: usize,
+    /// tables read from the cache of the storage instead of fetched
+    pub cache_hits: usize,
+}
+
+impl Sub for Usage {
+    type Output = Usage;
+
+    fn sub(self, rhs: Usage) -> Usage {
+        Usage {
+            requests: self.requests - rhs.requests,
+            cost: self.cost - rhs.cost,
+            bytes: self.bytes - rhs.bytes,
+            cache_hits: self.cache_hits - rhs.cache_hits,
+        }
+    }
+}
+
+pub fn usage() -> Usage {
+    Usage {
+        requests: REQUESTS.load(Ordering::SeqCst),
+        cost: COST.load(Ordering::SeqCst),
+        bytes: BYTES.load(Ordering::SeqCst),
+        cache_hits: CACHE_HITS.load(Ordering::SeqCst),
+    }
+}
+
+pub fn count_cache_hit() {
+    CACHE_HITS.fetch_add(1, Ordering::SeqCst);
+}
+
+/// count the bytes and rate limit points of a response, queries report their cost with `rateLimit { cost }`
+fn count_response(request: &[u8], response: &[u8]) -> usize {
+    #[derive(Deserialize)]
+    struct Body {
+        data: Option<Data>,
+    }
+    #[derive(Deserialize)]
+    struct Data {
+        #[serde(rename = "rateLimit")]
+        rate_limit: Option<RateLimit>,
+    }
+    #[derive(Deserialize)]
+    struct RateLimit {
+        cost: usize,
+    }
+    let cost = serde_json::from_slice::<Body>(response)
+        .ok()
+        .and_then(|body| body.data?.rate_limit)
+        .map_or(1, |rate_limit| rate_limit.cost);
+    COST.fetch_add(cost, Ordering::SeqCst);
+    BYTES.fetch_add(request.len() + response.len(), Ordering::SeqCst);
+    cost
+}
+
+/// a span per request named after the operation, e.g. `query listItems`, with the page cursor and item
+fn request_span<V: Serialize>(query: &str, variables: &V) -> tracing::Span {
+    let mut words = query.split(|c: char| c.is_whitespace() || c == '(' || c == '{');
+    let mut words = words.by_ref().filter(|word| !word.is_empty());
+    let kind = words.next().unwrap_or_default();
+    let operation = words.next().unwrap_or_default();
+    let variables = serde_json::to_value(variables).unwrap_or_default();
+    let variable = |name: &str| {
+        let value = variables
+            .get(name)
+            .or_else(|| variables.get("input")?.get(name))?;
+        value.as_str().map(str::to_string)
+    };
+    tracing::info_span!(
+        "graphql",
+        kind,
+        operation,
+        after = variable("after"),
+        item_id = variable("itemId"),
+        cost = tracing::field::Empty,
+        bytes = tracing::field::Empty,
+    )
+}
+
+/// how requests reach GitHub
+enum Transport {
+    /// `gh api graphql`, authenticated by `gh auth login`
+    Gh {
+        /// the host of `--host` or `HOST/OWNER`, otherwise `gh` resolves it
+        hostname: Option<String>,
+    },
+    /// POST to the API with a token from `GH_TOKEN` or `GITHUB_TOKEN`, or their
+    /// `GH_ENTERPRISE_` counterparts for GHES, which doesn't need `gh`, or with the token
+    /// of `--account` as `gh api` can't choose the account
+    Token {
+        client: reqwest::blocking::Client,
+        /// the GraphQL endpoint of the host
+        endpoint: String,
+        source: TokenSource,
+        token: String,
+    },
+}
+
+/// where the token of [`Transport::Token`] comes from
+enum TokenSource {
+    /// the environment variable the token is read from
+    Variable(&'static str),
+    /// an account logged in to `gh`, which may not be the active one
+    Account { host: String, account: String },
+}
+
+static TRANSPORT: OnceLock<Transport> = OnceLock::new();
+static CASSETTE: OnceLock<Cassette> = OnceLock::new();
+
+pub struct Opt {
+    /// PEM file of certificates trusted in addition to the system ones, for proxies and GHES
+    pub ca_cert: Option<PathBuf>,
+    /// save or serve back every request and response
+    pub cassette: Option<Cassette>,
+    /// the host of `--host` or `HOST/OWNER`, which takes precedence over `GH_HOST`
+    pub hostname: Option<String>,
+    /// the account of `gh` to send requests as instead of the active one
+    pub account: Option<String>,
+}
+
+const GITHUB_COM: &str = "github.com";
+
+/// the version of the API every request is pinned to, so that the changes GitHub ships
+/// behind a newer one don't reach the queries written against schema.docs.graphql
+const API_VERSION: &str = "2022-11-28";
+
+/// choose how requests are sent, which is `gh` unless a token is in the environment
+pub fn init(opt: Opt) -> Result<()> {
+    let transport = Transport::new(opt.ca_cert.as_deref(), opt.hostname, opt.account)?;
+    if matches!(transport, Transport::Gh { .. }) && opt.ca_cert.is_some() {
+        eprintln!(
+            "--ca-cert is ignored without GH_TOKEN, `gh` uses the certificates of the system"
+        );
+    }
+    let _ = TRANSPORT.set(transport);
+    if let Some(cassette) = opt.cassette {
+        let _ = CASSETTE.set(cassette);
+    }
+    Ok(())
+}
+
+impl Transport {
+    fn new(
+        ca_cert: Option<&Path>,
+        hostname: Option<String>,
+        account: Option<String>,
+    ) -> Result<Self> {
+        let host = resolve_host(hostname.clone());
+        let (source, token) = match account {
+            Some(account) => {
+                let token = account_token(&host, &account)?;
+                (
+                    TokenSource::Account {
+                        host: host.clone(),
+                        account,
+                    },
+                    token,
+                )
+            }
+            None => {
+                let variables = if is_enterprise(&host) {
+                    ["GH_ENTERPRISE_TOKEN", "GITHUB_ENTERPRISE_TOKEN"]
+                } else {
+                    ["GH_TOKEN", "GITHUB_TOKEN"]
+                };
+                let token = variables.into_iter().find_map(|variable| {
+                    let token = env::var(variable).ok().filter(|token| !token.is_empty())?;
+                    Some((variable, token))
+                });
+                let Some((variable, token)) = token else {
+                    return Ok(Transport::Gh { hostname });
+                };
+                (TokenSource::Variable(variable), token)
+            }
+        };
+        // proxies are read from HTTPS_PROXY and NO_PROXY
+        let mut client = reqwest::blocking::Client::builder();
+        if let Some(path) = ca_cert {
+            for certificate in read_certificates(path)? {
+                client = client.add_root_certificate(certificate);
+            }
+        }
+        Ok(Transport::Token {
+            client: client.build().context("Failed to build the HTTP client")?,
+            endpoint: graphql_endpoint(&host),
+            source,
+            token,
+        })
+    }
+
+    fn get() -> &'static Transport {
+        TRANSPORT.get_or_init(|| Transport::new(None, None, None).expect("no certificates to read"))
+    }
+
+    /// send the request body and return the response, or serve it from `--replay`
+    fn send(&self, body: &[u8]) -> Result<Response> {
+        let Some(cassette) = CASSETTE.get() else {
+            return self.send_live(body);
+        };
+        if cassette.mode == cassette::Mode::Replay {
+            let (scopes, body) = cassette.replay(body)?;
+            return Ok(Response { scopes, body });
+        }
+        let response = self.send_live(body)?;
+        cassette.record(body, response.scopes.as_deref(), &response.body)?;
+        Ok(response)
+    }
+
+    fn send_live(&self, body: &[u8]) -> Result<Response> {
+        match self {
+            Transport::Gh { hostname } => send_with_gh(body, hostname.as_deref()),
+            Transport::Token {
+                client,
+                endpoint,
+                token,
+                ..
+            } => {
+                let mut request = client
+                    .post(endpoint)
+                    .bearer_auth(token)
+                    .header("user-agent", concat!("gh-sql/", env!("CARGO_PKG_VERSION")))
+                    .header("content-type", "application/json")
+                    .header("x-github-api-version", API_VERSION)
+                    .body(body.to_vec());
+                // its failure is reported as the timeout once the response is checked
+                if let Some(remaining) = remaining() {
+                    request = request.timeout(remaining);
+                }
+                let response = request.send().context(RequestFailure::new(
+                    FailureKind::Network,
+                    "Failed to send request to GitHub",
+                ))?;
+                let status = response.status();
+                let scopes = response
+                    .headers()
+                    .get("x-oauth-scopes")
+                    .and_then(|scopes| scopes.to_str().ok())
+                    .map(str::to_string);
+                let bytes = response.bytes().context(RequestFailure::new(
+                    FailureKind::Network,
+                    "Failed to read response from GitHub",
+                ))?;
+                // GraphQL errors come with 200, others like bad credentials don't
+                if let Some(failure) = RequestFailure::from_status(status.as_u16(), &bytes) {
+                    return Err(failure.into());
+                }
+                if !status.is_success() {
+                    return Err(anyhow!(
+                        "GitHub responded with {}: {}",
+                        status,
+                        String::from_utf8_lossy(&bytes)
+                    ));
+                }
+                Ok(Response {
+                    scopes,
+                    body: bytes.to_vec(),
+                })
+            }
+        }
+    }
+
+    /// how to get the `project` scope
+    fn scope_hint(&self) -> String {
+        match self {
+            Transport::Gh { hostname: None } => "run `gh auth refresh -s project`".to_string(),
+            Transport::Gh {
+                hostname: Some(hostname),
+            } => format!("run `gh auth refresh -h {} -s project`", hostname),
+            Transport::Token {
+                source: TokenSource::Variable(variable),
+                ..
+            } => format!(
+                "use a token with the `project` (or `read:project`) scope in {}",
+                variable
+            ),
+            Transport::Token {
+                source: TokenSource::Account { host, account },
+                ..
+            } => format!(
+                "run `gh auth switch -h {host} -u {account}` and `gh auth refresh -h {host} -s project`",
+                host = host,
+                account = account
+            ),
+        }
+    }
+}
+
+struct Response {
+    /// scopes of a classic token, fine-grained and app tokens don't report them
+    scopes: Option<String>,
+    body: Vec<u8>,
+}
+
+/// the first response is checked for the scope of the projects API, which is missing from
+/// tokens made by `gh auth login` before Projects existed
+static SCOPES_CHECKED: AtomicBool = AtomicBool::new(false);
+
+/// how to get the `project` scope for the token in use
+pub fn scope_hint() -> String {
+    Transport::get().scope_hint()
+}
+
+/// the environment variable of the token if requests are sent without `gh`
+pub fn token_variable() -> Option<&'static str> {
+    match Transport::get() {
+        Transport::Token {
+            source: TokenSource::Variable(variable),
+            ..
+        } => Some(variable),
+        _ => None,
+    }
+}
+
+/// the host given by `--host` or `HOST/OWNER`, which `gh` has to be told about
+pub fn hostname() -> Option<&'static str> {
+    match Transport::get() {
+        Transport::Gh { hostname } => hostname.as_deref(),
+        Transport::Token {
+            source: TokenSource::Account { host, .. },
+            ..
+        } => Some(host),
+        Transport::Token { .. } => None,
+    }
+}
+
+/// the account of `--account`
+pub fn account() -> Option<&'static str> {
+    match Transport::get() {
+        Transport::Token {
+            source: TokenSource::Account { account, .. },
+            ..
+        } => Some(account),
+        _ => None,
+    }
+}
+
+/// the token `gh` keeps for the account on the host, without switching to it
+fn account_token(host: &str, account: &str) -> Result<String> {
+    let output = Command::new("gh")
+        .args(["auth", "token", "--hostname", host, "--user", account])
+        // `gh` prints these instead if they are set
+        .env_remove("GH_TOKEN")
+        .env_remove("GITHUB_TOKEN")
+        .env_remove("GH_ENTERPRISE_TOKEN")
+        .env_remove("GITHUB_ENTERPRISE_TOKEN")
+        .output()
+        .context("Failed to execute `gh` command, --account needs it")?;
+    let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || token.is_empty() {
+        let message = format!(
+            "Failed to get the token of {} on {} from gh: {}; check `gh auth status -h {}` or run `gh auth login -h {}`",
+            account,
+            host,
+            String::from_utf8_lossy(&output.stderr).trim(),
+            host,
+            host
+        );
+        return Err(RequestFailure::new(FailureKind::Auth, message).into());
+    }
+    Ok(token)
+}
+
+/// the host requests go to, chosen like `gh` does: the given one, `GH_HOST`, then
+/// github.com unless `gh` is only logged in to other hosts
+fn resolve_host(hostname: Option<String>) -> String {
+    hostname
+        .or_else(|| env::var("GH_HOST").ok().filter(|host| !host.is_empty()))
+        .or_else(|| {
+            let hosts = configured_hosts();
+            (!hosts.iter().any(|host| host == GITHUB_COM))
+                .then(|| hosts.into_iter().next())
+                .flatten()
+        })
+        .unwrap_or_else(|| GITHUB_COM.to_string())
+}
+
+/// the host requests go to, e.g. `github.com`
+pub fn host() -> String {
+    resolve_host(hostname().map(str::to_string))
+}
+
+/// the web URL of `path` on the host requests go to, e.g. `https://github.com/owner/repo`
+pub fn web_url(path: &str) -> String {
+    format!("https://{}/{}", host(), path)
+}
+
+/// the hosts `gh auth login` has been run for, the top-level keys of its `hosts.yml`
+fn configured_hosts() -> Vec<String> {
+    let Some(dir) = gh_config_dir() else {
+        return vec![];
+    };
+    let Ok(hosts) = fs::read_to_string(dir.join("hosts.yml")) else {
+        return vec![];
+    };
+    hosts
+        .lines()
+        .filter(|line| !line.starts_with([' ', '\t', '#']))
+        .filter_map(|line| line.trim_end().strip_suffix(':'))
+        .map(|host| host.trim_matches(['"', '\'']).to_string())
+        .collect()
+}
+
+/// where `gh` keeps its configuration
+fn gh_config_dir() -> Option<PathBuf> {
+    let var = |name| env::var_os(name).filter(|value| !value.is_empty());
+    if let Some(dir) = var("GH_CONFIG_DIR") {
+        return Some(dir.into());
+    }
+    if let Some(dir) = var("XDG_CONFIG_HOME") {
+        return Some(PathBuf::from(dir).join("gh"));
+    }
+    if cfg!(windows) {
+        if let Some(dir) = var("AppData") {
+            return Some(PathBuf::from(dir).join("GitHub CLI"));
+        }
+    }
+    Some(dirs_next::home_dir()?.join(".config").join("gh"))
+}
+
+/// GitHub Enterprise Server, as opposed to github.com and GHE.com tenants
+fn is_enterprise(host: &str) -> bool {
+    host != GITHUB_COM && !host.ends_with(".ghe.com")
+}
+
+fn graphql_endpoint(host: &str) -> String {
+    if is_enterprise(host) {
+        format!("https://{}/api/graphql", host)
+    } else {
+        format!("https://api.{}/graphql", host)
+    }
+}
+
+/// who the token belongs to and what it can do, as reported by the API
+pub struct Probe {
+    pub login: String,
+    /// `None` for tokens which don't report them
+    pub scopes: Option<String>,
+    pub has_project_scope: bool,
+    pub elapsed: Duration,
+}
+
+/// send a cheap `viewer` query, bypassing the scope check of [`graphql`]
+pub fn probe() -> Result<Probe> {
+    #[derive(Deserialize)]
+    struct Body {
+        data: Option<Data>,
+        #[serde(flatten)]
+        errors: GraphQLErrors,
+    }
+    #[derive(Deserialize)]
+    struct Data {
+        viewer: Viewer,
+    }
+    #[derive(Deserialize)]
+    struct Viewer {
+        login: String,
+    }
+    let transport = Transport::get();
+    let start = Instant::now();
+    let response = transport.send(br#"{"query":"query { viewer { login } }"}"#)?;
+    let elapsed = start.elapsed();
+    let body: Body = serde_json::from_slice(&response.body).with_context(|| {
+        format!(
+            "Failed to parse response: {}",
+            String::from_utf8_lossy(&response.body)
+        )
+    })?;
+    let Some(data) = body.data else {
+        return Err(anyhow!("{}", body.errors.error_msgs()));
+    };
+    Ok(Probe {
+        login: data.viewer.login,
+        has_project_scope: check_scopes(transport, response.scopes.as_deref()).is_ok(),
+        scopes: response.scopes,
+        elapsed,
+    })
+}
+
+fn check_scopes(transport: &Transport, scopes: Option<&str>) -> Result<()> {
+    let Some(scopes) = scopes else {
+        return Ok(());
+    };
+    let has_project_scope = scopes
+        .split(',')
+        .any(|scope| matches!(scope.trim(), "project" | "read:project"));
+    if !has_project_scope {
+        let message = format!(
+            "Your token lacks the project scope (it has: {}); {}",
+            scopes,
+            transport.scope_hint()
+        );
+        return Err(RequestFailure::new(FailureKind::Auth, message).into());
+    }
+    Ok(())
+}
+
+/// every certificate of a PEM file, which may be a bundle of them
+fn read_certificates(path: &Path) -> Result<Vec<reqwest::Certificate>> {
+    const END: &str = "-----END CERTIFICATE-----";
+    let pem =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let certificates = pem
+        .split_inclusive(END)
+        .filter(|block| block.contains("-----BEGIN CERTIFICATE-----"))
+        .map(|block| {
+            reqwest::Certificate::from_pem(block.trim().as_bytes())
+                .with_context(|| format!("Invalid certificate in {}", path.display()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if certificates.is_empty() {
+        return Err(anyhow!("No certificate found in {}", path.display()));
+    }
+    Ok(certificates)
+}
+
+fn send_with_gh(body: &[u8], hostname: Option<&str>) -> Result<Response> {
+    let mut gh = Command::new("gh");
+    gh.args(["api", "graphql", "--include", "--input", "-"]);
+    gh.args(["-H", &format!("X-GitHub-Api-Version: {}", API_VERSION)]);
+    if let Some(hostname) = hostname {
+        gh.args(["--hostname", hostname]);
+    }
+    let mut gh = gh
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to execute `gh` command, install it or set GH_TOKEN")?;
+    let stdin = gh.stdin.as_mut().expect("stdin is piped");
+    stdin
+        .write_all(body)
+        .context("Failed to write request body to stdin of `gh`")?;
+    let output = wait_with_deadline(gh).context("Failed to read response from `gh`")?;
+    // `gh` fails on GraphQL errors as well, whose response is still parsed, so only
+    // failures without a response or with one of a known reason are errors here
+    if !output.status.success() && output.stdout.is_empty() {
+        let stderr = std::str::from_utf8(&output.stderr).unwrap_or_default();
+        let code = output.status.code().expect("process has been exited");
+        // `gh` exits with 4 when it has no token for the host
+        let kind = if code == 4 {
+            FailureKind::Auth
+        } else {
+            FailureKind::Network
+        };
+        let message = format!("`gh` exited with status code: {}\n{}", code, stderr);
+        return Err(RequestFailure::new(kind, message).into());
+    }
+    // `--include` prints the status line and the headers before the body
+    let stdout = output.stdout;
+    let Some(end) = stdout.windows(4).position(|w| w == b"\r\n\r\n") else {
+        return Ok(Response {
+            scopes: None,
+            body: stdout,
+        });
+    };
+    let head = String::from_utf8_lossy(&stdout[..end]);
+    // e.g. `HTTP/2.0 401 Unauthorized`
+    let status = head
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1)?.parse().ok());
+    let body = &stdout[end + 4..];
+    if let Some(failure) = status.and_then(|status| RequestFailure::from_status(status, body)) {
+        return Err(failure.into());
+    }
+    let scopes = head.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.eq_ignore_ascii_case("x-oauth-scopes")
+            .then(|| value.trim().to_string())
+    });
+    Ok(Response {
+        scopes,
+        body: body.to_vec(),
+    })
+}
+
+fn check_interrupt() -> Result<()> {
+    if INTERRUPTED.load(Ordering::SeqCst) {
+        return Err(RequestFailure::new(FailureKind::Interrupted, "Interrupted").into());
+    }
+    if DEADLINE
+        .get()
+        .is_some_and(|deadline| Instant::now() >= deadline)
+    {
+        return Err(timed_out());
+    }
+    Ok(())
+}
+
+/// wait for `gh` like [`Child::wait_with_output`], killing it once the statement timed out
+fn wait_with_deadline(mut child: Child) -> Result<Output> {
+    let Some(deadline) = DEADLINE.get() else {
+        return Ok(child.wait_with_output()?);
+    };
+    drop(child.stdin.take());
+    // read while waiting, a response larger than the pipe would block `gh` otherwise
+    let read = |mut pipe: Box<dyn Read + Send>| {
+        thread::spawn(move || {
+            let mut buf = vec![];
+            pipe.read_to_end(&mut buf).map(|_| buf)
+        })
+    };
+    let stdout = read(Box::new(child.stdout.take().expect("stdout is piped")));
+    let stderr = read(Box::new(child.stderr.take().expect("stderr is piped")));
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            child.kill()?;
+            child.wait()?;
+            return Err(timed_out());
+        }
+        thread::sleep(Duration::from_millis(50));
+    };
+    Ok(Output {
+        status,
+        stdout: stdout.join().expect("reading stdout of `gh` panicked")?,
+        stderr: stderr.join().expect("reading stderr of `gh` panicked")?,
+    })
+}
+
+/// the time left until the statement times out, for the requests sent without `gh`
+fn remaining() -> Option<Duration> {
+    DEADLINE
+        .get()
+        .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+}
+
+/// why a request to GitHub failed, for the exit code to tell the failures worth retrying
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// a missing or invalid token, or one lacking a scope
+    Auth,
+    RateLimit,
+    /// no response, or a server error which may go away
+    Network,
+    Interrupted,
+    /// `--statement-timeout` elapsed
+    Timeout,
+}
+
+/// the error, or the context of one, of a request which failed for a known reason
+#[derive(Debug)]
+pub struct RequestFailure {
+    pub kind: FailureKind,
+    message: String,
+}
+
+impl RequestFailure {
+    fn new(kind: FailureKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    /// why the response with `status` failed, `None` for the errors of the request itself
+    fn from_status(status: u16, body: &[u8]) -> Option<Self> {
+        let body = String::from_utf8_lossy(body);
+        let kind = match status {
+            403 | 429 if body.to_lowercase().contains("rate limit") => FailureKind::RateLimit,
+            401 | 403 => FailureKind::Auth,
+            500.. => FailureKind::Network,
+            _ => return None,
+        };
+        let message = format!("GitHub responded with {}: {}", status, body);
+        Some(Self::new(kind, message))
+    }
+}
+
+impl fmt::Display for RequestFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for RequestFailure {}
+
 pub fn graphql<V, T>(query: &str, variables: &V) -> Result<GraphQLResponse<T, GraphQLErrors>>
 where
     V: Serialize,
@@ -21,31 +854,36 @@ where
         data: T,
     }
 
+    check_interrupt()?;
+    REQUESTS.fetch_add(1, Ordering::SeqCst);
     let req_body = ReqBody { query, variables };
     let req_body_bytes =
         serde_json::to_vec(&req_body).context("Failed to serialize request body")?;
+    let span = request_span(query, variables);
+    let _enter = span.enter();
 
-    let mut gh = Command::new("gh")
-        .args(["api", "graphql", "--input", "-"])
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .context("Failed to execute `gh` command")?;
-    let stdin = gh.stdin.as_mut().expect("stdin is piped");
-    stdin
-        .write_all(&req_body_bytes)
-        .context("Failed to write request body to stdin of `gh`")?;
-    let output = gh
-        .wait_with_output()
-        .context("Failed to read response from `gh`")?;
-    if !output.status.success() {
-        let stderr = std::str::from_utf8(&output.stderr).unwrap_or_default();
-        let code = output.status.code().expect("process has been exited");
-        anyhow!("`gh` exited with status code: {}\n{}", code, stderr);
+    let transport = Transport::get();
+    let response = transport.send(&req_body_bytes);
+    // `gh` is in our process group and got the SIGINT as well
+    check_interrupt()?;
+    let Response {
+        scopes,
+        body: response,
+    } = response?;
+    if !SCOPES_CHECKED.load(Ordering::SeqCst) {
+        check_scopes(transport, scopes.as_deref())?;
+        SCOPES_CHECKED.store(true, Ordering::SeqCst);
     }
-    let err_resp: serde_json::Result<GraphQLErrors> = serde_json::from_slice(&output.stdout);
-    let data_resp: RespBody<T> = match serde_json::from_slice(&output.stdout) {
+    let cost = count_response(&req_body_bytes, &response);
+    span.record("cost", cost);
+    span.record("bytes", req_body_bytes.len() + response.len());
+    let err_resp: serde_json::Result<GraphQLErrors> = serde_json::from_slice(&response);
+    if let Ok(errors) = &err_resp {
+        if let Some(failure) = errors.request_failure() {
+            return Err(failure.into());
+        }
+    }
+    let data_resp: RespBody<T> = match parse_degraded(&response) {
         Ok(d) => d,
         Err(de) => {
             let de = anyhow::Error::new(de).context("Failed to parse response");
@@ -66,6 +904,156 @@ where
     })
 }
 
+/// the kinds of fields and field values in schema.docs.graphql, and the data types of
+/// fields, which `doctor` compares with those of the live schema
+const KNOWN_KINDS: &[&str] = &[
+    "ProjectV2Field",
+    "ProjectV2IterationField",
+    "ProjectV2SingleSelectField",
+    "ProjectV2ItemFieldDateValue",
+    "ProjectV2ItemFieldIterationValue",
+    "ProjectV2ItemFieldLabelValue",
+    "ProjectV2ItemFieldMilestoneValue",
+    "ProjectV2ItemFieldNumberValue",
+    "ProjectV2ItemFieldPullRequestValue",
+    "ProjectV2ItemFieldRepositoryValue",
+    "ProjectV2ItemFieldReviewerValue",
+    "ProjectV2ItemFieldSingleSelectValue",
+    "ProjectV2ItemFieldTextValue",
+    "ProjectV2ItemFieldUserValue",
+    "ASSIGNEES",
+    "DATE",
+    "ITERATION",
+    "LABELS",
+    "LINKED_PULL_REQUESTS",
+    "MILESTONE",
+    "NUMBER",
+    "REPOSITORY",
+    "REVIEWERS",
+    "SINGLE_SELECT",
+    "TEXT",
+    "TITLE",
+    "TRACKED_BY",
+    "TRACKS",
+];
+
+/// the kinds of fields and field values, and the data types of fields, which GitHub added
+/// after schema.docs.graphql, found by introspecting the live schema
+pub fn unknown_kinds() -> Result<Vec<String>> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Data {
+        fields: Option<Type>,
+        values: Option<Type>,
+        data_types: Option<Type>,
+    }
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Type {
+        #[serde(default)]
+        possible_types: Option<Vec<Name>>,
+        #[serde(default)]
+        enum_values: Option<Vec<Name>>,
+    }
+    #[derive(Deserialize)]
+    struct Name {
+        name: String,
+    }
+    let query = r#"query {
+  fields: __type(name: "ProjectV2FieldConfiguration") { possibleTypes { name } }
+  values: __type(name: "ProjectV2ItemFieldValue") { possibleTypes { name } }
+  dataTypes: __type(name: "ProjectV2FieldType") { enumValues { name } }
+}"#;
+    #[derive(Serialize)]
+    struct Variables {}
+    let data: Data = graphql(query, &Variables {})?.data;
+    Ok([data.fields, data.values, data.data_types]
+        .into_iter()
+        .flatten()
+        .flat_map(|t| t.possible_types.into_iter().chain(t.enum_values).flatten())
+        .map(|name| name.name)
+        .filter(|name| !KNOWN_KINDS.contains(&name.as_str()))
+        .collect())
+}
+
+/// the kinds already warned about by [`parse_degraded`]
+static DEGRADED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// parse `response`, degrading the objects whose `__typename` is unknown to `T` with
+/// [`degrade`] rather than failing on the kinds GitHub adds after schema.docs.graphql
+fn parse_degraded<T: DeserializeOwned>(response: &[u8]) -> serde_json::Result<T> {
+    let mut error = match serde_json::from_slice(response) {
+        Ok(data) => return Ok(data),
+        Err(e) => e,
+    };
+    let Ok(mut json) = serde_json::from_slice::<serde_json::Value>(response) else {
+        return Err(error);
+    };
+    let mut typenames = vec![];
+    loop {
+        // e.g. unknown variant `ProjectV2ItemFieldFooValue`, expected one of ...
+        let message = error.to_string();
+        let Some(typename) = message
+            .strip_prefix("unknown variant `")
+            .and_then(|rest| rest.split_once('`'))
+            .map(|(typename, _)| typename.to_string())
+        else {
+            return Err(error);
+        };
+        if typenames.contains(&typename) {
+            return Err(error);
+        }
+        degrade(&mut json, &typename);
+        typenames.push(typename);
+        match serde_json::from_value(json.clone()) {
+            Ok(data) => break Ok(warn_degraded(data, typenames)),
+            Err(e) => error = e,
+        }
+    }
+}
+
+fn warn_degraded<T>(data: T, typenames: Vec<String>) -> T {
+    let mut degraded = DEGRADED.lock().unwrap_or_else(|e| e.into_inner());
+    for typename in typenames {
+        if !degraded.contains(&typename) {
+            eprintln!(
+                "{} is unknown to this version of gh-sql, its values are read as raw text",
+                typename
+            );
+            degraded.push(typename);
+        }
+    }
+    data
+}
+
+/// rewrite the objects of `typename` into kinds every query selects: a field value, which
+/// has a `field`, into a text value holding its raw JSON, and a field, which has an `id`,
+/// into a field of an unknown data type. Others become null
+fn degrade(json: &mut serde_json::Value, typename: &str) {
+    use serde_json::Value as Json;
+    match json {
+        Json::Array(values) => values.iter_mut().for_each(|value| degrade(value, typename)),
+        Json::Object(object) => {
+            if object.get("__typename").and_then(Json::as_str) == Some(typename) {
+                if object.contains_key("field") {
+                    let raw = Json::Object(object.clone()).to_string();
+                    object.insert("__typename".into(), "ProjectV2ItemFieldTextValue".into());
+                    object.insert("text".into(), raw.into());
+                } else if object.contains_key("id") {
+                    object.insert("__typename".into(), "ProjectV2Field".into());
+                } else {
+                    *json = Json::Null;
+                    return;
+                }
+            }
+            object
+                .values_mut()
+                .for_each(|value| degrade(value, typename));
+        }
+        _ => {}
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GraphQLResponse<T, E = GraphQLErrors> {
     pub data: T,
@@ -79,13 +1067,24 @@ pub struct GraphQLErrors {
 }
 
 impl GraphQLErrors {
+    /// every error with its type and path on one line
     pub fn error_msgs(&self) -> String {
         self.errors
             .iter()
-            .map(|e| e.message.as_str())
+            .map(ToString::to_string)
             .collect::<Vec<_>>()
             .join(" / ")
     }
+
+    /// a failure of the whole request rather than of some fields
+    fn request_failure(&self) -> Option<RequestFailure> {
+        let kind = self.errors.iter().find_map(|e| match e.kind.as_deref()? {
+            "RATE_LIMITED" => Some(FailureKind::RateLimit),
+            "INSUFFICIENT_SCOPES" => Some(FailureKind::Auth),
+            _ => None,
+        })?;
+        Some(RequestFailure::new(kind, self.error_msgs()))
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
@@ -93,6 +1092,33 @@ pub struct GraphQLError {
     #[serde(default = "Vec::new")]
     pub path: Vec<ObjectPath>,
     pub message: String,
+    /// e.g. `NOT_FOUND` or `FORBIDDEN`, which GitHub adds to most errors
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+}
+
+impl GraphQLError {
+    /// the error of one of several aliased mutations, without the alias in its path
+    pub fn unaliased(&self) -> Self {
+        Self {
+            path: self.path.iter().skip(1).cloned().collect(),
+            ..self.clone()
+        }
+    }
+}
+
+/// `message (TYPE at path.to.field)`
+impl fmt::Display for GraphQLError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        let path: Vec<_> = self.path.iter().map(ToString::to_string).collect();
+        match (&self.kind, path.is_empty()) {
+            (Some(kind), true) => write!(f, " ({})", kind),
+            (Some(kind), false) => write!(f, " ({} at {})", kind, path.join(".")),
+            (None, false) => write!(f, " (at {})", path.join(".")),
+            (None, true) => Ok(()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -101,3 +1127,12 @@ pub enum ObjectPath {
     Number(usize),
     String(String),
 }
+
+impl fmt::Display for ObjectPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjectPath::Number(n) => write!(f, "{}", n),
+            ObjectPath::String(s) => write!(f, "{}", s),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use std::borrow::Cow::{self, Borrowed, Owned};
+
+use rustyline::{
+    completion::Completer,
+    highlight::Highlighter,
+    hint::{Hinter, HistoryHinter},
+    validate::Validator,
+    Context, Helper,
+};
+
+const KEYWORDS: &[&str] = &[
+    "ADD", "ALTER", "AND", "AS", "ASC", "BEGIN", "BETWEEN", "BY", "CASE", "COLUMN", "COMMIT",
+    "COPY", "CREATE", "DELETE", "DESC", "DISTINCT", "DROP", "ELSE", "END", "EXPLAIN", "FALSE",
+    "FROM", "GROUP", "HAVING", "IN", "INNER", "INSERT", "INTO", "IS", "JOIN", "LEFT", "LIKE",
+    "LIMIT", "NOT", "NULL", "OFFSET", "ON", "OR", "ORDER", "OUTER", "RENAME", "ROLLBACK", "SELECT",
+    "SET", "TABLE", "THEN", "TO", "TRUE", "UPDATE", "VALUES", "WHEN", "WHERE", "WITH",
+];
+
+const KEYWORD: &str = "\x1b[1;34m";
+const STRING: &str = "\x1b[32m";
+const NUMBER: &str = "\x1b[33m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// rustyline helper of the prompt
+pub struct SqlHelper {
+    color: bool,
+    hinter: HistoryHinter,
+}
+
+impl Default for SqlHelper {
+    /// colors are disabled by `NO_COLOR`
+    fn default() -> Self {
+        Self {
+            color: std::env::var_os("NO_COLOR").is_none(),
+            hinter: HistoryHinter {},
+        }
+    }
+}
+
+impl Highlighter for SqlHelper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        if !self.color {
+            return Borrowed(line);
+        }
+        Owned(highlight_sql(line))
+    }
+
+    fn highlight_prompt<'b, 's: 'b, 'p: 'b>(
+        &'s self,
+        prompt: &'p str,
+        _default: bool,
+    ) -> Cow<'b, str> {
+        // the continuation prompt is indented
+        if self.color && prompt.starts_with(' ') {
+            Owned(format!("{}{}{}", DIM, prompt, RESET))
+        } else {
+            Borrowed(prompt)
+        }
+    }
+
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        if self.color {
+            Owned(format!("{}{}{}", DIM, hint, RESET))
+        } else {
+            Borrowed(hint)
+        }
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize) -> bool {
+        self.color
+    }
+}
+
+impl Completer for SqlHelper {
+    type Candidate = String;
+}
+
+/// suggest the rest of the latest history entry starting with the line, accepted with →
+impl Hinter for SqlHelper {
+    type Hint = String;
+
+    fn hint(&self, line: &str, pos: usize, ctx: &Context<'_>) -> Option<String> {
+        // a statement edited with `\e` may span lines, only its first line is suggested
+        let hint = self.hinter.hint(line, pos, ctx)?;
+        let hint = hint.lines().next().unwrap_or_default();
+        (!hint.is_empty()).then(|| hint.to_string())
+    }
+}
+
+impl Validator for SqlHelper {}
+
+impl Helper for SqlHelper {}
+
+/// color keywords, strings, numbers and `--` comments without changing the visible text
+fn highlight_sql(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() * 2);
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        let (len, color) = if rest.starts_with("--") {
+            (rest.len(), Some(DIM))
+        } else if c == '\'' {
+            // `''` is an escaped quote; an unterminated string runs to the end of the line
+            let mut end = rest.len();
+            let mut chars = rest.char_indices().skip(1).peekable();
+            while let Some((i, c)) = chars.next() {
+                if c == '\'' && chars.next_if(|(_, c)| *c == '\'').is_none() {
+                    end = i + 1;
+                    break;
+                }
+            }
+            (end, Some(STRING))
+        } else if c.is_alphanumeric() || c == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(rest.len());
+            let word = &rest[..len];
+            let color = if word.chars().all(|c| c.is_ascii_digit() || c == '.') {
+                Some(NUMBER)
+            } else if KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(word)) {
+                Some(KEYWORD)
+            } else {
+                None
+            };
+            (len, color)
+        } else {
+            (c.len_utf8(), None)
+        };
+        match color {
+            Some(color) => {
+                out.push_str(color);
+                out.push_str(&rest[..len]);
+                out.push_str(RESET);
+            }
+            None => out.push_str(&rest[..len]),
+        }
+        rest = &rest[len..];
+    }
+    out
+}
@@ -0,0 +1,167 @@
+use std::{
+    io::{ErrorKind, Write},
+    process::{Command, Stdio},
+    str::FromStr,
+    sync::{Mutex, OnceLock},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context, Error, Result};
+use gluesql::core::{executor::Payload, sqlparser::ast::Statement};
+use serde::Serialize;
+
+use crate::{sql, storage};
+
+/// what `--on-mutation` runs after a statement changed the project
+#[derive(Debug, Clone)]
+pub enum Hook {
+    /// POST the summary as JSON, which has a `text` for chat webhooks like Slack's
+    Webhook(String),
+    /// a shell command given the summary as JSON on stdin
+    Command(String),
+}
+
+impl FromStr for Hook {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Err(anyhow!("Empty hook"));
+        }
+        if s.starts_with("https://") || s.starts_with("http://") {
+            Ok(Hook::Webhook(s.to_string()))
+        } else {
+            Ok(Hook::Command(s.to_string()))
+        }
+    }
+}
+
+/// the hook and `OWNER/PROJECT_NUMBER` of the project, set once by [`set`]
+static HOOK: OnceLock<(Hook, String)> = OnceLock::new();
+
+pub fn set(hook: Hook, project: String) {
+    let _ = HOOK.set((hook, project));
+}
+
+/// the summaries of the writes buffered since `BEGIN`, run on `COMMIT` which sends them
+static PENDING: Mutex<Option<Vec<Summary<'static>>>> = Mutex::new(None);
+
+/// how long a webhook may take to respond, as the next statement waits for it
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// the JSON the hook is given
+#[derive(Debug, Serialize)]
+struct Summary<'a> {
+    /// e.g. `UPDATE of 3 row(s) of items in octo-org/1`
+    text: String,
+    /// `INSERT`, `UPDATE` or `DELETE`
+    kind: &'static str,
+    table: String,
+    rows: usize,
+    project: &'a str,
+    statement: String,
+}
+
+/// run the hook after an `INSERT`, `UPDATE` or `DELETE` of a project table, warning of its
+/// failure rather than failing the statement, which is done already. Inside a transaction
+/// the hook runs on `COMMIT` instead, and not at all on `ROLLBACK`
+pub fn after(statement: &Statement, payload: &Payload) {
+    let Some((hook, project)) = HOOK.get() else {
+        return;
+    };
+    let (kind, rows) = match payload {
+        Payload::Insert(n) => ("INSERT", *n),
+        Payload::Update(n) => ("UPDATE", *n),
+        Payload::Delete(n) => ("DELETE", *n),
+        Payload::StartTransaction => {
+            *PENDING.lock().unwrap() = Some(vec![]);
+            return;
+        }
+        Payload::Commit => {
+            for summary in PENDING.lock().unwrap().take().unwrap_or_default() {
+                report(hook, &summary);
+            }
+            return;
+        }
+        Payload::Rollback => {
+            discard();
+            return;
+        }
+        _ => return,
+    };
+    let Some(table) = sql::target_table(statement) else {
+        return;
+    };
+    if !storage::is_project_table(&table) || rows == 0 {
+        return;
+    }
+    let summary = Summary {
+        text: format!("{} of {} row(s) of {} in {}", kind, rows, table, project),
+        kind,
+        table,
+        rows,
+        project,
+        statement: statement.to_string(),
+    };
+    match PENDING.lock().unwrap().as_mut() {
+        Some(pending) => pending.push(summary),
+        None => report(hook, &summary),
+    }
+}
+
+/// forget the writes of a transaction which ended without sending them, or failed to
+pub fn discard() {
+    PENDING.lock().unwrap().take();
+}
+
+fn report(hook: &Hook, summary: &Summary) {
+    if let Err(e) = run(hook, summary) {
+        eprintln!("Failed to run the hook of --on-mutation: {:#}", e);
+    }
+}
+
+fn run(hook: &Hook, summary: &Summary) -> Result<()> {
+    let body = serde_json::to_vec(summary)?;
+    match hook {
+        Hook::Webhook(url) => {
+            let response = reqwest::blocking::Client::builder()
+                .timeout(WEBHOOK_TIMEOUT)
+                .build()?
+                .post(url)
+                .header("content-type", "application/json")
+                .body(body)
+                .send()
+                .with_context(|| format!("Failed to send to {}", url))?;
+            if !response.status().is_success() {
+                return Err(anyhow!("{} responded with {}", url, response.status()));
+            }
+        }
+        Hook::Command(command) => {
+            let mut child = if cfg!(windows) {
+                Command::new("cmd")
+                    .args(["/C", command])
+                    .stdin(Stdio::piped())
+                    .spawn()
+            } else {
+                Command::new("sh")
+                    .args(["-c", command])
+                    .stdin(Stdio::piped())
+                    .spawn()
+            }
+            .with_context(|| format!("Failed to execute {}", command))?;
+            let written = child.stdin.take().expect("stdin is piped").write_all(&body);
+            match written {
+                // commands which don't read the summary
+                Err(e) if e.kind() != ErrorKind::BrokenPipe => {
+                    return Err(e).with_context(|| format!("Failed to write to {}", command))
+                }
+                _ => {}
+            }
+            let status = child.wait()?;
+            if !status.success() {
+                return Err(anyhow!("`{}` exited with {}", command, status));
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use gluesql::core::prelude::Value;
+use serde::{Deserialize, Serialize};
+
+use crate::output;
+
+/// `gh-sql/journal.jsonl` in the local data directory, e.g. `~/.local/share` on Linux
+pub fn default_path() -> Option<PathBuf> {
+    Some(
+        dirs_next::data_local_dir()?
+            .join("gh-sql")
+            .join("journal.jsonl"),
+    )
+}
+
+/// the field values changed by one statement, a line of the journal
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Entry {
+    pub owner: String,
+    pub project: i64,
+    pub at: String,
+    pub changes: Vec<Change>,
+}
+
+/// the field `field` (name) of the item `id` changed from `old` to `new`, `None` is empty
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Change {
+    pub id: String,
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl Change {
+    pub fn new(id: &str, field: &str, old: &Value, new: &Value) -> Self {
+        let to_string = |value: &Value| (!value.is_null()).then(|| output::value_to_string(value));
+        Self {
+            id: id.to_string(),
+            field: field.to_string(),
+            old: to_string(old),
+            new: to_string(new),
+        }
+    }
+}
+
+/// a JSON Lines file of the changes applied to projects, which `undo` reverses
+#[derive(Debug, Clone)]
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn record(&self, owner: &str, project: i64, changes: Vec<Change>) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+        let entry = Entry {
+            owner: owner.to_string(),
+            project,
+            at: Local::now().to_rfc3339(),
+            changes,
+        };
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open {}", self.path.display()))?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        Ok(())
+    }
+
+    /// the latest entry of the project and its line number
+    pub fn last(&self, owner: &str, project: i64) -> Result<Option<(usize, Entry)>> {
+        for (n, line) in self.lines()?.iter().enumerate().rev() {
+            let entry: Entry = serde_json::from_str(line)
+                .with_context(|| format!("{}:{}", self.path.display(), n + 1))?;
+            if entry.owner == owner && entry.project == project {
+                return Ok(Some((n, entry)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// replace the entry at the line number `n`, `None` removes it
+    pub fn replace(&self, n: usize, entry: Option<&Entry>) -> Result<()> {
+        let mut lines = self.lines()?;
+        match entry {
+            Some(entry) => lines[n] = serde_json::to_string(entry)?,
+            None => {
+                lines.remove(n);
+            }
+        }
+        let content: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+        fs::write(&self.path, content)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+
+    fn lines(&self) -> Result<Vec<String>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", self.path.display()))
+            }
+        };
+        Ok(content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+}
@@ -0,0 +1,37 @@
+//! The storage of gh-sql, which runs SQL on a GitHub project, and the rest of the command
+//! behind it. With the `mock` feature, [`storage::mock::MockProjectStorage`] runs the
+//! statements on a project loaded from a JSON fixture instead.
+
+pub mod apply;
+pub mod audit;
+pub mod batch;
+pub mod browser;
+pub mod cassette;
+pub mod checkpoint;
+pub mod clipboard;
+pub mod columns;
+pub mod comment;
+pub mod completions;
+pub mod copy;
+pub mod csv;
+pub mod doctor;
+pub mod explain;
+pub mod filter;
+pub mod functions;
+pub mod gh;
+pub mod helper;
+pub mod hook;
+pub mod journal;
+pub mod lint;
+pub mod output;
+pub mod prompt;
+pub mod report;
+pub mod schedule;
+pub mod schema;
+pub mod scratch;
+pub mod serve;
+pub mod sql;
+pub mod storage;
+pub mod telemetry;
+pub mod tui;
+pub mod undo;
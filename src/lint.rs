@@ -0,0 +1,134 @@
+use std::{
+    fmt::Debug,
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::PathBuf,
+};
+
+use anyhow::{anyhow, Context, Result};
+use gluesql::core::{
+    data::Value,
+    prelude::Glue,
+    store::{GStore, GStoreMut, Planner},
+};
+use serde::Deserialize;
+
+use crate::{
+    output::{Format, Stats, StatsFormat},
+    sql,
+};
+
+pub struct Opt {
+    pub format: Format,
+    pub rules: Option<PathBuf>,
+    pub no_builtin: bool,
+    pub stats: Option<StatsFormat>,
+}
+
+/// a violation is an item matching `condition`
+#[derive(Debug, Deserialize)]
+struct Rule {
+    name: String,
+    #[serde(rename = "where")]
+    condition: String,
+}
+
+pub struct Lint<S>
+where
+    S: GStore + GStoreMut + Planner,
+{
+    opt: Opt,
+    glue: Glue<S>,
+}
+
+impl<S> Lint<S>
+where
+    S: GStore + GStoreMut + Planner,
+{
+    pub fn new(opt: Opt, glue: Glue<S>) -> Self {
+        Self { opt, glue }
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        let (output, stats) = Stats::measure(|| self.check());
+        let (labels, violations, rule_count) = output?;
+        let count = violations.len();
+        let stdout = std::io::stdout();
+        let mut stdout = BufWriter::new(stdout.lock());
+        self.opt
+            .format
+            .print(&mut stdout, labels, violations, &stats)?;
+        stdout.flush()?;
+        drop(stdout);
+        if let Some(format) = self.opt.stats {
+            stats.report(format, "lint");
+        }
+        if count > 0 {
+            return Err(anyhow!("{} violation(s) of {} rule(s)", count, rule_count));
+        }
+        Ok(())
+    }
+
+    /// returns the labels and rows of the violations, and the number of rules checked
+    fn check(&mut self) -> Result<(Vec<String>, Vec<Vec<Value>>, usize)> {
+        let (columns, _) = sql::select(&mut self.glue, "SELECT * FROM items")?;
+        let title = column(&columns, "Title").context("Column not found: items.Title")?;
+        let mut rules = vec![];
+        if !self.opt.no_builtin {
+            rules.extend(builtin_rules(&columns));
+        }
+        if let Some(path) = &self.opt.rules {
+            let file = File::open(path).with_context(|| format!("{}", path.display()))?;
+            let config: Vec<Rule> = serde_json::from_reader(BufReader::new(file))
+                .with_context(|| format!("failed to parse rules: {}", path.display()))?;
+            rules.extend(config);
+        }
+
+        let mut violations = vec![];
+        for rule in &rules {
+            let statement = format!("SELECT id, {} FROM items WHERE {};", title, rule.condition);
+            let (_, rows) = sql::select(&mut self.glue, &statement)
+                .with_context(|| format!("rule \"{}\" failed", rule.name))?;
+            violations.extend(rows.into_iter().map(|mut row| {
+                row.insert(0, Value::Str(rule.name.clone()));
+                row
+            }));
+        }
+        let labels = vec!["rule".to_string(), "id".to_string(), title.to_string()];
+        Ok((labels, violations, rules.len()))
+    }
+}
+
+/// find a column regardless of `--snake-case-columns`
+fn column<'a>(columns: &'a [String], name: &str) -> Option<&'a str> {
+    let name = name.replace(' ', "_");
+    columns
+        .iter()
+        .find(|column| column.eq_ignore_ascii_case(&name))
+        .map(String::as_str)
+}
+
+/// rules over the fields most projects have; a rule is skipped if its fields don't exist
+fn builtin_rules(columns: &[String]) -> Vec<Rule> {
+    let mut rules = vec![];
+    let status = column(columns, "Status");
+    if let (Some(status), Some(assignees)) = (status, column(columns, "Assignees")) {
+        rules.push(Rule {
+            name: "in-progress-without-assignee".to_string(),
+            condition: format!("{} = 'In Progress' AND LIST_LEN({}) = 0", status, assignees),
+        });
+    }
+    if let Some(status) = status {
+        rules.push(Rule {
+            name: "missing-status".to_string(),
+            condition: format!("{} IS NULL", status),
+        });
+    }
+    if let Some(estimate) = column(columns, "Estimate") {
+        rules.push(Rule {
+            name: "missing-estimate".to_string(),
+            condition: format!("{} IS NULL", estimate),
+        });
+    }
+    rules
+}
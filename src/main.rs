@@ -1,45 +1,660 @@
-use anyhow::Result;
-use structopt::StructOpt;
+use std::{
+    io::{self, IsTerminal, Read},
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    process,
+};
 
-mod batch;
-mod gh;
-mod output;
-mod prompt;
-mod storage;
+use anyhow::{anyhow, Result};
+use gluesql::core::store::{GStore, GStoreMut, Planner};
+use structopt::{
+    clap::{AppSettings, Error as ClapError, ErrorKind, Shell},
+    StructOpt,
+};
+
+use gh_sql::{
+    apply, audit, batch, cassette, checkpoint, columns, comment, completions, csv, doctor, gh,
+    helper, hook, journal, lint, output, prompt, report, schedule, schema, serve, storage,
+    telemetry, undo,
+};
 
 #[derive(Debug, StructOpt)]
 #[structopt(name = "ghsql")]
 struct Opt {
-    #[structopt(name = "OWNER")]
+    #[structopt(
+        name = "OWNER",
+        help = "User or organization owning the project, as HOST/OWNER for another host than the default of gh"
+    )]
     owner: String,
     #[structopt(name = "PROJECT_NUMBER")]
-    project_number: u32,
+    project_number: Option<u32>,
     #[structopt(short, long, help = "SQL statement to execute")]
     execute: Option<String>,
     #[structopt(
         short,
         long,
         default_value = "table",
-        help = "\"table\", \"json\" or these initial"
+        help = "\"table\", \"json\", \"markdown\", \"csv\", \"gha\", \"dot\" or these initial, \"chart\" or \"json-envelope\""
     )]
     output: output::Format,
+    #[structopt(
+        long,
+        help = "Emit a \"notice\", \"warning\" or \"error\" annotation per row with `-o gha`"
+    )]
+    annotate: Option<output::Annotation>,
+    #[structopt(
+        long,
+        value_name = "TEMPLATE",
+        help = "Print a line per row with `{{Column}}` replaced by its value, e.g. \"{{Repository}}#{{Issue}}: {{Title}}\""
+    )]
+    template: Option<output::Template>,
+    #[structopt(
+        long,
+        help = "Open the issue or pull request of each result row in the browser, asking first if there are several"
+    )]
+    open: bool,
+    #[structopt(
+        long,
+        help = "Place the results on the clipboard in the format of `-o` instead of printing them"
+    )]
+    clip: bool,
+    #[structopt(
+        long,
+        conflicts_with_all = &["clip", "comment-to"],
+        help = "Show results in a full-screen table which can be scrolled, sorted and filtered"
+    )]
+    tui: bool,
+    #[structopt(
+        long,
+        env = "GH_SQL_ON_MUTATION",
+        value_name = "HOOK",
+        help = "After an INSERT, UPDATE or DELETE of the project, POST a JSON summary to a webhook URL or run a shell command with it on stdin"
+    )]
+    on_mutation: Option<hook::Hook>,
+    #[structopt(
+        long,
+        env = "GH_SQL_STATEMENT_TIMEOUT",
+        value_name = "DURATION",
+        help = "Abort a statement still running after this long, e.g. \"60s\" or \"5m\", stopping its further requests and failing with a timeout"
+    )]
+    statement_timeout: Option<gh::Timeout>,
+    #[structopt(
+        long,
+        default_value = "narrow",
+        value_name = "MODE",
+        help = "Width of ambiguous characters like ① in tables: \"narrow\", or \"cjk-wide\" for East Asian terminals"
+    )]
+    width_mode: output::WidthMode,
+    #[structopt(
+        long,
+        requires = "execute",
+        help = "Post the result as a Markdown table to an issue, pull request or discussion (`owner/repo#123`)"
+    )]
+    comment_to: Option<comment::Target>,
+    #[structopt(
+        long,
+        help = "Normalize column names like \"Linked pull requests\" to `linked_pull_requests`"
+    )]
+    snake_case_columns: bool,
+    #[structopt(
+        long,
+        default_value = "api",
+        value_name = "ORDER",
+        help = "Columns of `SELECT * FROM items`: \"api\" for all, \"view\" for the fields of the first table view in its order, or \"view:NAME\" for another view"
+    )]
+    column_order: storage::ColumnOrder,
+    #[structopt(
+        long,
+        env = "GH_SQL_HIDE_COLUMNS",
+        value_name = "COLUMNS",
+        help = "Columns left out of `SELECT *` but selectable by name, e.g. \"id,Repository\" of items or \"options.id\" of another table"
+    )]
+    hide_columns: Option<String>,
+    #[structopt(
+        long,
+        env = "GH_SQL_COMPUTED_COLUMNS",
+        parse(from_os_str),
+        value_name = "PATH",
+        help = "File of `name = expression` lines added as columns to `SELECT *`, by default columns/OWNER/PROJECT_NUMBER.sql in the gh-sql config directory if it exists"
+    )]
+    computed_columns: Option<PathBuf>,
+    #[structopt(
+        long = "mount-csv",
+        number_of_values = 1,
+        value_name = "NAME=PATH",
+        help = "Expose a CSV file as a read-only table (`name=path.csv`), can be repeated"
+    )]
+    csv_mounts: Vec<csv::Mount>,
+    #[cfg(feature = "mock")]
+    #[structopt(
+        long,
+        parse(from_os_str),
+        value_name = "FIXTURE",
+        help = "Run statements on the project described by a JSON file instead of GitHub"
+    )]
+    mock: Option<PathBuf>,
+    #[structopt(
+        short,
+        long,
+        help = "Do not ask for confirmation of destructive operations like `DROP COLUMN`, nor of `--open`"
+    )]
+    yes: bool,
+    #[structopt(
+        long,
+        help = "Write to items even if someone else changed them since they were fetched"
+    )]
+    force: bool,
+    #[structopt(
+        long,
+        default_value = "continue",
+        value_name = "MODE",
+        help = "After a change of an `UPDATE` failed, \"continue\" sends the remaining ones and \"abort\" skips them"
+    )]
+    on_error: storage::OnError,
+    #[structopt(
+        long,
+        parse(from_os_str),
+        value_name = "PATH",
+        help = "File where changed field values are recorded for `undo`, by default journal.jsonl in the local data directory"
+    )]
+    journal: Option<PathBuf>,
+    #[structopt(
+        long,
+        help = "Scan items from the first page even if an earlier scan of the project failed midway"
+    )]
+    no_resume: bool,
+    #[structopt(
+        long,
+        help = "Keep the first 10 labels and assignees of an item instead of fetching the rest, for speed"
+    )]
+    truncate_lists: bool,
+    #[structopt(
+        long,
+        parse(from_os_str),
+        value_name = "PATH",
+        help = "Append the mutations sent to GitHub to a file as SQL statements with the time and the user"
+    )]
+    audit_file: Option<PathBuf>,
+    #[structopt(
+        long,
+        value_name = "HOST",
+        help = "Host of gh to use instead of its default, e.g. a GitHub Enterprise Server (also GH_HOST)"
+    )]
+    host: Option<String>,
+    #[structopt(
+        long,
+        value_name = "LOGIN",
+        help = "Account of gh to send requests as when several are logged in to the host, instead of the active one"
+    )]
+    account: Option<String>,
+    #[structopt(
+        long,
+        parse(from_os_str),
+        value_name = "PATH",
+        help = "PEM file of certificates to trust when requests are sent with GH_TOKEN, e.g. of a proxy or GitHub Enterprise Server"
+    )]
+    ca_cert: Option<PathBuf>,
+    #[structopt(
+        long,
+        parse(from_os_str),
+        value_name = "DIR",
+        conflicts_with = "replay",
+        help = "Save every GraphQL request and response to DIR, to be served back with --replay"
+    )]
+    record: Option<PathBuf>,
+    #[structopt(
+        long,
+        parse(from_os_str),
+        value_name = "DIR",
+        help = "Serve the responses saved by --record in DIR instead of sending requests to GitHub"
+    )]
+    replay: Option<PathBuf>,
+    #[structopt(
+        long,
+        default_value = "emacs",
+        help = "Key bindings of the interactive prompt, \"emacs\" or \"vi\""
+    )]
+    keymap: prompt::Keymap,
+    #[structopt(
+        long,
+        default_value = "100",
+        help = "Number of lines kept in the history of the interactive prompt"
+    )]
+    history_size: usize,
+    #[structopt(
+        long,
+        default_value = "{owner}/{number}{tx} ghsql> ",
+        help = "Prompt of the interactive mode, `{owner}` and `{number}` are replaced with the project's, `{tx}` with `*` in a transaction"
+    )]
+    prompt: String,
+    #[structopt(
+        long,
+        require_equals = true,
+        value_name = "FORMAT",
+        help = "Print the requests, rate limit points, bytes, cache hits and time of each statement to stderr, as \"text\" (default) or \"json\" (`--stats=json`)"
+    )]
+    stats: Option<Option<output::StatsFormat>>,
+    #[structopt(
+        long,
+        default_value = "text",
+        value_name = "FORMAT",
+        help = "Print failures to stderr as \"text\" or as \"json\" objects with a stable `code` like \"sql_error\" or \"rate_limited\""
+    )]
+    errors: output::ErrorFormat,
+    #[structopt(
+        long,
+        require_equals = true,
+        value_name = "FORMAT",
+        help = "Log statements, GraphQL requests and fetches with their duration to stderr, as \"text\" (default) or \"json\" (`--log=json`)"
+    )]
+    log: Option<Option<telemetry::LogFormat>>,
+    #[cfg(feature = "otlp")]
+    #[structopt(
+        long,
+        value_name = "URL",
+        env = "OTEL_EXPORTER_OTLP_ENDPOINT",
+        help = "Export the spans of --log to an OpenTelemetry collector over OTLP/HTTP, e.g. http://localhost:4318"
+    )]
+    otlp_endpoint: Option<String>,
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Serve SQL over HTTP (`POST /query`)
+    Serve {
+        #[structopt(long, default_value = "127.0.0.1:8080", help = "Address to listen on")]
+        listen: SocketAddr,
+        #[structopt(
+            long,
+            env = "GH_SQL_SERVE_TOKEN",
+            hide_env_values = true,
+            help = "Token requests must send as `Authorization: Bearer TOKEN`, a random one is printed at startup if not given"
+        )]
+        token: Option<String>,
+        #[structopt(
+            long,
+            help = "Run statements changing the project too, not only SELECT and EXPLAIN"
+        )]
+        allow_writes: bool,
+    },
+    /// Apply field updates read from stdin as rows of `id,field,value`
+    Apply {
+        #[structopt(long, default_value = "csv", help = "\"csv\" or \"json\"")]
+        format: apply::InputFormat,
+        #[structopt(
+            long,
+            default_value = "50",
+            help = "Number of updates sent in one request"
+        )]
+        batch_size: usize,
+    },
+    /// Create a project for OWNER, copying the fields of PROJECT_NUMBER if given
+    CreateProject {
+        #[structopt(long, help = "Title of the new project")]
+        title: String,
+    },
+    /// Report items violating the built-in rules and those in RULES, failing if there are any
+    Lint {
+        #[structopt(
+            long,
+            parse(from_os_str),
+            help = "JSON file of additional rules (`[{\"name\": \"...\", \"where\": \"...\"}]`)"
+        )]
+        rules: Option<PathBuf>,
+        #[structopt(long, help = "Only check the rules in RULES")]
+        no_builtin: bool,
+    },
+    /// Print the tables and columns with their types, writability and single select options
+    Schema {
+        #[structopt(long, default_value = "sql", help = "\"sql\" or \"json\"")]
+        format: schema::SchemaFormat,
+    },
+    /// Print a report computed from the project tables
+    Report {
+        #[structopt(subcommand)]
+        kind: report::Kind,
+    },
+    /// Run the queries of a file of reports, each to its own destination
+    Schedule {
+        #[structopt(subcommand)]
+        action: schedule::Action,
+    },
+    /// Check gh, the authentication, the API and access to PROJECT_NUMBER, printing fixes for
+    /// the problems found
+    Doctor,
+    /// Revert field values changed by gh-sql, as recorded in the journal
+    Undo {
+        #[structopt(long, help = "Revert the changes of the latest statement")]
+        last: bool,
+        #[structopt(
+            long,
+            default_value = "50",
+            help = "Number of updates sent in one request"
+        )]
+        batch_size: usize,
+    },
+    /// Print the completion script of SHELL, given without OWNER
+    Completions {
+        #[structopt(
+            possible_values = &Shell::variants(),
+            case_insensitive = true,
+            required_unless = "projects",
+            help = "\"bash\", \"zsh\", \"fish\", \"powershell\" or \"elvish\""
+        )]
+        shell: Option<Shell>,
+        #[structopt(
+            long,
+            hidden = true,
+            help = "Print the projects the scripts complete, as OWNER PROJECT_NUMBER lines"
+        )]
+        projects: bool,
+    },
+}
+
+/// whether `gh` runs this binary as the `sql` extension, which it installs as
+/// `extensions/gh-sql/gh-sql` in its data directory
+fn is_gh_extension() -> bool {
+    let Ok(exe) = std::env::current_exe() else {
+        return false;
+    };
+    exe.parent()
+        .and_then(Path::parent)
+        .and_then(Path::file_name)
+        == Some("extensions".as_ref())
 }
 
-fn main() -> Result<()> {
-    let opt = Opt::from_args();
-    let storage = storage::ProjectNextStorage::new(opt.owner, opt.project_number as i64)?;
-    let glue = gluesql::prelude::Glue::new(storage);
+fn main() {
+    if let Err(e) = run() {
+        // the failures of statements are printed as they happen
+        if e.downcast_ref::<output::Reported>().is_none() {
+            output::report_error(&e);
+        }
+        process::exit(output::Failure::of(&e).exit_code());
+    }
+}
+
+fn run() -> Result<()> {
+    // for `completions`, which is about the command rather than a project
+    let mut app = Opt::clap().setting(AppSettings::SubcommandsNegateReqs);
+    if is_gh_extension() {
+        app = app.bin_name("gh sql");
+    }
+    let matches = app.get_matches();
+    if matches.subcommand_matches("completions").is_some() {
+        let Command::Completions { shell, projects } = Command::from_clap(&matches) else {
+            unreachable!();
+        };
+        return match shell {
+            Some(shell) if !projects => completions::print(Opt::clap(), shell),
+            _ => completions::print_projects(),
+        };
+    }
+    if !matches.is_present("OWNER") {
+        ClapError::with_description(
+            "The following required arguments were not provided:\n    <OWNER>",
+            ErrorKind::MissingRequiredArgument,
+        )
+        .exit();
+    }
+    let mut opt = Opt::from_clap(&matches);
+    output::set_error_format(opt.errors);
+    // like `gh -R HOST/OWNER/REPO`
+    let hostname = match opt.owner.split_once('/') {
+        Some((hostname, owner))
+            if hostname.is_empty() || owner.is_empty() || owner.contains('/') =>
+        {
+            return Err(anyhow!("OWNER must be OWNER or HOST/OWNER: {}", opt.owner));
+        }
+        Some((hostname, _)) if opt.host.as_deref().is_some_and(|host| host != hostname) => {
+            return Err(anyhow!(
+                "--host differs from the host of OWNER: {}",
+                opt.owner
+            ));
+        }
+        Some((hostname, owner)) => {
+            let hostname = hostname.to_string();
+            opt.owner = owner.to_string();
+            Some(hostname)
+        }
+        None => opt.host.take(),
+    };
+    let _telemetry = telemetry::init(telemetry::Opt {
+        log: opt
+            .log
+            .map(|format| format.unwrap_or(telemetry::LogFormat::Text)),
+        #[cfg(feature = "otlp")]
+        otlp_endpoint: opt.otlp_endpoint.take(),
+    })?;
+    let cassette = match (&opt.record, &opt.replay) {
+        (Some(dir), _) => Some(cassette::Cassette::new(
+            dir.clone(),
+            cassette::Mode::Record,
+        )?),
+        (_, Some(dir)) => Some(cassette::Cassette::new(
+            dir.clone(),
+            cassette::Mode::Replay,
+        )?),
+        (None, None) => None,
+    };
+    gh::init(gh::Opt {
+        ca_cert: opt.ca_cert.clone(),
+        cassette,
+        hostname,
+        account: opt.account.take(),
+    })?;
+    let stats = opt
+        .stats
+        .map(|format| format.unwrap_or(output::StatsFormat::Text));
+    output::set_width_mode(opt.width_mode);
+    if let Some(timeout) = opt.statement_timeout {
+        gh::set_statement_timeout(timeout);
+    }
+    if let Some(hide_columns) = &opt.hide_columns {
+        columns::hide(hide_columns);
+    }
+    let computed_columns = opt.computed_columns.take().or_else(|| {
+        let project_number = opt.project_number?;
+        columns::computed_path(&opt.owner, project_number.into()).filter(|path| path.exists())
+    });
+    if let Some(path) = computed_columns {
+        columns::load_computed(&path)?;
+    }
+    if let Some(hook) = opt.on_mutation.take() {
+        let project = match opt.project_number {
+            Some(project_number) => format!("{}/{}", opt.owner, project_number),
+            None => opt.owner.clone(),
+        };
+        hook::set(hook, project);
+    }
+    let storage_opt = storage::Opt {
+        snake_case_columns: opt.snake_case_columns,
+        csv_mounts: std::mem::take(&mut opt.csv_mounts),
+        yes: opt.yes,
+        // `serve` would wait for an answer on its terminal instead of responding
+        interactive: !matches!(opt.command, Some(Command::Serve { .. })),
+        force: opt.force,
+        on_error: opt.on_error,
+        journal: opt
+            .journal
+            .take()
+            .or_else(journal::default_path)
+            .map(journal::Journal::new),
+        audit: opt.audit_file.take().map(audit::Audit::new),
+        column_order: opt.column_order.clone(),
+        // a resumed scan would leave its first pages out of a recording, and serve pages
+        // which a replay doesn't have
+        checkpoints: (!opt.no_resume && opt.record.is_none() && opt.replay.is_none())
+            .then(checkpoint::default_dir)
+            .flatten(),
+        truncate_lists: opt.truncate_lists,
+    };
+
+    #[cfg(feature = "mock")]
+    if let Some(fixture) = opt.mock.take() {
+        if matches!(
+            opt.command,
+            Some(
+                Command::Apply { .. }
+                    | Command::CreateProject { .. }
+                    | Command::Doctor
+                    | Command::Schema { .. }
+                    | Command::Undo { .. }
+            )
+        ) {
+            return Err(anyhow!("--mock only runs statements"));
+        }
+        let storage = storage::mock::MockProjectStorage::load(&fixture, storage_opt)?;
+        return run_statements(opt, gluesql::core::prelude::Glue::new(storage), stats);
+    }
+
+    if let Some(Command::CreateProject { title }) = opt.command {
+        let project = storage::create_project(&opt.owner, title)?;
+        if let Some(project_number) = opt.project_number {
+            let source =
+                storage::ProjectNextStorage::new(opt.owner, project_number as i64, storage_opt)?;
+            for name in source.copy_fields_to(project.number)? {
+                eprintln!("Could not copy field \"{}\"", name);
+            }
+        }
+        println!("{}", project.url);
+        return Ok(());
+    }
+    if let Some(Command::Doctor) = opt.command {
+        let doctor_opt = doctor::Opt {
+            owner: opt.owner.clone(),
+            project_number: opt.project_number.map(i64::from),
+        };
+        return doctor::run(doctor_opt, |project_number| {
+            storage::ProjectNextStorage::new(opt.owner, project_number, storage_opt)
+        });
+    }
+    let project_number = opt
+        .project_number
+        .ok_or_else(|| anyhow!("PROJECT_NUMBER is required"))?;
+    let journal = storage_opt.journal.clone();
+    let storage =
+        storage::ProjectNextStorage::new(opt.owner.clone(), project_number as i64, storage_opt)?;
+
+    if let Some(Command::Apply { format, batch_size }) = opt.command {
+        let apply_opt = apply::Opt { format, batch_size };
+        let mut apply = apply::Apply::new(apply_opt, storage);
+        return apply.run();
+    }
+    if let Some(Command::Undo { last, batch_size }) = opt.command {
+        if !last {
+            return Err(anyhow!("Pass --last to revert the latest changes"));
+        }
+        let undo_opt = undo::Opt {
+            journal: journal.ok_or_else(|| anyhow!("--journal is required to undo"))?,
+            owner: opt.owner,
+            project_number: project_number as i64,
+            batch_size,
+        };
+        let mut undo = undo::Undo::new(undo_opt, storage);
+        return undo.run();
+    }
+    if let Some(Command::Schema { format }) = opt.command {
+        return schema::print(&storage, format);
+    }
+    let glue = gluesql::core::prelude::Glue::new(storage);
+    run_statements(opt, glue, stats)
+}
 
-    if let Some(statement) = opt.execute {
+/// run the statements of `serve`, `lint`, `report`, `-e`, stdin or the prompt
+fn run_statements<S>(
+    opt: Opt,
+    glue: gluesql::core::prelude::Glue<S>,
+    stats: Option<output::StatsFormat>,
+) -> Result<()>
+where
+    S: GStore + GStoreMut + Planner + prompt::Session + Send,
+{
+    let format = opt
+        .output
+        .with_annotate(opt.annotate)?
+        .with_template(opt.template)?;
+    if let Some(Command::Serve {
+        listen,
+        token,
+        allow_writes,
+    }) = opt.command
+    {
+        let serve_opt = serve::Opt {
+            listen,
+            token,
+            allow_writes,
+        };
+        let mut server = serve::Server::new(serve_opt, glue)?;
+        server.run()
+    } else if let Some(Command::Lint { rules, no_builtin }) = opt.command {
+        let lint_opt = lint::Opt {
+            format,
+            rules,
+            no_builtin,
+            stats,
+        };
+        let mut lint = lint::Lint::new(lint_opt, glue);
+        lint.run()
+    } else if let Some(Command::Report { kind }) = opt.command {
+        let report_opt = report::Opt {
+            format,
+            kind,
+            stats,
+        };
+        let mut report = report::Report::new(report_opt, glue);
+        report.run()
+    } else if let Some(Command::Schedule { action }) = opt.command {
+        let schedule_opt = schedule::Opt {
+            format,
+            action,
+            stats,
+        };
+        let mut schedule = schedule::Schedule::new(schedule_opt, glue);
+        schedule.run()
+    } else if let Some(statement) = opt.execute {
         let batch_opt = batch::Opt {
-            format: opt.output,
+            format,
             statement,
+            comment_to: opt.comment_to,
+            stats,
+            open: opt.open,
+            yes: opt.yes,
+            clip: opt.clip,
+            tui: opt.tui,
+        };
+        let mut batch = batch::Batch::new(batch_opt, glue);
+        batch.run()
+    } else if !io::stdin().is_terminal() {
+        // piped statements, e.g. `echo 'SELECT ...' | gh sql OWNER PROJECT_NUMBER`
+        let mut statement = String::new();
+        io::stdin().read_to_string(&mut statement)?;
+        let batch_opt = batch::Opt {
+            format,
+            statement,
+            comment_to: opt.comment_to,
+            stats,
+            open: opt.open,
+            yes: opt.yes,
+            clip: opt.clip,
+            tui: opt.tui,
         };
         let mut batch = batch::Batch::new(batch_opt, glue);
         batch.run()
     } else {
-        let prompt_opt = prompt::Opt { format: opt.output };
-        let rl = rustyline::Editor::<()>::new();
+        let prompt_opt = prompt::Opt {
+            format,
+            template: opt.prompt,
+            stats,
+            tui: opt.tui,
+        };
+        let config = rustyline::Config::builder()
+            .edit_mode(opt.keymap.into())
+            .max_history_size(opt.history_size)
+            .build();
+        let mut rl = rustyline::Editor::with_config(config);
+        rl.set_helper(Some(helper::SqlHelper::default()));
         let mut prompt = prompt::Prompt::new(prompt_opt, glue, rl);
         prompt.run()
     }
@@ -1,14 +1,30 @@
-use std::{fmt, io, str::FromStr};
+use std::{
+    env, fmt,
+    fs::OpenOptions,
+    io::{self, Write},
+    str::FromStr,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
 
 use anyhow::{anyhow, Error, Result};
-use gluesql::data::Value;
-use gluesql::result::Error as SqlError;
-use unicode_width::UnicodeWidthStr;
+use gluesql::core::data::Value;
+use gluesql::core::error::Error as SqlError;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthChar;
+
+use crate::{
+    gh,
+    storage::{error_cause, PartialFailure, StorageError},
+};
 
 /// display anyhow::Error in a more readable way
 pub fn error_to_string(e: SqlError) -> String {
-    if let SqlError::Storage(e) = &e {
-        format!("{e:?}")
+    if let Some(cause) = error_cause(&e) {
+        match cause.downcast_ref::<StorageError>() {
+            Some(e) => format!("{e:?}"),
+            None => format!("{cause:?}"),
+        }
     } else {
         format!("{e}")
     }
@@ -18,6 +34,20 @@ pub fn error_to_string(e: SqlError) -> String {
 pub enum Format {
     Table,
     Json,
+    Markdown,
+    Csv,
+    /// one JSON object of the columns, rows and [`Stats`]
+    JsonEnvelope,
+    /// horizontal bar chart of a label and a number column
+    Chart,
+    /// Graphviz digraph of parent and child (and edge label) columns
+    Dot,
+    /// Markdown job summary for GitHub Actions, optionally with one annotation per row
+    Gha {
+        annotate: Option<Annotation>,
+    },
+    /// a line per row of `--template`
+    Template(Template),
 }
 
 impl FromStr for Format {
@@ -27,25 +57,462 @@ impl FromStr for Format {
         match s {
             "t" | "table" => Ok(Format::Table),
             "j" | "json" => Ok(Format::Json),
+            "m" | "markdown" => Ok(Format::Markdown),
+            "c" | "csv" => Ok(Format::Csv),
+            "chart" => Ok(Format::Chart),
+            "json-envelope" => Ok(Format::JsonEnvelope),
+            "d" | "dot" => Ok(Format::Dot),
+            "g" | "gha" => Ok(Format::Gha { annotate: None }),
             other => Err(anyhow!("Unknown format: {}", other)),
         }
     }
 }
 
+/// severity of a GitHub Actions workflow command
+#[derive(Debug, Clone, Copy)]
+pub enum Annotation {
+    Notice,
+    Warning,
+    Error,
+}
+
+impl FromStr for Annotation {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "notice" => Ok(Annotation::Notice),
+            "warning" => Ok(Annotation::Warning),
+            "error" => Ok(Annotation::Error),
+            other => Err(anyhow!("Unknown annotation level: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Annotation::Notice => "notice",
+            Annotation::Warning => "warning",
+            Annotation::Error => "error",
+        })
+    }
+}
+
 impl Format {
+    pub fn with_annotate(self, annotate: Option<Annotation>) -> Result<Self> {
+        match (self, annotate) {
+            (Format::Gha { .. }, annotate) => Ok(Format::Gha { annotate }),
+            (format, None) => Ok(format),
+            (_, Some(_)) => Err(anyhow!("--annotate can only be used with `-o gha`")),
+        }
+    }
+
+    pub fn with_template(self, template: Option<Template>) -> Result<Self> {
+        match (self, template) {
+            (format, None) => Ok(format),
+            (Format::Table, Some(template)) => Ok(Format::Template(template)),
+            (_, Some(_)) => Err(anyhow!("--template can not be used with `-o`")),
+        }
+    }
+
     pub fn print<W: io::Write>(
         &self,
         w: W,
         labels: Vec<String>,
         rows: Vec<Vec<Value>>,
+        stats: &Stats,
     ) -> Result<()> {
         match self {
             Format::Table => print_as_table(w, labels, rows),
             Format::Json => print_as_json(w, labels, rows),
+            Format::Markdown => print_as_markdown(w, &labels, &rows),
+            Format::Csv => print_as_csv(w, Some(&labels), &rows),
+            Format::JsonEnvelope => print_as_json_envelope(w, labels, rows, stats),
+            Format::Chart => print_as_chart(w, &labels, &rows),
+            Format::Dot => print_as_dot(w, &labels, &rows),
+            Format::Gha { annotate } => print_as_gha(w, *annotate, labels, rows),
+            Format::Template(template) => template.print(w, &labels, &rows),
+        }
+    }
+}
+
+/// text with `{{Column}}` placeholders, printed once per row with the values of the columns
+#[derive(Debug)]
+pub struct Template {
+    parts: Vec<TemplatePart>,
+}
+
+#[derive(Debug)]
+enum TemplatePart {
+    Text(String),
+    Column(String),
+}
+
+impl FromStr for Template {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = vec![];
+        let mut rest = s;
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start..].find("}}") else {
+                return Err(anyhow!("Unclosed {{{{ in template: {}", s));
+            };
+            if start > 0 {
+                parts.push(TemplatePart::Text(rest[..start].to_string()));
+            }
+            let column = rest[start + 2..start + end].trim();
+            parts.push(TemplatePart::Column(column.to_string()));
+            rest = &rest[start + end + 2..];
+        }
+        if !rest.is_empty() {
+            parts.push(TemplatePart::Text(rest.to_string()));
+        }
+        Ok(Self { parts })
+    }
+}
+
+impl Template {
+    /// a line per row, with the values as printed in a table
+    fn print<W: io::Write>(&self, mut w: W, labels: &[String], rows: &[Vec<Value>]) -> Result<()> {
+        let indexes = self
+            .parts
+            .iter()
+            .filter_map(|part| match part {
+                TemplatePart::Text(_) => None,
+                TemplatePart::Column(column) => Some(
+                    labels
+                        .iter()
+                        .position(|label| label == column)
+                        .ok_or_else(|| anyhow!("Unknown column in template: {}", column)),
+                ),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        for row in rows {
+            let mut indexes = indexes.iter();
+            let mut line = String::new();
+            for part in &self.parts {
+                match part {
+                    TemplatePart::Text(text) => line.push_str(text),
+                    TemplatePart::Column(_) => {
+                        let idx = indexes.next().expect("an index per column");
+                        print_value_in_table(&mut line, &row[*idx])?;
+                    }
+                }
+            }
+            writeln!(w, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// how wide the terminal draws characters of ambiguous width like `①` or `→`
+#[derive(Debug, Clone, Copy, Default)]
+pub enum WidthMode {
+    /// one column, as in most western locales
+    #[default]
+    Narrow,
+    /// two columns, as East Asian terminals do
+    CjkWide,
+}
+
+impl FromStr for WidthMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "narrow" => Ok(WidthMode::Narrow),
+            "cjk-wide" => Ok(WidthMode::CjkWide),
+            other => Err(anyhow!("Unknown width mode: {}", other)),
+        }
+    }
+}
+
+static WIDTH_MODE: OnceLock<WidthMode> = OnceLock::new();
+
+/// set how tables and charts measure text, before anything is printed
+pub fn set_width_mode(mode: WidthMode) {
+    let _ = WIDTH_MODE.set(mode);
+}
+
+/// the columns `s` takes in a terminal, per grapheme cluster so that an emoji sequence
+/// counts once
+pub fn display_width(s: &str) -> usize {
+    let cjk = matches!(WIDTH_MODE.get(), Some(WidthMode::CjkWide));
+    let width = |c: char| if cjk { c.width_cjk() } else { c.width() }.unwrap_or(0);
+    s.graphemes(true)
+        .map(|grapheme| {
+            // VS16 asks for the emoji presentation of a narrow character like `❤`
+            if grapheme.contains('\u{FE0F}') {
+                return 2;
+            }
+            // the characters joined to the first, like `👩‍💻`, are drawn into its cell
+            if grapheme.contains('\u{200D}') {
+                return grapheme.chars().next().map_or(0, width);
+            }
+            grapheme.chars().map(width).sum()
+        })
+        .sum()
+}
+
+/// measured while executing a statement
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Stats {
+    pub elapsed: Duration,
+    pub usage: gh::Usage,
+}
+
+impl Stats {
+    pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Stats) {
+        let (start, usage) = (Instant::now(), gh::usage());
+        let output = f();
+        let stats = Stats {
+            elapsed: start.elapsed(),
+            usage: gh::usage() - usage,
+        };
+        (output, stats)
+    }
+
+    /// print to stderr what `subject` (a statement or command) used, for `--stats`
+    pub fn report(&self, format: StatsFormat, subject: &str) {
+        let Stats { elapsed, usage } = self;
+        match format {
+            StatsFormat::Text => eprintln!(
+                "{} request(s), {} point(s), {} byte(s), {} cache hit(s) in {:.3}s: {}",
+                usage.requests,
+                usage.cost,
+                usage.bytes,
+                usage.cache_hits,
+                elapsed.as_secs_f64(),
+                subject.lines().next().unwrap_or_default(),
+            ),
+            StatsFormat::Json => eprintln!(
+                "{}",
+                serde_json::json!({
+                    "statement": subject,
+                    "api_calls": usage.requests,
+                    "cost": usage.cost,
+                    "bytes": usage.bytes,
+                    "cache_hits": usage.cache_hits,
+                    "elapsed_ms": elapsed.as_millis() as u64,
+                })
+            ),
+        }
+    }
+}
+
+/// how `--stats` prints [`Stats`]
+#[derive(Debug, Clone, Copy)]
+pub enum StatsFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for StatsFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(StatsFormat::Text),
+            "json" => Ok(StatsFormat::Json),
+            other => Err(anyhow!("Unknown stats format: {}", other)),
+        }
+    }
+}
+
+/// how failures are printed to stderr, as prose or, for `--errors json`, as one JSON object
+/// with a stable `code` per failure
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for ErrorFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(ErrorFormat::Text),
+            "json" => Ok(ErrorFormat::Json),
+            other => Err(anyhow!("Unknown error format: {}", other)),
         }
     }
 }
 
+static ERROR_FORMAT: OnceLock<ErrorFormat> = OnceLock::new();
+
+/// set how failures are printed, before anything is executed
+pub fn set_error_format(format: ErrorFormat) {
+    let _ = ERROR_FORMAT.set(format);
+}
+
+pub fn error_format() -> ErrorFormat {
+    ERROR_FORMAT.get().copied().unwrap_or_default()
+}
+
+/// the class of a failure, which decides the exit code and the `code` of `--errors json`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// a statement which can't be parsed
+    Syntax,
+    /// a statement which is rejected
+    Sql,
+    /// invalid arguments and other mistakes of the user
+    Other,
+    Auth,
+    RateLimit,
+    /// no response from GitHub, or a server error which may go away
+    Network,
+    /// another failure of GitHub or of reading the project
+    Api,
+    /// changes of which only some were applied
+    Partial,
+    Interrupted,
+    /// a statement which took longer than `--statement-timeout`
+    Timeout,
+}
+
+impl Failure {
+    pub fn code(self) -> &'static str {
+        match self {
+            Failure::Syntax => "syntax_error",
+            Failure::Sql => "sql_error",
+            Failure::Other => "error",
+            Failure::Auth => "auth_error",
+            Failure::RateLimit => "rate_limited",
+            Failure::Network => "network_error",
+            Failure::Api => "api_error",
+            Failure::Partial => "partial_failure",
+            Failure::Interrupted => "interrupted",
+            Failure::Timeout => "timeout",
+        }
+    }
+
+    /// part of the CLI as documented in the README, keep them stable
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Failure::Syntax | Failure::Sql | Failure::Other => 1,
+            Failure::Auth => 2,
+            Failure::RateLimit => 3,
+            Failure::Network => 4,
+            Failure::Api => 5,
+            Failure::Partial => 6,
+            // like a shell reports SIGINT
+            Failure::Interrupted => 130,
+            // like `timeout` of coreutils
+            Failure::Timeout => 124,
+        }
+    }
+
+    pub fn of(e: &Error) -> Failure {
+        if let Some(Reported(failure)) = e.downcast_ref() {
+            return *failure;
+        }
+        if e.downcast_ref::<PartialFailure>().is_some() {
+            return Failure::Partial;
+        }
+        let kind = e
+            .downcast_ref::<gh::RequestFailure>()
+            .map(|failure| failure.kind);
+        match kind {
+            Some(gh::FailureKind::Auth) => Failure::Auth,
+            Some(gh::FailureKind::RateLimit) => Failure::RateLimit,
+            Some(gh::FailureKind::Network) => Failure::Network,
+            Some(gh::FailureKind::Interrupted) => Failure::Interrupted,
+            Some(gh::FailureKind::Timeout) => Failure::Timeout,
+            None => Failure::Other,
+        }
+    }
+
+    fn of_sql(e: &SqlError) -> Failure {
+        match e {
+            SqlError::Parser(_) => Failure::Syntax,
+            SqlError::StorageMsg(_) => match error_cause(e) {
+                Some(cause) => match cause.downcast_ref::<StorageError>() {
+                    Some(StorageError(e)) => match Failure::of(e) {
+                        Failure::Other => Failure::Api,
+                        failure => failure,
+                    },
+                    None => Failure::Other,
+                },
+                None => Failure::Sql,
+            },
+            _ => Failure::Sql,
+        }
+    }
+}
+
+/// a failure already printed by [`report_sql_error`], which ends the command with its
+/// exit code
+#[derive(Debug)]
+pub struct Reported(pub Failure);
+
+impl fmt::Display for Reported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "statement failed ({})", self.0.code())
+    }
+}
+
+impl std::error::Error for Reported {}
+
+/// print the failure of a statement to stderr
+pub fn report_sql_error(e: SqlError) -> Failure {
+    let failure = Failure::of_sql(&e);
+    match error_format() {
+        ErrorFormat::Text => eprintln!("SQL execution error: {}", error_to_string(e)),
+        ErrorFormat::Json => {
+            let cause = error_cause(&e);
+            let partial = cause
+                .as_ref()
+                .and_then(|cause| cause.downcast_ref::<StorageError>())
+                .and_then(|StorageError(e)| e.downcast_ref());
+            eprintln!("{}", error_to_json(failure, &format!("{:#}", e), partial));
+        }
+    }
+    failure
+}
+
+/// print the error ending the command to stderr
+pub fn report_error(e: &Error) {
+    match error_format() {
+        // as Rust does for an error returned from `main`
+        ErrorFormat::Text => eprintln!("Error: {:?}", e),
+        ErrorFormat::Json => {
+            let message = format!("{:#}", e);
+            let error = error_to_json(Failure::of(e), &message, e.downcast_ref());
+            eprintln!("{}", error);
+        }
+    }
+}
+
+fn error_to_json(
+    failure: Failure,
+    message: &str,
+    partial: Option<&PartialFailure>,
+) -> serde_json::Value {
+    let mut error = serde_json::json!({
+        "code": failure.code(),
+        "message": message,
+    });
+    if let Some(PartialFailure { applied, failures }) = partial {
+        error["applied"] = (*applied).into();
+        error["failures"] = failures
+            .iter()
+            .map(|(id, column, error)| {
+                serde_json::json!({
+                    "id": id,
+                    "column": column,
+                    "error": error,
+                })
+            })
+            .collect();
+    }
+    error
+}
+
 fn print_as_table<W: io::Write>(
     mut w: W,
     labels: Vec<String>,
@@ -58,13 +525,16 @@ fn print_as_table<W: io::Write>(
                 .map(|value| {
                     let mut s = String::new();
                     print_value_in_table(&mut s, &value)?;
-                    let width = s.width();
+                    let width = display_width(&s);
                     Ok((s, width))
                 })
                 .collect::<Result<Vec<_>>>()
         })
         .collect::<Result<Vec<_>>>()?;
-    let label_widths = labels.iter().map(|label| label.width()).collect::<Vec<_>>();
+    let label_widths = labels
+        .iter()
+        .map(|label| display_width(label))
+        .collect::<Vec<_>>();
     let column_widths = rows.iter().fold(label_widths, |mut widths, row| {
         for (max_width, (_, width)) in widths.iter_mut().zip(row) {
             *max_width = (*max_width).max(*width);
@@ -78,7 +548,7 @@ fn print_as_table<W: io::Write>(
             "{}{:pad$} | ",
             label,
             "",
-            pad = max_width - label.width()
+            pad = max_width - display_width(&label)
         )?;
     }
     w.write_all(b"\n")?;
@@ -92,6 +562,197 @@ fn print_as_table<W: io::Write>(
     Ok(())
 }
 
+const CHART_WIDTH: f64 = 50.0;
+
+fn print_as_chart<W: io::Write>(mut w: W, labels: &[String], rows: &[Vec<Value>]) -> Result<()> {
+    if labels.len() != 2 {
+        return Err(anyhow!(
+            "chart needs 2 columns (a label and a number), got {}",
+            labels.len()
+        ));
+    }
+    let bars = rows
+        .iter()
+        .map(|row| {
+            let mut label = String::new();
+            print_value_in_table(&mut label, &row[0])?;
+            let count = match &row[1] {
+                Value::I64(i) => *i as f64,
+                Value::F64(f) => *f,
+                Value::Null => 0.0,
+                other => return Err(anyhow!("chart needs a number, got {:?}", other)),
+            };
+            Ok((label, count))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let label_width = bars
+        .iter()
+        .map(|(label, _)| display_width(label))
+        .max()
+        .unwrap_or(0);
+    let max = bars.iter().map(|(_, count)| *count).fold(0.0, f64::max);
+    for (label, count) in bars {
+        let len = if max > 0.0 {
+            (count.max(0.0) / max * CHART_WIDTH).round() as usize
+        } else {
+            0
+        };
+        writeln!(
+            w,
+            "{}{:pad$} | {} {}",
+            label,
+            "",
+            "#".repeat(len),
+            count,
+            pad = label_width - display_width(&label)
+        )?;
+    }
+    Ok(())
+}
+
+/// one edge per row, or a lone node if the parent is NULL
+fn print_as_dot<W: io::Write>(mut w: W, labels: &[String], rows: &[Vec<Value>]) -> Result<()> {
+    if !(2..=3).contains(&labels.len()) {
+        return Err(anyhow!(
+            "dot needs 2 or 3 columns (parent, child and an optional edge label), got {}",
+            labels.len()
+        ));
+    }
+    writeln!(w, "digraph {{")?;
+    for row in rows {
+        let ids = row
+            .iter()
+            .map(|value| {
+                let mut s = String::new();
+                print_value_in_table(&mut s, value)?;
+                Ok(format!(
+                    "\"{}\"",
+                    s.replace('\\', "\\\\").replace('"', "\\\"")
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        match (&row[0], &row[1], row.get(2)) {
+            (_, Value::Null, _) => {}
+            (Value::Null, _, _) => writeln!(w, "  {};", ids[1])?,
+            (_, _, None | Some(Value::Null)) => writeln!(w, "  {} -> {};", ids[0], ids[1])?,
+            (_, _, Some(_)) => writeln!(w, "  {} -> {} [label={}];", ids[0], ids[1], ids[2])?,
+        }
+    }
+    writeln!(w, "}}")?;
+    Ok(())
+}
+
+fn print_as_markdown<W: io::Write>(mut w: W, labels: &[String], rows: &[Vec<Value>]) -> Result<()> {
+    write!(w, "|")?;
+    for label in labels {
+        write!(w, " {} |", escape_markdown_cell(label))?;
+    }
+    writeln!(w)?;
+    write!(w, "|")?;
+    for _ in labels {
+        write!(w, " --- |")?;
+    }
+    writeln!(w)?;
+    for row in rows {
+        write!(w, "|")?;
+        for value in row {
+            let mut s = String::new();
+            print_value_in_table(&mut s, value)?;
+            write!(w, " {} |", escape_markdown_cell(&s))?;
+        }
+        writeln!(w)?;
+    }
+    Ok(())
+}
+
+fn escape_markdown_cell(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', "<br>")
+}
+
+/// append the markdown table to `$GITHUB_STEP_SUMMARY` (or `w` outside of Actions)
+/// and emit annotations to `w`
+fn print_as_gha<W: io::Write>(
+    mut w: W,
+    annotate: Option<Annotation>,
+    labels: Vec<String>,
+    rows: Vec<Vec<Value>>,
+) -> Result<()> {
+    match env::var_os("GITHUB_STEP_SUMMARY") {
+        Some(path) => {
+            let summary = OpenOptions::new().create(true).append(true).open(path)?;
+            let mut summary = io::BufWriter::new(summary);
+            print_as_markdown(&mut summary, &labels, &rows)?;
+            writeln!(summary)?;
+        }
+        None => print_as_markdown(&mut w, &labels, &rows)?,
+    }
+    let level = match annotate {
+        Some(level) => level,
+        None => return Ok(()),
+    };
+    for row in rows {
+        let mut message = String::new();
+        for (i, (label, value)) in labels.iter().zip(&row).enumerate() {
+            if i > 0 {
+                message.push_str(", ");
+            }
+            message.push_str(label);
+            message.push_str(": ");
+            print_value_in_table(&mut message, value)?;
+        }
+        writeln!(w, "::{}::{}", level, escape_workflow_command(&message))?;
+    }
+    Ok(())
+}
+
+fn escape_workflow_command(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// write RFC 4180 CSV, quoting fields which contain `,`, `"` or line breaks
+pub fn print_as_csv<W: io::Write>(
+    mut w: W,
+    labels: Option<&[String]>,
+    rows: &[Vec<Value>],
+) -> Result<()> {
+    fn write_record<W: io::Write>(w: &mut W, fields: &[String]) -> io::Result<()> {
+        let fields: Vec<_> = fields
+            .iter()
+            .map(|field| {
+                if field.contains([',', '"', '\n', '\r']) {
+                    format!("\"{}\"", field.replace('"', "\"\""))
+                } else {
+                    field.clone()
+                }
+            })
+            .collect();
+        writeln!(w, "{}", fields.join(","))
+    }
+    if let Some(labels) = labels {
+        write_record(&mut w, labels)?;
+    }
+    for row in rows {
+        let fields = row
+            .iter()
+            .map(|value| {
+                let mut s = String::new();
+                print_value_in_table(&mut s, value).map(|_| s)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        write_record(&mut w, &fields)?;
+    }
+    Ok(())
+}
+
+/// a value as printed in a table cell
+pub fn value_to_string(value: &Value) -> String {
+    let mut s = String::new();
+    print_value_in_table(&mut s, value).expect("writing to a String never fails");
+    s
+}
+
 fn print_value_in_table<W: fmt::Write>(fmt: &mut W, value: &Value) -> Result<(), fmt::Error> {
     match value {
         Value::Bool(b) => write!(fmt, "{}", *b),
@@ -101,9 +762,7 @@ fn print_value_in_table<W: fmt::Write>(fmt: &mut W, value: &Value) -> Result<(),
         Value::Date(dt) => write!(fmt, "{}", *dt),
         Value::Timestamp(ts) => write!(fmt, "{}", *ts),
         Value::Time(tm) => write!(fmt, "{}", *tm),
-        Value::Interval(_) => unimplemented!(),
-        Value::Uuid(_) => unimplemented!(),
-        Value::Map(_) => unimplemented!(),
+        Value::Interval(iv) => write!(fmt, "{}", iv.to_sql_str()),
         Value::List(list) => {
             if let [head, tail @ ..] = list.as_slice() {
                 print_value_in_table(fmt, head)?;
@@ -115,23 +774,63 @@ fn print_value_in_table<W: fmt::Write>(fmt: &mut W, value: &Value) -> Result<(),
             Ok(())
         }
         Value::Null => write!(fmt, ""),
+        // the other types only come of casts and functions like GENERATE_UUID()
+        _ => write!(fmt, "{}", String::from(value)),
     }
 }
 
 fn print_as_json<W: io::Write>(mut w: W, labels: Vec<String>, rows: Vec<Vec<Value>>) -> Result<()> {
     for row in rows {
-        let mut row_map = serde_json::Map::with_capacity(labels.len());
-        for (label, value) in labels.iter().zip(row) {
-            let json_value = into_json_value(value);
-            row_map.insert(label.clone(), json_value);
-        }
+        let row_map = into_json_object(&labels, row);
         serde_json::to_writer(&mut w, &row_map)?;
         writeln!(&mut w)?;
     }
     Ok(())
 }
 
-fn into_json_value(value: Value) -> serde_json::Value {
+/// print the result as one JSON object of the columns, the rows as arrays and the stats
+fn print_as_json_envelope<W: io::Write>(
+    mut w: W,
+    labels: Vec<String>,
+    rows: Vec<Vec<Value>>,
+    stats: &Stats,
+) -> Result<()> {
+    let envelope = serde_json::json!({
+        "columns": labels,
+        "row_count": rows.len(),
+        "rows": rows
+            .into_iter()
+            .map(|row| row.into_iter().map(into_json_value).collect())
+            .collect::<Vec<serde_json::Value>>(),
+        "elapsed_ms": stats.elapsed.as_millis() as u64,
+        "api_calls": stats.usage.requests,
+    });
+    serde_json::to_writer(&mut w, &envelope)?;
+    writeln!(&mut w)?;
+    Ok(())
+}
+
+/// convert result rows into a JSON array of objects keyed by column label
+pub fn rows_to_json(labels: &[String], rows: Vec<Vec<Value>>) -> serde_json::Value {
+    rows.into_iter()
+        .map(|row| serde_json::Value::Object(into_json_object(labels, row)))
+        .collect::<Vec<_>>()
+        .into()
+}
+
+fn into_json_object(
+    labels: &[String],
+    row: Vec<Value>,
+) -> serde_json::Map<String, serde_json::Value> {
+    let mut row_map = serde_json::Map::with_capacity(labels.len());
+    for (label, value) in labels.iter().zip(row) {
+        let json_value = into_json_value(value);
+        row_map.insert(label.clone(), json_value);
+    }
+    row_map
+}
+
+pub fn into_json_value(value: Value) -> serde_json::Value {
     match value {
         Value::Bool(b) => b.into(),
         Value::I64(i) => i.into(),
@@ -140,14 +839,13 @@ fn into_json_value(value: Value) -> serde_json::Value {
         Value::Date(dt) => format!("{}", dt).into(),
         Value::Timestamp(ts) => format!("{}", ts).into(),
         Value::Time(tm) => format!("{}", tm).into(),
-        Value::Interval(_) => unimplemented!(),
-        Value::Uuid(_) => unimplemented!(),
-        Value::Map(_) => unimplemented!(),
+        Value::Interval(iv) => iv.to_sql_str().into(),
         Value::List(list) => list
             .into_iter()
             .map(into_json_value)
             .collect::<Vec<_>>()
             .into(),
         Value::Null => serde_json::Value::Null,
+        value => String::from(value).into(),
     }
 }
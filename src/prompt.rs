@@ -1,54 +1,119 @@
 use std::{
+    env,
     fmt::Debug,
+    fs,
     io::{BufWriter, Write},
+    process::{self, Command},
+    str::FromStr,
 };
 
-use anyhow::Result;
-use futures::executor::block_on;
-use gluesql::{
+use anyhow::{anyhow, Context, Error, Result};
+use gluesql::core::{
     executor::Payload,
-    prelude::{plan, translate, Glue, Value},
+    prelude::{Glue, Value},
     sqlparser::tokenizer::Token,
-    store::{GStore, GStoreMut},
+    store::{GStore, GStoreMut, Planner},
 };
-use rustyline::{error::ReadlineError, Editor, Helper};
+use rustyline::{error::ReadlineError, EditMode, Editor, Helper};
+use unicode_width::UnicodeWidthStr;
 
-use crate::output::{error_to_string, Format};
+use crate::{
+    browser, gh,
+    output::{report_sql_error, Format, Stats, StatsFormat},
+    sql, tui,
+};
 
 pub struct Opt {
     pub format: Format,
+    /// `{owner}` and `{number}` are replaced with those of the project, `{tx}` with `*` in a transaction
+    pub template: String,
+    pub stats: Option<StatsFormat>,
+    /// show the results with [`tui::browse`] instead of paging them
+    pub tui: bool,
+}
+
+/// key bindings of the prompt
+#[derive(Debug, Clone, Copy)]
+pub enum Keymap {
+    Emacs,
+    Vi,
 }
 
-pub struct Prompt<K, S, H>
+impl FromStr for Keymap {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "emacs" => Ok(Self::Emacs),
+            "vi" => Ok(Self::Vi),
+            _ => Err(anyhow!("Unknown keymap: {}", s)),
+        }
+    }
+}
+
+impl From<Keymap> for EditMode {
+    fn from(keymap: Keymap) -> Self {
+        match keymap {
+            Keymap::Emacs => EditMode::Emacs,
+            Keymap::Vi => EditMode::Vi,
+        }
+    }
+}
+
+/// a storage whose project can be switched with `\\c`,
+/// keeping the tables which don't belong to the project
+pub trait Session {
+    fn connect(&mut self, owner: String, project_number: i64) -> Result<()>;
+    /// owner and number of the current project
+    fn project(&self) -> (&str, i64);
+    /// whether writes are buffered until `COMMIT`
+    fn in_transaction(&self) -> bool;
+    /// the values a single select or iteration field of `items` can be set to, by its column
+    fn choices(&self, column: &str) -> Option<Vec<String>>;
+    /// a storage of the same project for `SELECT`s on another thread, sharing what has been
+    /// fetched, if the storage can be read from several threads
+    fn reader(&self) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+}
+
+pub struct Prompt<S, H>
 where
-    K: Debug,
-    S: GStore<K> + GStoreMut<K>,
+    S: GStore + GStoreMut + Planner + Session,
     H: Helper,
 {
     opt: Opt,
-    glue: Glue<K, S>,
+    glue: Glue<S>,
     rl: Editor<H>,
     input_buf: String,
     tokens_buf: Vec<Token>,
+    last_statement: String,
+    /// the URLs of the rows of the last result, for `\open`
+    last_urls: Vec<String>,
 }
 
-impl<K, S, H> Prompt<K, S, H>
+impl<S, H> Prompt<S, H>
 where
-    K: Debug,
-    S: GStore<K> + GStoreMut<K>,
+    S: GStore + GStoreMut + Planner + Session,
     H: Helper,
 {
-    pub fn new(opt: Opt, glue: Glue<K, S>, rl: Editor<H>) -> Self {
+    pub fn new(opt: Opt, glue: Glue<S>, rl: Editor<H>) -> Self {
         Self {
             opt,
             rl,
             glue,
             input_buf: String::new(),
             tokens_buf: vec![],
+            last_statement: String::new(),
+            last_urls: vec![],
         }
     }
 
     pub fn run(&mut self) -> Result<()> {
+        gh::trap_interrupt();
         loop {
             if let Err(e) = self.readline() {
                 match e.downcast::<ReadlineError>() {
@@ -70,85 +135,289 @@ where
         self.input_buf.is_empty() && self.tokens_buf.is_empty()
     }
 
-    fn prompt(&self) -> &'static str {
+    fn prompt(&self) -> String {
+        let storage = &self.glue.storage;
+        let (owner, number) = storage.project();
+        let prompt = self
+            .opt
+            .template
+            .replace("{owner}", owner)
+            .replace("{number}", &number.to_string())
+            .replace("{tx}", if storage.in_transaction() { "*" } else { "" });
         if self.is_buffer_empty() {
-            "ghsql> "
+            prompt
         } else {
-            "    -> "
+            format!("{:>width$}", "-> ", width = prompt.width())
         }
     }
 
     fn readline(&mut self) -> Result<()> {
-        let line = self.rl.readline(self.prompt())?;
+        let line = self.rl.readline(&self.prompt())?;
         if line.is_empty() {
             return Ok(());
         }
         self.rl.add_history_entry(line.as_str());
-        self.input_buf.push_str(&line);
+        if line.starts_with('\\') {
+            if let Err(e) = self.meta_command(&line) {
+                eprintln!("{:#}", e);
+            }
+            return Ok(());
+        }
+        self.push_input(&line);
+        while self.execute_buffered()? {}
+        Ok(())
+    }
+
+    fn push_input(&mut self, text: &str) {
+        self.input_buf.push_str(text);
         self.input_buf.push('\n');
-        let dialect = gluesql::sqlparser::dialect::GenericDialect {};
+        let dialect = gluesql::core::sqlparser::dialect::GenericDialect {};
         let mut tokenizer =
-            gluesql::sqlparser::tokenizer::Tokenizer::new(&dialect, &self.input_buf);
+            gluesql::core::sqlparser::tokenizer::Tokenizer::new(&dialect, &self.input_buf);
         if let Ok(new_tokens) = tokenizer.tokenize() {
             self.tokens_buf.extend(new_tokens);
             self.input_buf = String::new();
         }
-        let tokens = if let Some(pos) = self.tokens_buf.iter().position(|t| t == &Token::SemiColon)
-        {
-            let ws_len = self.tokens_buf[pos + 1..]
-                .iter()
-                .take_while(|t| matches!(t, Token::Whitespace(_)))
-                .count();
-            self.tokens_buf.drain(..=pos + ws_len).collect()
-        } else {
-            return Ok(());
-        };
-        let mut parser = gluesql::sqlparser::parser::Parser::new(tokens, &dialect);
-        let statement = match parser.parse_statement() {
+    }
+
+    /// the statement being typed, or the last executed one if there is none
+    fn current_statement(&self) -> String {
+        if self.is_buffer_empty() {
+            return self.last_statement.clone();
+        }
+        let mut text: String = self.tokens_buf.iter().map(ToString::to_string).collect();
+        text.push_str(&self.input_buf);
+        text
+    }
+
+    /// execute the first complete statement in the buffer, returns false if there is none
+    fn execute_buffered(&mut self) -> Result<bool> {
+        let tokens: Vec<_> =
+            if let Some(pos) = self.tokens_buf.iter().position(|t| t == &Token::SemiColon) {
+                let ws_len = self.tokens_buf[pos + 1..]
+                    .iter()
+                    .take_while(|t| matches!(t, Token::Whitespace(_)))
+                    .count();
+                self.tokens_buf.drain(..=pos + ws_len).collect()
+            } else {
+                return Ok(false);
+            };
+        self.last_statement = tokens.iter().map(ToString::to_string).collect();
+        let statement = match sql::parse(tokens) {
             Ok(statement) => statement,
             Err(e) => {
                 eprintln!("Syntax Error: {}", e);
-                return Ok(());
+                return Ok(true);
             }
         };
-        let output = translate(&statement)
-            .and_then(|statement| block_on(plan(self.glue.storage.as_ref().unwrap(), statement)))
-            .and_then(|plan| self.glue.execute_stmt(plan));
+        gh::clear_interrupt();
+        #[allow(clippy::result_large_err)]
+        let (output, stats) = Stats::measure(|| sql::execute(&mut self.glue, &statement));
         match output {
             Ok(Payload::Select { labels, rows }) => {
-                print(&self.opt.format, labels, rows)?;
+                self.last_urls = browser::urls(&labels, &rows);
+                if self.opt.tui {
+                    tui::browse(labels, rows, &mut self.glue)?;
+                } else {
+                    let height = self.rl.dimensions().map(|(_, height)| height);
+                    print(&self.opt.format, labels, rows, &stats, height)?;
+                }
             }
             Ok(_) => {}
             Err(err) => {
-                eprintln!("SQL execution error: {}", error_to_string(err));
+                report_sql_error(err);
+            }
+        }
+        if let Some(format) = self.opt.stats {
+            stats.report(format, self.last_statement.trim());
+        }
+        Ok(true)
+    }
+
+    /// open the current statement in `$VISUAL` / `$EDITOR` and run what is saved
+    fn edit(&mut self) -> Result<()> {
+        let editor = env::var("VISUAL")
+            .or_else(|_| env::var("EDITOR"))
+            .unwrap_or_else(|_| "vi".to_string());
+        let mut editor = editor.split_whitespace();
+        let program = editor.next().context("$EDITOR is empty")?;
+        let path = env::temp_dir().join(format!("ghsql-{}.sql", process::id()));
+        fs::write(&path, self.current_statement().trim_end())?;
+        let status = Command::new(program)
+            .args(editor)
+            .arg(&path)
+            .status()
+            .with_context(|| format!("failed to run {}", program))?;
+        let text = fs::read_to_string(&path);
+        fs::remove_file(&path)?;
+        if !status.success() {
+            return Err(anyhow!("{} exited with {}", program, status));
+        }
+        let text = text?;
+        let text = text.trim_end();
+        self.input_buf = String::new();
+        self.tokens_buf = vec![];
+        if text.is_empty() {
+            return Ok(());
+        }
+        println!("{}", text);
+        self.rl.add_history_entry(text);
+        self.push_input(text);
+        while self.execute_buffered()? {}
+        Ok(())
+    }
+
+    /// run the statements in a file as if they were typed
+    fn include(&mut self, path: &str) -> Result<()> {
+        let text = fs::read_to_string(path).with_context(|| path.to_string())?;
+        self.push_input(&text);
+        while self.execute_buffered()? {}
+        Ok(())
+    }
+
+    /// psql-style backslash commands, accepted on a line of their own
+    fn meta_command(&mut self, line: &str) -> Result<()> {
+        let mut args = line.split_whitespace();
+        match (args.next().unwrap_or_default(), args.next(), args.next()) {
+            ("\\format", Some(format), None) => self.opt.format = format.parse()?,
+            ("\\format", ..) => return Err(anyhow!("Usage: \\format FORMAT")),
+            ("\\e", None, _) => self.edit()?,
+            ("\\open", None, _) => browser::open(&self.last_urls, false)?,
+            ("\\i", Some(_), _) => {
+                // the rest of the line, so that paths may contain spaces
+                let path = line.split_once(char::is_whitespace).unwrap().1.trim();
+                self.include(path)?
+            }
+            ("\\i", None, _) => return Err(anyhow!("Usage: \\i FILE")),
+            ("\\c" | "\\connect", Some(owner), Some(project_number)) if args.next().is_none() => {
+                let project_number = project_number
+                    .parse()
+                    .with_context(|| format!("Invalid project number: {}", project_number))?;
+                let storage = &mut self.glue.storage;
+                storage.connect(owner.to_string(), project_number)?;
+                eprintln!("Connected to {} #{}", owner, project_number);
             }
+            ("\\c" | "\\connect", ..) => return Err(anyhow!("Usage: \\c OWNER PROJECT_NUMBER")),
+            (command, ..) => return Err(anyhow!("Unknown command: {}", command)),
         }
         Ok(())
     }
 }
 
+/// page the result with `less`, or print it if `less` can't be run. `less` fits the result
+/// to the terminal itself
 #[cfg(unix)]
-fn print(format: &Format, labels: Vec<String>, rows: Vec<Vec<Value>>) -> Result<()> {
-    use std::process::{Command, Stdio};
-    let mut pager = Command::new("less")
-        .args(["-FS"])
-        .stdin(Stdio::piped())
-        .spawn()?;
+fn print(
+    format: &Format,
+    labels: Vec<String>,
+    rows: Vec<Vec<Value>>,
+    stats: &Stats,
+    _height: Option<usize>,
+) -> Result<()> {
+    let mut less = Command::new("less");
+    less.args(["-FS"]);
+    print_to_pager(less, format, labels, rows, stats)
+}
+
+/// page the result with `PAGER`, e.g. `more`, or a screen of `height` rows at a time if it
+/// doesn't fit in the terminal
+#[cfg(windows)]
+fn print(
+    format: &Format,
+    labels: Vec<String>,
+    rows: Vec<Vec<Value>>,
+    stats: &Stats,
+    height: Option<usize>,
+) -> Result<()> {
+    if let Some(pager) = env::var("PAGER")
+        .ok()
+        .filter(|pager| !pager.trim().is_empty())
+    {
+        let mut command = Command::new("cmd");
+        command.args(["/C", &pager]);
+        return print_to_pager(command, format, labels, rows, stats);
+    }
+    let Some(height) = height else {
+        return print_to_stdout(format, labels, rows, stats);
+    };
+    let mut text = vec![];
+    format.print(&mut text, labels, rows, stats)?;
+    page(&String::from_utf8_lossy(&text), height)
+}
+
+/// print `text` a screen at a time like `more`, going on with Enter and stopping at `q`
+#[cfg(windows)]
+fn page(text: &str, height: usize) -> Result<()> {
+    let lines: Vec<&str> = text.lines().collect();
+    // the last row of the screen is left for the prompt
+    let page_size = height.saturating_sub(1).max(1);
+    let stdout = std::io::stdout();
+    let mut stdout = stdout.lock();
+    for (n, page) in lines.chunks(page_size).enumerate() {
+        if n > 0 {
+            eprint!(
+                "-- More ({}/{} lines) -- Enter for the next page, q to quit ",
+                n * page_size,
+                lines.len()
+            );
+            let mut answer = String::new();
+            std::io::stdin().read_line(&mut answer)?;
+            if answer.trim().eq_ignore_ascii_case("q") {
+                break;
+            }
+        }
+        for line in page {
+            writeln!(stdout, "{}", line)?;
+        }
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+/// write the result to the stdin of `pager`, or print it if the pager can't be run
+fn print_to_pager(
+    mut pager: Command,
+    format: &Format,
+    labels: Vec<String>,
+    rows: Vec<Vec<Value>>,
+    stats: &Stats,
+) -> Result<()> {
+    use std::process::Stdio;
+    let Ok(mut pager) = pager.stdin(Stdio::piped()).spawn() else {
+        return print_to_stdout(format, labels, rows, stats);
+    };
     let pipe = pager.stdin.as_mut().unwrap();
     let mut pipe = BufWriter::new(pipe);
-    format.print(&mut pipe, labels, rows)?;
-    pipe.flush()?;
+    let written = format
+        .print(&mut pipe, labels, rows, stats)
+        .and_then(|()| Ok(pipe.flush()?));
     drop(pipe);
     pager.wait()?;
-    Ok(())
+    match written {
+        // quit before the whole result was written
+        Err(e) if is_broken_pipe(&e) => Ok(()),
+        written => written,
+    }
 }
 
-#[cfg(windows)]
-fn print(format: &Format, labels: Vec<String>, rows: Vec<Vec<Value>>) -> Result<()> {
+fn print_to_stdout(
+    format: &Format,
+    labels: Vec<String>,
+    rows: Vec<Vec<Value>>,
+    stats: &Stats,
+) -> Result<()> {
     let stdout = std::io::stdout();
     let stdout = stdout.lock();
     let mut stdout = BufWriter::new(stdout);
-    format.print(&mut stdout, labels, rows)?;
+    format.print(&mut stdout, labels, rows, stats)?;
     stdout.flush()?;
     Ok(())
 }
+
+fn is_broken_pipe(e: &Error) -> bool {
+    e.chain().any(|cause| {
+        cause
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|e| e.kind() == std::io::ErrorKind::BrokenPipe)
+    })
+}
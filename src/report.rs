@@ -0,0 +1,257 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    io::{BufWriter, Write},
+};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{Duration, Local, NaiveDateTime};
+use gluesql::core::{
+    data::Value,
+    prelude::Glue,
+    store::{GStore, GStoreMut, Planner},
+};
+use structopt::StructOpt;
+
+use crate::{
+    output::{Format, Stats, StatsFormat},
+    prompt::Session,
+    sql,
+};
+
+#[derive(Debug, StructOpt)]
+pub enum Kind {
+    /// Open and closed items (and points) per day of an iteration
+    Burndown {
+        #[structopt(long, help = "Title of the iteration, e.g. \"Sprint 12\"")]
+        iteration: String,
+        #[structopt(long, help = "Number field summed as points, e.g. \"Estimate\"")]
+        number_field: Option<String>,
+    },
+    /// Issues and pull requests which are items of PROJECT_NUMBER and another project
+    Duplicates {
+        #[structopt(
+            long = "with",
+            required = true,
+            help = "Other project to compare with, as PROJECT_NUMBER or OWNER/PROJECT_NUMBER, can be repeated"
+        )]
+        projects: Vec<String>,
+    },
+}
+
+pub struct Opt {
+    pub format: Format,
+    pub kind: Kind,
+    pub stats: Option<StatsFormat>,
+}
+
+pub struct Report<S>
+where
+    S: GStore + GStoreMut + Planner + Session,
+{
+    opt: Opt,
+    glue: Glue<S>,
+}
+
+type Rows = (Vec<String>, Vec<Vec<Value>>);
+
+impl<S> Report<S>
+where
+    S: GStore + GStoreMut + Planner + Session,
+{
+    pub fn new(opt: Opt, glue: Glue<S>) -> Self {
+        Self { opt, glue }
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        let (output, stats) = Stats::measure(|| match &self.opt.kind {
+            Kind::Burndown {
+                iteration,
+                number_field,
+            } => {
+                let (iteration, number_field) = (iteration.clone(), number_field.clone());
+                self.burndown(&iteration, number_field.as_deref())
+            }
+            Kind::Duplicates { projects } => {
+                let projects = projects.clone();
+                self.duplicates(&projects)
+            }
+        });
+        let (labels, rows) = output?;
+        let stdout = std::io::stdout();
+        let mut stdout = BufWriter::new(stdout.lock());
+        self.opt.format.print(&mut stdout, labels, rows, &stats)?;
+        stdout.flush()?;
+        drop(stdout);
+        if let Some(format) = self.opt.stats {
+            stats.report(format, "report");
+        }
+        Ok(())
+    }
+
+    /// A day is counted as closed once the last closed/merged/reopened event up to that day
+    /// closes the item. Closed items without any such event are closed from the first day.
+    fn burndown(&mut self, title: &str, number_field: Option<&str>) -> Result<Rows> {
+        let (_, rows) = sql::select(
+            &mut self.glue,
+            "SELECT title, start_date, duration, field_name FROM iterations",
+        )?;
+        let (start_date, duration, field_name) = rows
+            .into_iter()
+            .find_map(|row| match row.as_slice() {
+                [Value::Str(t), Value::Date(start_date), Value::I64(duration), Value::Str(field_name)]
+                    if t == title =>
+                {
+                    Some((*start_date, *duration, field_name.clone()))
+                }
+                _ => None,
+            })
+            .ok_or_else(|| anyhow!("No iteration titled: {}", title))?;
+
+        let (labels, rows) = sql::select(&mut self.glue, "SELECT * FROM items")?;
+        let column = |name: &str| {
+            labels
+                .iter()
+                .position(|label| label == name)
+                .ok_or_else(|| anyhow!("Column not found: items.{}", name))
+        };
+        let (id_idx, state_idx, iteration_idx) =
+            (column("id")?, column("State")?, column(&field_name)?);
+        let points_idx = number_field.map(column).transpose()?;
+        let items: Vec<_> = rows
+            .into_iter()
+            .filter(|row| matches!(&row[iteration_idx], Value::Str(t) if t == title))
+            .map(|row| {
+                let points = match points_idx.map(|i| &row[i]) {
+                    Some(Value::F64(n)) => *n,
+                    _ => 0.0,
+                };
+                let closed = matches!(&row[state_idx], Value::Str(s) if s != "OPEN");
+                match &row[id_idx] {
+                    Value::Str(id) => (id.clone(), closed, points),
+                    _ => unreachable!(),
+                }
+            })
+            .collect();
+
+        let (_, events) = sql::select(
+            &mut self.glue,
+            "SELECT item_id, event, created_at FROM item_events",
+        )?;
+        let mut transitions: HashMap<String, Vec<(NaiveDateTime, bool)>> = HashMap::new();
+        for event in events {
+            if let [Value::Str(item_id), Value::Str(event), Value::Timestamp(created_at)] =
+                event.as_slice()
+            {
+                let closed = match event.as_str() {
+                    "closed" | "merged" => true,
+                    "reopened" => false,
+                    _ => continue,
+                };
+                transitions
+                    .entry(item_id.clone())
+                    .or_default()
+                    .push((*created_at, closed));
+            }
+        }
+        for transitions in transitions.values_mut() {
+            transitions.sort();
+        }
+
+        let today = Local::now().naive_local().date();
+        let mut labels = vec![
+            "date".to_string(),
+            "open_items".to_string(),
+            "closed_items".to_string(),
+        ];
+        if number_field.is_some() {
+            labels.extend(["open_points".to_string(), "closed_points".to_string()]);
+        }
+        let rows = (0..duration)
+            .map(|day| start_date + Duration::days(day))
+            .take_while(|date| *date <= today)
+            .map(|date| {
+                let (mut open, mut closed) = ((0, 0.0), (0, 0.0));
+                for (id, closed_now, points) in &items {
+                    let is_closed = match transitions.get(id) {
+                        Some(transitions) => transitions
+                            .iter()
+                            .take_while(|(at, _)| at.date() <= date)
+                            .last()
+                            .is_some_and(|(_, closed)| *closed),
+                        None => *closed_now,
+                    };
+                    let count = if is_closed { &mut closed } else { &mut open };
+                    count.0 += 1;
+                    count.1 += points;
+                }
+                let mut row = vec![Value::Date(date), Value::I64(open.0), Value::I64(closed.0)];
+                if number_field.is_some() {
+                    row.extend([Value::F64(open.1), Value::F64(closed.1)]);
+                }
+                row
+            })
+            .collect();
+        Ok((labels, rows))
+    }
+
+    /// The projects are fetched one after another by switching to them, so the session ends
+    /// on the last one
+    fn duplicates(&mut self, projects: &[String]) -> Result<Rows> {
+        let storage = &self.glue.storage;
+        let (owner, number) = storage.project();
+        let mut others = vec![];
+        for project in projects {
+            let (other_owner, other_number) = match project.split_once('/') {
+                Some((owner, number)) => (owner, number),
+                None => (owner, project.as_str()),
+            };
+            let other_number: i64 = other_number
+                .parse()
+                .with_context(|| format!("Invalid project number: {}", project))?;
+            others.push((other_owner.to_string(), other_number));
+        }
+        let projects = [(owner.to_string(), number)].into_iter().chain(others);
+
+        // (title, projects) by (repository, number), in the order of the repositories
+        let mut items: BTreeMap<(String, i64), (Value, Vec<Value>)> = BTreeMap::new();
+        for (i, (owner, number)) in projects.enumerate() {
+            if i > 0 {
+                let storage = &mut self.glue.storage;
+                storage.connect(owner.clone(), number)?;
+            }
+            // the reserved columns come first whatever they are named
+            let (_, rows) = sql::select(&mut self.glue, "SELECT * FROM items")?;
+            let name = Value::Str(format!("{}/{}", owner, number));
+            for row in rows {
+                let [_, Value::Str(repository), Value::I64(issue), title, ..] = row.as_slice()
+                else {
+                    continue;
+                };
+                let (_, projects) = items
+                    .entry((repository.clone(), *issue))
+                    .or_insert_with(|| (title.clone(), vec![]));
+                if !projects.contains(&name) {
+                    projects.push(name.clone());
+                }
+            }
+        }
+
+        let labels = ["repository", "number", "title", "projects"]
+            .map(str::to_string)
+            .to_vec();
+        let rows = items
+            .into_iter()
+            .filter(|(_, (_, projects))| projects.len() > 1)
+            .map(|((repository, issue), (title, projects))| {
+                vec![
+                    Value::Str(repository),
+                    Value::I64(issue),
+                    title,
+                    Value::List(projects),
+                ]
+            })
+            .collect();
+        Ok((labels, rows))
+    }
+}
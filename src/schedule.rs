@@ -0,0 +1,230 @@
+use std::{
+    fmt::Debug,
+    fs::{self, File},
+    io::{BufReader, BufWriter, Write},
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    thread,
+};
+
+use anyhow::{anyhow, Context, Result};
+use gluesql::core::{
+    data::Value,
+    executor::Payload,
+    prelude::Glue,
+    sqlparser::ast::Statement as SqlStatement,
+    store::{GStore, GStoreMut, Planner},
+};
+use serde::Deserialize;
+use structopt::StructOpt;
+
+use crate::{
+    comment,
+    output::{error_to_string, Format, Stats, StatsFormat, Template},
+    prompt::Session,
+    sql::{self, Statement},
+};
+
+#[derive(Debug, StructOpt)]
+pub enum Action {
+    /// Run every report of a TOML or JSON file in one process, so that they share the fetched
+    /// items
+    Run {
+        #[structopt(
+            parse(from_os_str),
+            help = "TOML file of reports (`[[reports]]` tables of `name`, `query`, `file`, ...), or JSON unless it ends with .toml (`[{\"name\": \"...\", \"query\": \"...\", \"file\": \"...\"}]`)"
+        )]
+        reports: PathBuf,
+    },
+}
+
+pub struct Opt {
+    /// the format of reports which don't set theirs
+    pub format: Format,
+    pub action: Action,
+    pub stats: Option<StatsFormat>,
+}
+
+/// a named query and where its result goes: stdout unless one of `file`, `comment_to` or
+/// `step_summary` is given, or several of them
+#[derive(Debug, Deserialize)]
+struct Report {
+    name: String,
+    query: String,
+    /// same values as `-o`
+    format: Option<String>,
+    /// same as `--template`, instead of `format`
+    template: Option<String>,
+    /// written over with the result
+    file: Option<PathBuf>,
+    /// `owner/repo#123` to post the result to as a Markdown table
+    comment_to: Option<String>,
+    /// append the result as a Markdown table to `$GITHUB_STEP_SUMMARY`
+    #[serde(default)]
+    step_summary: bool,
+}
+
+/// the reports of a TOML file, as `[[reports]]` tables
+#[derive(Debug, Deserialize)]
+struct Reports {
+    reports: Vec<Report>,
+}
+
+/// read the reports of a `.toml` file, or of a JSON array in any other file
+fn load(path: &Path) -> Result<Vec<Report>> {
+    let parse_error = || format!("failed to parse reports: {}", path.display());
+    if path.extension().is_some_and(|ext| ext == "toml") {
+        let text = fs::read_to_string(path).with_context(|| format!("{}", path.display()))?;
+        let Reports { reports } = toml::from_str(&text).with_context(parse_error)?;
+        return Ok(reports);
+    }
+    let file = File::open(path).with_context(|| format!("{}", path.display()))?;
+    serde_json::from_reader(BufReader::new(file)).with_context(parse_error)
+}
+
+/// the labels and rows of the query of a report
+type Selected = (Vec<String>, Vec<Vec<Value>>);
+
+pub struct Schedule<S>
+where
+    S: GStore + GStoreMut + Planner + Session + Send,
+{
+    opt: Opt,
+    glue: Glue<S>,
+}
+
+impl<S> Schedule<S>
+where
+    S: GStore + GStoreMut + Planner + Session + Send,
+{
+    pub fn new(opt: Opt, glue: Glue<S>) -> Self {
+        Self { opt, glue }
+    }
+
+    /// run every report, going on after one failed, and fail if any did
+    pub fn run(&mut self) -> Result<()> {
+        let Action::Run { reports: path } = &self.opt.action;
+        let reports = load(path)?;
+        let results = self.execute(&reports);
+        let mut failed = 0;
+        for (report, (result, stats)) in reports.iter().zip(results) {
+            if let Some(format) = self.opt.stats {
+                stats.report(format, &report.name);
+            }
+            if let Err(e) = result.and_then(|selected| self.write(report, selected, &stats)) {
+                eprintln!("Report \"{}\" failed: {:#}", report.name, e);
+                failed += 1;
+            }
+        }
+        if failed > 0 {
+            return Err(anyhow!("{} of {} report(s) failed", failed, reports.len()));
+        }
+        Ok(())
+    }
+
+    /// execute the queries of the reports. If every one is a single `SELECT` they are
+    /// executed at once on threads, each with a [`Session::reader`] of the storage so that
+    /// the project is fetched once by the first of them, otherwise in order. The requests
+    /// counted by the stats of a report may then have been sent for another
+    fn execute(&mut self, reports: &[Report]) -> Vec<(Result<Selected>, Stats)> {
+        let threads = thread::available_parallelism()
+            .map_or(1, NonZeroUsize::get)
+            .min(reports.len());
+        let readers = if threads > 1 && reports.iter().all(|report| is_select(&report.query)) {
+            let storage = &self.glue.storage;
+            (0..threads)
+                .map(|_| storage.reader())
+                .collect::<Option<Vec<_>>>()
+        } else {
+            None
+        };
+        let Some(readers) = readers else {
+            return reports
+                .iter()
+                .map(|report| select(&mut self.glue, &report.query))
+                .collect();
+        };
+        // the reports are dealt out to the threads in turn
+        let mut results: Vec<_> = thread::scope(|scope| {
+            let handles: Vec<_> = readers
+                .into_iter()
+                .enumerate()
+                .map(|(first, reader)| {
+                    scope.spawn(move || {
+                        let mut glue = Glue::new(reader);
+                        (first..reports.len())
+                            .step_by(threads)
+                            .map(|idx| (idx, select(&mut glue, &reports[idx].query)))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("a report panicked"))
+                .collect()
+        });
+        results.sort_by_key(|(idx, _)| *idx);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+
+    fn write(&self, report: &Report, (labels, rows): Selected, stats: &Stats) -> Result<()> {
+        let format = match (&report.format, &report.template) {
+            (None, None) => None,
+            (Some(format), None) => Some(format.parse()?),
+            (None, Some(template)) => Some(Format::Template(template.parse::<Template>()?)),
+            (Some(_), Some(_)) => return Err(anyhow!("`template` can not be used with `format`")),
+        };
+        let format = format.as_ref().unwrap_or(&self.opt.format);
+        let to_stdout =
+            report.file.is_none() && report.comment_to.is_none() && !report.step_summary;
+        if to_stdout {
+            let stdout = std::io::stdout();
+            let mut stdout = BufWriter::new(stdout.lock());
+            format.print(&mut stdout, labels.clone(), rows.clone(), stats)?;
+            stdout.flush()?;
+        }
+        if let Some(path) = &report.file {
+            let mut text = vec![];
+            format.print(&mut text, labels.clone(), rows.clone(), stats)?;
+            fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        if let Some(target) = &report.comment_to {
+            let target: comment::Target = target.parse()?;
+            let mut body = vec![];
+            Format::Markdown.print(&mut body, labels.clone(), rows.clone(), stats)?;
+            let url = comment::post(&target, &String::from_utf8(body)?)?;
+            eprintln!("Posted: {}", url);
+        }
+        if report.step_summary {
+            // the summary is printed to stdout outside of GitHub Actions
+            let stdout = std::io::stdout();
+            let mut stdout = BufWriter::new(stdout.lock());
+            Format::Gha { annotate: None }.print(&mut stdout, labels, rows, stats)?;
+            stdout.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// whether `query` is a single `SELECT`, which doesn't change what the others read
+fn is_select(query: &str) -> bool {
+    match sql::parse_all(query).as_deref() {
+        Ok([Statement::Sql(statement)]) => matches!(**statement, SqlStatement::Query(_)),
+        _ => false,
+    }
+}
+
+fn select<S>(glue: &mut Glue<S>, query: &str) -> (Result<Selected>, Stats)
+where
+    S: GStore + GStoreMut + Planner,
+{
+    #[allow(clippy::result_large_err)]
+    let (output, stats) = Stats::measure(|| sql::execute_str(glue, query));
+    let selected = match output {
+        Ok(Payload::Select { labels, rows }) => Ok((labels, rows)),
+        Ok(other) => Err(anyhow!("not a SELECT: {:?}", other)),
+        Err(e) => Err(anyhow!("{}", error_to_string(e))),
+    };
+    (selected, stats)
+}
@@ -0,0 +1,89 @@
+use std::{
+    io::{self, BufWriter, Write},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, Error, Result};
+
+use crate::storage::{ProjectNextStorage, TableDescription};
+
+#[derive(Debug)]
+pub enum SchemaFormat {
+    Json,
+    Sql,
+}
+
+impl FromStr for SchemaFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "sql" => Ok(Self::Sql),
+            _ => Err(anyhow!("Unknown schema format: {}", s)),
+        }
+    }
+}
+
+pub fn print(storage: &ProjectNextStorage, format: SchemaFormat) -> Result<()> {
+    let tables = storage.describe()?;
+    let stdout = io::stdout();
+    let mut stdout = BufWriter::new(stdout.lock());
+    match format {
+        SchemaFormat::Json => {
+            serde_json::to_writer_pretty(&mut stdout, &tables)?;
+            writeln!(stdout)?;
+        }
+        SchemaFormat::Sql => print_as_sql(&mut stdout, &tables)?,
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+/// `CREATE TABLE` statements, with the writability and options as comments
+fn print_as_sql<W: Write>(mut w: W, tables: &[TableDescription]) -> Result<()> {
+    for (i, table) in tables.iter().enumerate() {
+        if i > 0 {
+            writeln!(w)?;
+        }
+        writeln!(w, "CREATE TABLE {} (", identifier(&table.name))?;
+        for (j, column) in table.columns.iter().enumerate() {
+            let mut comment = vec![];
+            if !column.writable {
+                comment.push("read-only".to_string());
+            }
+            if let Some(kind) = &column.unknown_kind {
+                comment.push(format!("{} field unknown to gh-sql, read as text", kind));
+            }
+            if let Some(options) = &column.options {
+                comment.push(format!("options: {}", options.join(", ")));
+            }
+            write!(
+                w,
+                "  {} {}{}{}",
+                identifier(&column.name),
+                column.data_type,
+                if column.nullable { " NULL" } else { "" },
+                if j + 1 < table.columns.len() { "," } else { "" },
+            )?;
+            if comment.is_empty() {
+                writeln!(w)?;
+            } else {
+                writeln!(w, " -- {}", comment.join("; "))?;
+            }
+        }
+        writeln!(w, ");")?;
+    }
+    Ok(())
+}
+
+/// quote names like "Linked pull requests"
+pub fn identifier(name: &str) -> String {
+    let plain = name.chars().next().is_some_and(|c| !c.is_ascii_digit())
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    if plain {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+}
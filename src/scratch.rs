@@ -0,0 +1,231 @@
+use std::collections::{BTreeMap, HashMap};
+
+use gluesql::core::{
+    ast::ColumnDef,
+    data::{Key, Schema, Value},
+    error::{Error as GlueSQLError, Result as GlueSQLResult},
+    store::{AlterTableError, RowIter},
+};
+
+/// in-memory tables created by `CREATE TABLE` or mounted from files, living as long as the process
+#[derive(Default, Clone)]
+pub struct Scratch {
+    id_counter: u64,
+    tables: HashMap<String, Table>,
+}
+
+#[derive(Clone)]
+struct Table {
+    schema: Schema,
+    rows: BTreeMap<u64, Vec<Value>>,
+    readonly: bool,
+}
+
+#[allow(clippy::result_large_err)]
+impl Scratch {
+    pub fn schema(&self, table_name: &str) -> Option<Schema> {
+        self.tables
+            .get(table_name)
+            .map(|table| table.schema.clone())
+    }
+
+    /// schemas of all tables by name, and whether each is read-only
+    pub fn tables(&self) -> Vec<(Schema, bool)> {
+        let mut tables: Vec<_> = self
+            .tables
+            .values()
+            .map(|table| (table.schema.clone(), table.readonly))
+            .collect();
+        tables.sort_by(|(a, _), (b, _)| a.table_name.cmp(&b.table_name));
+        tables
+    }
+
+    pub fn scan(&self, table_name: &str) -> GlueSQLResult<RowIter<'_>> {
+        let table = self.table(table_name)?;
+        let rows: Vec<_> = table
+            .rows
+            .iter()
+            .map(|(id, row)| Ok((Key::U64(*id), row.clone())))
+            .collect();
+        Ok(Box::new(rows.into_iter()))
+    }
+
+    pub fn create(&mut self, schema: &Schema) {
+        self.tables.insert(
+            schema.table_name.clone(),
+            Table {
+                schema: schema.clone(),
+                rows: BTreeMap::new(),
+                readonly: false,
+            },
+        );
+    }
+
+    /// add a read-only table holding `rows`
+    pub fn mount(&mut self, schema: Schema, rows: Vec<Vec<Value>>) {
+        let rows = rows
+            .into_iter()
+            .map(|row| {
+                self.id_counter += 1;
+                (self.id_counter, row)
+            })
+            .collect();
+        self.tables.insert(
+            schema.table_name.clone(),
+            Table {
+                schema,
+                rows,
+                readonly: true,
+            },
+        );
+    }
+
+    pub fn drop(&mut self, table_name: &str) -> GlueSQLResult<()> {
+        if self
+            .tables
+            .get(table_name)
+            .is_some_and(|table| table.readonly)
+        {
+            return Err(GlueSQLError::StorageMsg("readonly table".to_string()));
+        }
+        self.tables.remove(table_name);
+        Ok(())
+    }
+
+    pub fn rename(&mut self, table_name: &str, new_table_name: &str) -> GlueSQLResult<()> {
+        if self.tables.contains_key(new_table_name) {
+            return Err(GlueSQLError::StorageMsg(format!(
+                "table already exists: {}",
+                new_table_name
+            )));
+        }
+        let mut table = self
+            .tables
+            .remove(table_name)
+            .ok_or_else(|| AlterTableError::TableNotFound(table_name.to_string()))?;
+        table.schema.table_name = new_table_name.to_string();
+        self.tables.insert(new_table_name.to_string(), table);
+        Ok(())
+    }
+
+    pub fn rename_column(
+        &mut self,
+        table_name: &str,
+        old_column_name: &str,
+        new_column_name: &str,
+    ) -> GlueSQLResult<()> {
+        let table = self.table_mut(table_name)?;
+        let column_defs = table
+            .schema
+            .column_defs
+            .as_mut()
+            .ok_or_else(|| AlterTableError::SchemalessTableFound(table_name.to_string()))?;
+        let column_def = column_defs
+            .iter_mut()
+            .find(|def| def.name == old_column_name)
+            .ok_or(AlterTableError::RenamingColumnNotFound)?;
+        column_def.name = new_column_name.to_string();
+        Ok(())
+    }
+
+    /// add a nullable column, existing rows get NULL
+    pub fn add_column(&mut self, table_name: &str, column_def: &ColumnDef) -> GlueSQLResult<()> {
+        let table = self.table_mut(table_name)?;
+        let column_defs = table
+            .schema
+            .column_defs
+            .as_mut()
+            .ok_or_else(|| AlterTableError::SchemalessTableFound(table_name.to_string()))?;
+        if column_defs.iter().any(|def| def.name == column_def.name) {
+            return Err(AlterTableError::AlreadyExistingColumn(column_def.name.clone()).into());
+        }
+        if !column_def.nullable {
+            return Err(AlterTableError::DefaultValueRequired(column_def.clone()).into());
+        }
+        column_defs.push(column_def.clone());
+        for row in table.rows.values_mut() {
+            row.push(Value::Null);
+        }
+        Ok(())
+    }
+
+    pub fn drop_column(
+        &mut self,
+        table_name: &str,
+        column_name: &str,
+        if_exists: bool,
+    ) -> GlueSQLResult<()> {
+        let table = self.table_mut(table_name)?;
+        let column_defs = table
+            .schema
+            .column_defs
+            .as_mut()
+            .ok_or_else(|| AlterTableError::SchemalessTableFound(table_name.to_string()))?;
+        let Some(idx) = column_defs.iter().position(|def| def.name == column_name) else {
+            if if_exists {
+                return Ok(());
+            }
+            return Err(AlterTableError::DroppingColumnNotFound(column_name.to_string()).into());
+        };
+        column_defs.remove(idx);
+        for row in table.rows.values_mut() {
+            row.remove(idx);
+        }
+        Ok(())
+    }
+
+    pub fn insert(&mut self, table_name: &str, rows: Vec<Vec<Value>>) -> GlueSQLResult<()> {
+        let mut id_counter = self.id_counter;
+        let table = self.table_mut(table_name)?;
+        for row in rows {
+            id_counter += 1;
+            table.rows.insert(id_counter, row);
+        }
+        self.id_counter = id_counter;
+        Ok(())
+    }
+
+    pub fn update(&mut self, table_name: &str, rows: Vec<(Key, Vec<Value>)>) -> GlueSQLResult<()> {
+        let table = self.table_mut(table_name)?;
+        for (key, row) in rows {
+            table.rows.insert(row_id(&key)?, row);
+        }
+        Ok(())
+    }
+
+    pub fn delete(&mut self, table_name: &str, keys: Vec<Key>) -> GlueSQLResult<()> {
+        let table = self.table_mut(table_name)?;
+        for key in keys {
+            table.rows.remove(&row_id(&key)?);
+        }
+        Ok(())
+    }
+
+    fn table(&self, table_name: &str) -> GlueSQLResult<&Table> {
+        self.tables
+            .get(table_name)
+            .ok_or_else(|| GlueSQLError::StorageMsg(format!("table not found: {}", table_name)))
+    }
+
+    fn table_mut(&mut self, table_name: &str) -> GlueSQLResult<&mut Table> {
+        let table = self
+            .tables
+            .get_mut(table_name)
+            .ok_or_else(|| GlueSQLError::StorageMsg(format!("table not found: {}", table_name)))?;
+        if table.readonly {
+            return Err(GlueSQLError::StorageMsg("readonly table".to_string()));
+        }
+        Ok(table)
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn row_id(key: &Key) -> GlueSQLResult<u64> {
+    match key {
+        Key::U64(id) => Ok(*id),
+        _ => Err(GlueSQLError::StorageMsg(format!(
+            "invalid row key: {:?}",
+            key
+        ))),
+    }
+}
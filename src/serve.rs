@@ -0,0 +1,332 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context, Result};
+use gluesql::core::{
+    executor::Payload,
+    prelude::Glue,
+    sqlparser::ast::Statement as SqlStatement,
+    store::{GStore, GStoreMut, Planner},
+};
+use serde::Deserialize;
+
+use crate::{
+    output::{error_to_string, rows_to_json},
+    sql::{self, Statement},
+};
+
+/// the largest request body read, larger ones are refused with 413
+const MAX_BODY_LEN: usize = 1024 * 1024;
+/// the largest request line and headers read
+const MAX_HEAD_LEN: u64 = 64 * 1024;
+/// how long a client may take to send its request or read the response
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct Opt {
+    pub listen: SocketAddr,
+    /// the bearer token requests must have, a random one printed at startup if `None`
+    pub token: Option<String>,
+    /// run statements other than `SELECT` and `EXPLAIN`
+    pub allow_writes: bool,
+}
+
+pub struct Server<S>
+where
+    S: GStore + GStoreMut + Planner,
+{
+    opt: Opt,
+    glue: Glue<S>,
+    token: String,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    content_type: Option<String>,
+    host: Option<String>,
+    origin: Option<String>,
+    authorization: Option<String>,
+    body: Vec<u8>,
+}
+
+struct Response {
+    status: u16,
+    body: serde_json::Value,
+}
+
+impl Response {
+    fn ok(body: serde_json::Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: serde_json::json!({ "error": message.into() }),
+        }
+    }
+}
+
+impl<S> Server<S>
+where
+    S: GStore + GStoreMut + Planner,
+{
+    pub fn new(opt: Opt, glue: Glue<S>) -> Result<Self> {
+        let token = match opt.token.clone() {
+            Some(token) => token,
+            None => random_token()?,
+        };
+        Ok(Self { opt, glue, token })
+    }
+
+    /// Serve requests one at a time so that every statement sees the same storage cache
+    pub fn run(&mut self) -> Result<()> {
+        let listener = TcpListener::bind(self.opt.listen)
+            .with_context(|| format!("Failed to listen on {}", self.opt.listen))?;
+        eprintln!("Listening on http://{}", listener.local_addr()?);
+        if self.opt.token.is_none() {
+            eprintln!("Token: {}", self.token);
+        }
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("Failed to accept connection: {}", e);
+                    continue;
+                }
+            };
+            if let Err(e) = self.handle(stream) {
+                eprintln!("Failed to handle request: {:?}", e);
+            }
+        }
+        Ok(())
+    }
+
+    fn handle(&mut self, mut stream: TcpStream) -> Result<()> {
+        // a client which stalls would block every other one
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+        let response = match read_request(&mut stream) {
+            Ok(Ok(req)) => self.route(req),
+            Ok(Err(response)) => response,
+            Err(e) => Response::error(400, format!("{:#}", e)),
+        };
+        write_response(&mut stream, response)
+    }
+
+    fn route(&mut self, req: Request) -> Response {
+        // web pages the user visits can send requests to localhost too, or to a name of
+        // theirs resolving to it
+        if !req
+            .host
+            .as_deref()
+            .is_some_and(|host| self.is_allowed_host(host))
+        {
+            return Response::error(403, "forbidden host");
+        }
+        if let Some(origin) = &req.origin {
+            let host = origin.strip_prefix("http://").unwrap_or_default();
+            if !self.is_allowed_host(host) {
+                return Response::error(403, "forbidden origin");
+            }
+        }
+        let expected = format!("Bearer {}", self.token);
+        let authorized = req
+            .authorization
+            .as_deref()
+            .is_some_and(|authorization| constant_time_eq(authorization, &expected));
+        if !authorized {
+            return Response::error(401, "missing or wrong token");
+        }
+        match (req.method.as_str(), req.path.as_str()) {
+            ("POST", "/query") => match parse_statement(&req) {
+                Ok(statement) => self.query(&statement),
+                Err(response) => response,
+            },
+            (_, "/query") => Response::error(405, "method not allowed"),
+            _ => Response::error(404, "not found"),
+        }
+    }
+
+    /// whether `host` of the `Host` or `Origin` header names the address listened on. Any
+    /// host is allowed on an unspecified address like `0.0.0.0`, whose names aren't known
+    fn is_allowed_host(&self, host: &str) -> bool {
+        let listen = self.opt.listen;
+        if listen.ip().is_unspecified() || host == listen.to_string() {
+            return true;
+        }
+        let port = listen.port();
+        listen.ip().is_loopback()
+            && ["localhost", "127.0.0.1", "[::1]"]
+                .iter()
+                .any(|name| host == format!("{}:{}", name, port))
+    }
+
+    fn query(&mut self, text: &str) -> Response {
+        let statement = match sql::parse_all(text) {
+            Ok(statements) if statements.len() > 1 => {
+                return Response::error(400, "send one statement per request")
+            }
+            Ok(statements) => statements.into_iter().next().expect("not empty"),
+            Err(err) => return Response::error(400, error_to_string(err)),
+        };
+        // the paths would be of the machine serving, not of the client
+        if let Statement::Copy(_) = statement {
+            return Response::error(403, "COPY can't be used over HTTP");
+        }
+        if !self.opt.allow_writes && !is_read_only(&statement) {
+            return Response::error(
+                403,
+                "only SELECT and EXPLAIN are allowed, start serve with --allow-writes to change the project",
+            );
+        }
+        match sql::execute(&mut self.glue, &statement) {
+            Ok(Payload::Select { labels, rows }) => Response::ok(rows_to_json(&labels, rows)),
+            Ok(Payload::Insert(n)) => Response::ok(serde_json::json!({ "inserted": n })),
+            Ok(Payload::Update(n)) => Response::ok(serde_json::json!({ "updated": n })),
+            Ok(Payload::Delete(n)) => Response::ok(serde_json::json!({ "deleted": n })),
+            Ok(_) => Response::ok(serde_json::json!({})),
+            Err(err) => Response::error(400, error_to_string(err)),
+        }
+    }
+}
+
+/// whether `statement` only reads the project
+fn is_read_only(statement: &Statement) -> bool {
+    match statement {
+        Statement::Sql(statement) => {
+            matches!(
+                **statement,
+                SqlStatement::Query(_) | SqlStatement::Explain { .. }
+            )
+        }
+        Statement::Copy(_) => false,
+    }
+}
+
+/// Accept `{"query": "..."}` with a JSON content type, which a web page can't send to
+/// another origin without a preflight
+fn parse_statement(req: &Request) -> Result<String, Response> {
+    #[derive(Deserialize)]
+    struct QueryBody {
+        query: String,
+    }
+    let is_json = req
+        .content_type
+        .as_deref()
+        .is_some_and(|ct| ct.starts_with("application/json"));
+    if !is_json {
+        return Err(Response::error(
+            415,
+            "the body must be {\"query\": \"...\"} with Content-Type: application/json",
+        ));
+    }
+    let body: QueryBody = serde_json::from_slice(&req.body)
+        .map_err(|e| Response::error(400, format!("Failed to parse request body: {}", e)))?;
+    Ok(body.query)
+}
+
+/// a random token of 128 bits from the OS in hex
+fn random_token() -> Result<String> {
+    let mut bytes = [0; 16];
+    getrandom::getrandom(&mut bytes).map_err(|e| anyhow!("Failed to generate a token: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// `a == b` in a time which doesn't depend on where they differ, so that the token can't be
+/// guessed byte by byte
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// read a request, or the response refusing it
+fn read_request(stream: &mut TcpStream) -> Result<Result<Request, Response>> {
+    let mut reader = BufReader::new(stream.take(MAX_HEAD_LEN));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => return Err(anyhow!("Malformed request line")),
+    };
+    let mut content_length = 0;
+    let mut content_type = None;
+    let mut host = None;
+    let mut origin = None;
+    let mut authorization = None;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        if !line.ends_with('\n') {
+            return Err(anyhow!("Headers too long or incomplete"));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse().context("Invalid Content-Length")?;
+            } else if name.eq_ignore_ascii_case("content-type") {
+                content_type = Some(value.to_ascii_lowercase());
+            } else if name.eq_ignore_ascii_case("host") {
+                host = Some(value.to_ascii_lowercase());
+            } else if name.eq_ignore_ascii_case("origin") {
+                origin = Some(value.to_ascii_lowercase());
+            } else if name.eq_ignore_ascii_case("authorization") {
+                authorization = Some(value.to_string());
+            }
+        }
+    }
+    if content_length > MAX_BODY_LEN {
+        return Ok(Err(Response::error(
+            413,
+            format!("the body is larger than {} bytes", MAX_BODY_LEN),
+        )));
+    }
+    reader.get_mut().set_limit(content_length as u64);
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(Ok(Request {
+        method,
+        path,
+        content_type,
+        host,
+        origin,
+        authorization,
+        body,
+    }))
+}
+
+fn write_response(stream: &mut TcpStream, response: Response) -> Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        _ => "Internal Server Error",
+    };
+    let body = serde_json::to_vec(&response.body)?;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason,
+        body.len()
+    )?;
+    stream.write_all(&body)?;
+    stream.flush()?;
+    Ok(())
+}
@@ -0,0 +1,217 @@
+use std::fmt;
+
+use anyhow::anyhow;
+use gluesql::core::{
+    data::Value,
+    error::{Error as SqlError, Result as SqlResult},
+    executor::Payload,
+    prelude::{translate, Glue},
+    sqlparser::{
+        ast::{Delete, FromTable, Insert, Statement as SqlStatement, TableFactor},
+        dialect::GenericDialect,
+        parser::{Parser, ParserError},
+        tokenizer::{Token, Tokenizer},
+    },
+    store::{GStore, GStoreMut, Planner},
+};
+use tracing::field::Empty;
+
+use crate::{
+    columns, copy, explain, functions, gh, hook, output::error_to_string, storage::glue_error,
+};
+
+/// a statement gh-sql handles, `COPY` is executed outside of GlueSQL
+pub enum Statement {
+    Sql(Box<SqlStatement>),
+    Copy(copy::Copy),
+}
+
+impl fmt::Display for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Statement::Sql(statement) => write!(f, "{}", statement),
+            Statement::Copy(copy::Copy::To { query, path, .. }) => {
+                write!(f, "COPY ({}) TO '{}'", query, path.display())
+            }
+            Statement::Copy(copy::Copy::From {
+                table_name, path, ..
+            }) => write!(f, "COPY {} FROM '{}'", table_name, path.display()),
+        }
+    }
+}
+
+/// parse the first statement in `tokens`
+pub fn parse(tokens: Vec<Token>) -> Result<Statement, ParserError> {
+    if copy::is_copy(&tokens) {
+        return copy::parse(tokens).map(Statement::Copy);
+    }
+    let mut parser = Parser::new(&GenericDialect {}).with_tokens(tokens);
+    parser
+        .parse_statement()
+        .map(|statement| Statement::Sql(Box::new(statement)))
+}
+
+/// the table an `INSERT`, `UPDATE` or `DELETE` writes to
+pub fn target_table(statement: &SqlStatement) -> Option<String> {
+    let relation = match statement {
+        SqlStatement::Insert(Insert { table_name, .. }) => return Some(table_name.to_string()),
+        SqlStatement::Update { table, .. } => &table.relation,
+        SqlStatement::Delete(Delete {
+            from: FromTable::WithFromKeyword(from) | FromTable::WithoutKeyword(from),
+            ..
+        }) => &from.first()?.relation,
+        _ => return None,
+    };
+    match relation {
+        TableFactor::Table { name, .. } => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+#[allow(clippy::result_large_err)]
+pub fn execute<S>(glue: &mut Glue<S>, statement: &Statement) -> SqlResult<Payload>
+where
+    S: GStore + GStoreMut + Planner,
+{
+    execute_with(glue, statement, true)
+}
+
+/// `expand_wildcard` lists the columns set by [`columns::set_wildcard`] for `SELECT *`,
+/// which statements of gh-sql itself don't, as they need every column
+#[allow(clippy::result_large_err)]
+fn execute_with<S>(
+    glue: &mut Glue<S>,
+    statement: &Statement,
+    expand_wildcard: bool,
+) -> SqlResult<Payload>
+where
+    S: GStore + GStoreMut + Planner,
+{
+    let span =
+        tracing::info_span!("statement", statement = %statement, rows = Empty, error = Empty);
+    let _enter = span.enter();
+    let payload = gh::with_statement_timeout(|| match statement {
+        Statement::Sql(statement) => {
+            let mut statement = statement.clone();
+            if expand_wildcard {
+                columns::expand_wildcard(&glue.storage, &mut statement);
+            }
+            execute_sql(glue, &statement)
+        }
+        Statement::Copy(copy) => copy::execute(glue, copy),
+    });
+    match &payload {
+        Ok(Payload::Select { rows, .. }) => {
+            span.record("rows", rows.len());
+        }
+        Ok(Payload::Insert(n) | Payload::Update(n) | Payload::Delete(n)) => {
+            span.record("rows", n);
+        }
+        Ok(_) => {}
+        Err(e) => {
+            span.record("error", tracing::field::display(e));
+        }
+    }
+    payload
+}
+
+/// expand gh-sql functions, plan and execute a statement
+#[allow(clippy::result_large_err)]
+pub fn execute_sql<S>(glue: &mut Glue<S>, statement: &SqlStatement) -> SqlResult<Payload>
+where
+    S: GStore + GStoreMut + Planner,
+{
+    if let SqlStatement::Explain { statement, .. } = statement {
+        return explain::explain(glue, statement).map_err(glue_error);
+    }
+    let storage = &glue.storage;
+    let mut statement = statement.clone();
+    // before the functions, which may scan lazy columns
+    columns::set_statement(&statement);
+    functions::expand(storage, &mut statement).map_err(glue_error)?;
+    columns::expand_insert_columns(storage, &mut statement).map_err(glue_error)?;
+    let translated = translate(&statement)?;
+    let plan = storage.plan(translated.into())?;
+    let payload = match glue.execute_stmt(&plan) {
+        Ok(payload) => payload,
+        Err(e) => {
+            // the transaction is over even if some of its writes failed to be sent
+            if let SqlStatement::Commit { .. } = statement {
+                hook::discard();
+            }
+            return Err(e);
+        }
+    };
+    hook::after(&statement, &payload);
+    Ok(payload)
+}
+
+#[allow(clippy::result_large_err)]
+fn tokenize(sql: &str) -> SqlResult<Vec<Token>> {
+    Tokenizer::new(&GenericDialect {}, sql)
+        .tokenize()
+        .map_err(|e| SqlError::Parser(format!("{:?}", e)))
+}
+
+/// execute the first statement in `sql`
+#[allow(clippy::result_large_err)]
+pub fn execute_str<S>(glue: &mut Glue<S>, sql: &str) -> SqlResult<Payload>
+where
+    S: GStore + GStoreMut + Planner,
+{
+    let tokens = tokenize(sql)?;
+    if tokens.iter().all(|t| matches!(t, Token::Whitespace(_))) {
+        return Err(SqlError::Parser("Empty statement".to_string()));
+    }
+    let statement = parse(tokens).map_err(|e| SqlError::Parser(e.to_string()))?;
+    execute(glue, &statement)
+}
+
+/// execute the first statement in `sql` with every column in `SELECT *`
+#[allow(clippy::result_large_err)]
+fn execute_str_all_columns<S>(glue: &mut Glue<S>, sql: &str) -> SqlResult<Payload>
+where
+    S: GStore + GStoreMut + Planner,
+{
+    let statement = parse(tokenize(sql)?).map_err(|e| SqlError::Parser(e.to_string()))?;
+    execute_with(glue, &statement, false)
+}
+
+/// parse every statement in `sql`, separated by `;`
+#[allow(clippy::result_large_err)]
+pub fn parse_all(sql: &str) -> SqlResult<Vec<Statement>> {
+    let mut tokens = tokenize(sql)?.into_iter().peekable();
+    let mut statements = vec![];
+    while tokens.peek().is_some() {
+        let mut statement = vec![];
+        for token in tokens.by_ref() {
+            let end = token == Token::SemiColon;
+            statement.push(token);
+            if end {
+                break;
+            }
+        }
+        if statement
+            .iter()
+            .all(|t| matches!(t, Token::Whitespace(_) | Token::SemiColon))
+        {
+            continue;
+        }
+        statements.push(parse(statement).map_err(|e| SqlError::Parser(e.to_string()))?);
+    }
+    if statements.is_empty() {
+        return Err(SqlError::Parser("Empty statement".to_string()));
+    }
+    Ok(statements)
+}
+
+/// execute a `SELECT` of gh-sql itself in `sql` and return its labels and rows
+pub fn select<S>(glue: &mut Glue<S>, sql: &str) -> anyhow::Result<(Vec<String>, Vec<Vec<Value>>)>
+where
+    S: GStore + GStoreMut + Planner,
+{
+    match execute_str_all_columns(glue, sql).map_err(|e| anyhow!("{}", error_to_string(e)))? {
+        Payload::Select { labels, rows } => Ok((labels, rows)),
+        other => Err(anyhow!("unexpected payload: {:?}", other)),
+    }
+}
@@ -1,24 +1,55 @@
-use std::sync::Mutex;
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    io::{self, IsTerminal},
+    ops::Bound::{self, Excluded, Included, Unbounded},
+    path::PathBuf,
+    rc::Rc,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
 
 use anyhow::Result;
-use async_trait::async_trait;
-use gluesql::{
-    ast::{ColumnDef, ColumnOption, ColumnOptionDef, DataType},
-    data::{Row, Schema, ValueError},
-    prelude::Value,
-    result::{Error as GlueSQLError, Result as GlueSQLResult},
-    store::{GStore, GStoreMut, RowIter, Store, StoreMut},
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime};
+use gluesql::core::{
+    ast::{ColumnDef, DataType, Expr, IndexOperator, OrderByExpr},
+    data::{Key, Schema, SchemaIndex, SchemaIndexOrd, Value, ValueError},
+    error::{Error as GlueSQLError, Result as GlueSQLResult},
+    plan::StatementPlan,
+    planner::{
+        fetch_schema_map, plan_aggregate, plan_hash_join, plan_index, plan_primary_key,
+        plan_schemaless, validate,
+    },
+    store::{
+        AlterTable, AlterTableError, CustomFunction, CustomFunctionMut, Index, IndexError,
+        IndexMut, Metadata, Planner, RowIter, Store, StoreMut, Transaction,
+    },
 };
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use crate::gh::{self, GraphQLResponse};
+use crate::{
+    audit::{self, Audit},
+    checkpoint::Checkpoint,
+    columns, csv,
+    gh::{self, GraphQLResponse},
+    journal::{self, Journal},
+    output,
+    prompt::Session,
+    schema,
+    scratch::Scratch,
+};
 
+#[derive(Clone)]
 struct Field {
     id: String,
     name: String,
+    /// unique column name in `items`, derived from `name`
+    column: String,
     kind: FieldKind,
 }
 
+#[derive(Clone)]
 enum FieldKind {
     Normal(FieldType),
     SingleSelect(Vec<FieldOption>),
@@ -32,7 +63,7 @@ enum FieldKind {
     },
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[allow(nonstandard_style, clippy::upper_case_acronyms)]
 enum FieldType {
     ASSIGNEES,
@@ -47,6 +78,7 @@ enum FieldType {
     TITLE,
     TRACKED_BY,
     TRACKS,
+    /// a data type GitHub added after schema.docs.graphql, read as text
     Other(String),
 }
 
@@ -62,11 +94,110 @@ impl FieldType {
     }
 }
 
+impl Field {
+    /// the data type of a field of a kind unknown to gh-sql
+    fn unknown_kind(&self) -> Option<&str> {
+        match &self.kind {
+            FieldKind::Normal(FieldType::Other(kind)) => Some(kind),
+            _ => None,
+        }
+    }
+
+    /// the error of writing the field, which is read-only
+    fn readonly(&self) -> GlueSQLError {
+        match self.unknown_kind() {
+            Some(kind) => GlueSQLError::StorageMsg(format!(
+                "readonly column: {} is a {} field, which gh-sql can read as text but not write",
+                self.column, kind
+            )),
+            None => GlueSQLError::StorageMsg(format!("readonly column: {}", self.column)),
+        }
+    }
+
+    fn data_type(&self) -> DataType {
+        match &self.kind {
+            FieldKind::Normal(FieldType::DATE) => DataType::Date,
+            FieldKind::Normal(FieldType::NUMBER) => DataType::Float,
+            FieldKind::Normal(
+                FieldType::ASSIGNEES
+                | FieldType::LABELS
+                | FieldType::LINKED_PULL_REQUESTS
+                | FieldType::REVIEWERS,
+            ) => DataType::List,
+            _ => DataType::Text,
+        }
+    }
+}
+
+/// a table as printed by the `schema` subcommand
+#[derive(Debug, Serialize)]
+pub struct TableDescription {
+    pub name: String,
+    pub columns: Vec<ColumnDescription>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ColumnDescription {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub data_type: String,
+    pub nullable: bool,
+    pub writable: bool,
+    /// names of the options of a single select field
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<Vec<String>>,
+    /// the data type of a field of a kind unknown to gh-sql, read as text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unknown_kind: Option<String>,
+}
+
+impl TableDescription {
+    /// `column` gives the writability and options of the column at an index
+    fn new(schema: &Schema, column: impl Fn(usize) -> (bool, Option<Vec<String>>)) -> Self {
+        let columns = schema
+            .column_defs
+            .iter()
+            .flatten()
+            .enumerate()
+            .map(|(idx, def)| {
+                let (writable, options) = column(idx);
+                ColumnDescription {
+                    name: def.name.clone(),
+                    data_type: format!("{:?}", def.data_type).to_uppercase(),
+                    nullable: def.nullable,
+                    writable,
+                    options,
+                    unknown_kind: None,
+                }
+            })
+            .collect();
+        Self {
+            name: schema.table_name.clone(),
+            columns,
+        }
+    }
+}
+
+/// metadata of the project, the only row of `project`
+#[derive(Clone)]
+struct Project {
+    id: String,
+    title: String,
+    short_description: Option<String>,
+    readme: Option<String>,
+    public: bool,
+    closed: bool,
+}
+
+#[derive(Clone)]
 struct FieldOption {
     id: String,
     name: String,
+    color: String,
+    description: String,
 }
 
+#[derive(Clone)]
 struct FieldIteration {
     id: String,
     title: String,
@@ -74,72 +205,569 @@ struct FieldIteration {
     start_date: String,
 }
 
+impl FieldIteration {
+    /// the first day of the iteration
+    fn start_date(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(&self.start_date, "%Y-%m-%d").ok()
+    }
+
+    /// the last day of the iteration (inclusive)
+    fn end_date(&self) -> Option<NaiveDate> {
+        Some(self.start_date()? + Duration::days(self.duration - 1))
+    }
+
+    fn contains(&self, date: NaiveDate) -> bool {
+        match (self.start_date(), self.end_date()) {
+            (Some(start_date), Some(end_date)) => start_date <= date && date <= end_date,
+            _ => false,
+        }
+    }
+}
+
+/// columns of `items` which are not backed by a project field
+const RESERVED_COLUMNS: [(&str, DataType); 18] = [
+    ("id", DataType::Text),
+    ("Repository", DataType::Text),
+    ("Issue", DataType::Int),
+    ("Title", DataType::Text),
+    ("Assignees", DataType::List),
+    ("Labels", DataType::List),
+    ("State", DataType::Text),
+    // lazy, see `LAZY_COLUMNS`
+    ("Body", DataType::Text),
+    ("Comments", DataType::Int),
+    ("Reactions", DataType::Int),
+    ("MilestoneDueOn", DataType::Date),
+    ("MilestoneState", DataType::Text),
+    // the users and teams of the Reviewers field
+    ("ReviewerUsers", DataType::List),
+    ("ReviewerTeams", DataType::List),
+    ("ClosedAt", DataType::Timestamp),
+    ("MergedAt", DataType::Timestamp),
+    // lazy, the body of the latest comment and when the timeline last changed
+    ("LastComment", DataType::Text),
+    ("TimelineUpdatedAt", DataType::Timestamp),
+];
+
+/// the first [`RESERVED_COLUMNS`], whose names fields never get. The later ones, added
+/// since, give way to fields of the same names, so that queries of those fields keep
+/// reading them
+const CORE_COLUMNS: usize = 6;
+
+/// reserved columns of `items` an `UPDATE` can write, the others are read-only
+const WRITABLE_COLUMNS: [&str; 2] = ["Repository", "State"];
+
+/// reserved columns of `items` which are `NULL` until a statement names them, then fetched
+/// per 100 items with a request of their own, as they are costly to list with every item
+const LAZY_COLUMNS: [&str; 3] = ["Body", "LastComment", "TimelineUpdatedAt"];
+
+/// the index of the reserved column `name` in the rows of `items`
+fn reserved_index(name: &str) -> usize {
+    RESERVED_COLUMNS
+        .iter()
+        .position(|(reserved, _)| *reserved == name)
+        .unwrap()
+}
+
+/// the index of the column of the field at `field_idx` in the rows of `items`, after the
+/// [`RESERVED_COLUMNS`]
+fn field_column_index(field_idx: usize) -> usize {
+    RESERVED_COLUMNS.len() + field_idx
+}
+
+/// the index of the field of the column of `items` at `col_idx`, `None` for a reserved one
+fn column_field_index(col_idx: usize) -> Option<usize> {
+    col_idx.checked_sub(RESERVED_COLUMNS.len())
+}
+
+/// the column names given to the [`LAZY_COLUMNS`]
+fn lazy_columns(reserved_columns: &[String]) -> Vec<String> {
+    LAZY_COLUMNS
+        .iter()
+        .map(|name| reserved_columns[reserved_index(name)].clone())
+        .collect()
+}
+
+#[cfg(feature = "mock")]
+pub mod mock;
+
+#[derive(Clone)]
+pub struct Opt {
+    /// normalize column names like "Linked pull requests" to `linked_pull_requests`
+    pub snake_case_columns: bool,
+    /// CSV files exposed as read-only tables
+    pub csv_mounts: Vec<csv::Mount>,
+    /// skip the confirmation of destructive operations like `DROP COLUMN`
+    pub yes: bool,
+    /// whether that confirmation may be asked on the terminal, which `serve` can't
+    pub interactive: bool,
+    /// write to items even if they changed since they were fetched
+    pub force: bool,
+    /// what an `UPDATE` of items does after a change failed
+    pub on_error: OnError,
+    /// where the changed field values are recorded for `undo`
+    pub journal: Option<Journal>,
+    /// where the mutations are appended as SQL
+    pub audit: Option<Audit>,
+    /// the columns `SELECT * FROM items` lists
+    pub column_order: ColumnOrder,
+    /// where the pages of a scan of items are saved to resume it after a failure
+    pub checkpoints: Option<PathBuf>,
+    /// keep the first page of the labels and assignees of an item instead of fetching the rest
+    pub truncate_lists: bool,
+}
+
+/// the order of the columns of `SELECT * FROM items`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColumnOrder {
+    /// every column, the fields in the order of the API
+    Api,
+    /// the fields shown by a table view in its order, the first table view if `None`
+    View(Option<String>),
+}
+
+impl FromStr for ColumnOrder {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "api" => Ok(Self::Api),
+            "view" => Ok(Self::View(None)),
+            _ => match s.strip_prefix("view:") {
+                Some(name) => Ok(Self::View(Some(name.to_string()))),
+                None => Err(anyhow::anyhow!(
+                    "Unknown column order: {}, expected api, view or view:NAME",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+/// whether the remaining changes of an `UPDATE` are sent after one of them failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnError {
+    Continue,
+    Abort,
+}
+
+impl FromStr for OnError {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "continue" => Ok(OnError::Continue),
+            "abort" => Ok(OnError::Abort),
+            other => Err(anyhow::anyhow!("Unknown error handling: {}", other)),
+        }
+    }
+}
+
+/// changes of which some failed while the others were applied, by an `UPDATE` or `apply`
+#[derive(Debug)]
+pub struct PartialFailure {
+    pub applied: usize,
+    /// item id, column and error of each failed change
+    pub failures: Vec<(String, String, String)>,
+}
+
+impl fmt::Display for PartialFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} change(s) failed and {} were applied",
+            self.failures.len(),
+            self.applied
+        )
+    }
+}
+
+impl std::error::Error for PartialFailure {}
+
+/// an error of the storage as GlueSQL's, from which [`output`] can still tell what failed
+pub struct StorageError(pub anyhow::Error);
+
+impl fmt::Debug for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+thread_local! {
+    /// the message and the error of the last [`glue_error`] of the thread
+    static LAST_ERROR: RefCell<Option<(String, Rc<anyhow::Error>)>> = const { RefCell::new(None) };
+}
+
+/// `e` as GlueSQL's error, which only carries its message, [`output`] can still tell what
+/// failed from [`error_cause`]
+pub fn glue_error(e: anyhow::Error) -> GlueSQLError {
+    let message = format!("{:#}", e);
+    LAST_ERROR.set(Some((message.clone(), Rc::new(e))));
+    GlueSQLError::StorageMsg(message)
+}
+
+/// the error `e` was made of by [`glue_error`], if it was on this thread
+pub fn error_cause(e: &GlueSQLError) -> Option<Rc<anyhow::Error>> {
+    let GlueSQLError::StorageMsg(message) = e else {
+        return None;
+    };
+    LAST_ERROR.with_borrow(|last| match last {
+        Some((last_message, e)) if last_message == message => Some(Rc::clone(e)),
+        _ => None,
+    })
+}
+
+fn storage_error(e: anyhow::Error) -> GlueSQLError {
+    glue_error(StorageError(e).into())
+}
+
+/// `query` with the spreads on `ProjectV2FieldCommon` and `ProjectV2ItemFieldValueCommon`
+/// uncommented. GitHub accepts them but the code generator can't validate a spread of an
+/// interface in a union, and they select the id, name and data type of the fields and
+/// values of the kinds added after schema.docs.graphql, which `gh::graphql` then degrades
+/// to text instead of failing
+fn with_common_fields(query: &str) -> String {
+    query.replace("#... on ProjectV2", "... on ProjectV2")
+}
+
+/// set the field `field` (name or column) of the item `id` to `value`, NULL clears it
+#[derive(Debug)]
+pub struct FieldUpdate {
+    pub id: String,
+    pub field: String,
+    pub value: Value,
+}
+
 pub struct ProjectNextStorage {
     owner: String,
     project_number: i64,
-    cache: Mutex<Option<Cache>>,
+    opt: Opt,
+    /// shared with the storages of [`Session::reader`]
+    cache: Arc<Mutex<Option<Cache>>>,
+    scratch: Scratch,
+    transaction: Option<TransactionBuffer>,
+}
+
+/// writes to the project buffered between `BEGIN` and `COMMIT`
+struct TransactionBuffer {
+    /// `items` when the transaction began, `COMMIT` sends the differences from it
+    base_items: SharedRows,
+    /// restored by `ROLLBACK`
+    scratch: Scratch,
+    /// ids of the items updated in the cache, in order
+    updated: Vec<String>,
+    /// ids of the items removed from the cache
+    deleted: Vec<String>,
+    /// rows to add, which are not visible until `COMMIT`
+    inserted: Vec<Vec<Value>>,
+    /// writes to `options` and `project`, which are not visible until `COMMIT`
+    writes: Vec<Write>,
+}
+
+enum Write {
+    InsertOptions(Vec<Vec<Value>>),
+    UpdateOptions(Vec<(String, Vec<Value>)>),
+    DeleteOptions(Vec<String>),
+    UpdateProject(Vec<(String, Vec<Value>)>),
+}
+
+impl TransactionBuffer {
+    fn len(&self) -> usize {
+        self.updated.len() + self.deleted.len() + self.inserted.len() + self.writes.len()
+    }
+}
+
+/// the index of `items` over its `id`, which GlueSQL plans for `WHERE id = '...'` and
+/// `ORDER BY id` instead of a full scan
+const ITEMS_INDEX: &str = "items_id";
+
+/// field values set by `COMMIT` in one request
+const COMMIT_BATCH_SIZE: usize = 50;
+
+/// tables backed by the project, any other table lives in [`Scratch`]
+const PROJECT_TABLES: [&str; 10] = [
+    "items",
+    "options",
+    "iterations",
+    "project",
+    "reactions",
+    "item_events",
+    "issues",
+    "pull_requests",
+    "assignable_users",
+    "labels",
+];
+
+pub fn is_project_table(table_name: &str) -> bool {
+    PROJECT_TABLES.contains(&table_name)
+}
+
+/// how the rows of a table are fetched, for `EXPLAIN`
+pub fn fetch_plan(table_name: &str) -> &'static str {
+    match table_name {
+        "items" => {
+            "listFields, listViews with --column-order view, then listItems per 100 items and listContentLabels or listContentAssignees per 100 more labels or assignees of an item than its first 10; once per session; listItemBodies, listItemComments or listItemTimelines per 100 items once a statement names Body, LastComment or TimelineUpdatedAt"
+        }
+        "options" | "iterations" | "project" => {
+            "listFields, listViews with --column-order view; once per session, shared by items, options, iterations and project, without paginating the items"
+        }
+        "reactions" => {
+            "listReactions per 100 items and listContentReactions per 100 more reactions of an item than its first 100, once per session on first use"
+        }
+        "item_events" => {
+            "listItemEvents per 20 items and listContentEvents per 100 more events of an item than its first 100, once per session on first use"
+        }
+        "issues" => {
+            "listLinkedRepositories once per session, then listIssues per 100 issues of each linked repository, once per session on first use"
+        }
+        "pull_requests" => {
+            "listLinkedRepositories once per session, then listPullRequests per 100 pull requests of each linked repository, once per session on first use"
+        }
+        "assignable_users" => {
+            "listLinkedRepositories once per session, then listAssignableUsers per 100 users of each linked repository, once per session on first use"
+        }
+        "labels" => {
+            "listLinkedRepositories once per session, then listLabels per 100 labels of each linked repository, once per session on first use"
+        }
+        _ => "in memory, no requests",
+    }
 }
 
+/// keyed rows which the cache shares with the scans reading them
+type SharedRows = Arc<Vec<(String, Vec<Value>)>>;
+
+#[derive(Clone)]
 pub struct Cache {
-    project_id: String,
+    project: Project,
+    reserved_columns: Vec<String>,
     fields: Vec<Field>,
-    items: Vec<(String, Row)>,
+    /// shared with the scans reading it, a write clones it only while one is in progress
+    items: SharedRows,
+    /// the position of every item in `items` by id, kept by [`Cache::index_items`] for
+    /// lookups, ranges and the order of [`ITEMS_INDEX`]
+    item_positions: BTreeMap<String, usize>,
+    /// `updatedAt` of the items when they were fetched, compared before writing to them
+    updated_at: HashMap<String, String>,
+    /// whether `items` and `updated_at` have been fetched, `options`, `iterations` and
+    /// `project` are served without them
+    items_loaded: bool,
+    /// whether a scan has read `options`, `iterations` or `project`, the following scans are
+    /// cache hits
+    scanned: bool,
+    /// the [`LAZY_COLUMNS`] of `items` which have been fetched
+    lazy_loaded: HashSet<&'static str>,
+    /// fetched on the first scan of `reactions`, shared with the scans like `items`
+    reactions: Option<SharedRows>,
+    /// fetched on the first scan of `item_events`
+    item_events: Option<SharedRows>,
+    /// `nameWithOwner` of the repositories linked to the project, fetched on the first scan
+    /// of `issues`, `pull_requests`, `assignable_users` or `labels`
+    repositories: Option<Vec<String>>,
+    /// fetched on the first scan of `issues`
+    issues: Option<SharedRows>,
+    /// fetched on the first scan of `pull_requests`
+    pull_requests: Option<SharedRows>,
+    /// fetched on the first scan of `assignable_users`
+    assignable_users: Option<SharedRows>,
+    /// fetched on the first scan of `labels`
+    labels: Option<SharedRows>,
 }
 
 impl Cache {
-    fn items_schema(&self) -> Schema {
-        let reserved_column_defs = [
-            ColumnDef {
-                name: "id".to_string(),
-                data_type: DataType::Text,
-                options: vec![],
-            },
-            ColumnDef {
-                name: "Repository".to_string(),
-                data_type: DataType::Text,
-                options: vec![],
-            },
-            ColumnDef {
-                name: "Issue".to_string(),
-                data_type: DataType::Int,
-                options: vec![],
-            },
-            ColumnDef {
-                name: "Title".to_string(),
-                data_type: DataType::Text,
-                options: vec![],
-            },
-            ColumnDef {
-                name: "Assignees".to_string(),
-                data_type: DataType::List,
-                options: vec![],
-            },
-            ColumnDef {
-                name: "Labels".to_string(),
-                data_type: DataType::List,
-                options: vec![],
-            },
-        ];
-        let field_column_defs = self.fields.iter().map(|field| ColumnDef {
-            name: field.name.to_string(),
-            data_type: DataType::Text,
-            options: vec![ColumnOptionDef {
-                option: ColumnOption::Null,
-                name: None,
-            }],
-        });
-        let column_defs = reserved_column_defs
-            .into_iter()
-            .chain(field_column_defs)
+    /// index `items` by id, after it is replaced or items are added or removed
+    fn index_items(&mut self) {
+        self.item_positions = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(idx, (id, _))| (id.clone(), idx))
+            .collect();
+    }
+
+    /// the position of the item `item_id` in `items`
+    fn item_position(&self, item_id: &str) -> Option<usize> {
+        self.item_positions.get(item_id).copied()
+    }
+
+    /// the row of the item `item_id`
+    fn item(&self, item_id: &str) -> Option<&Vec<Value>> {
+        self.item_position(item_id).map(|idx| &self.items[idx].1)
+    }
+
+    /// a condition matching the item by its issue or pull request, or the title of a draft
+    /// issue, so that the audited statements can be run on another project
+    fn item_condition(&self, item_id: &str) -> String {
+        let Some(row) = self.item(item_id) else {
+            return audit::eq(self.reserved_column("id"), &Value::Str(item_id.to_string()));
+        };
+        let [_, repository, issue, title, ..] = row.as_slice() else {
+            unreachable!()
+        };
+        let conditions = if repository.is_null() {
+            [("Repository", repository), ("Title", title)]
+        } else {
+            [("Repository", repository), ("Issue", issue)]
+        };
+        let conditions: Vec<_> = conditions
+            .iter()
+            .map(|(name, value)| audit::eq(self.reserved_column(name), value))
             .collect();
+        conditions.join(" AND ")
+    }
+
+    /// the id of the item of the issue or pull request, if it is in the project
+    fn item_of(&self, repository: &str, number: i64) -> Option<&str> {
+        self.items
+            .iter()
+            .find(|(_, row)| match row.as_slice() {
+                [_, Value::Str(repo), Value::I64(issue), ..] => {
+                    repo == repository && *issue == number
+                }
+                _ => false,
+            })
+            .map(|(id, _)| id.as_str())
+    }
+
+    /// `UPDATE items SET column = value WHERE ...` of the item
+    fn audit_item_update(&self, item_id: &str, column: &str, value: &Value) -> String {
+        audit::update(
+            "items",
+            &[(column, value)],
+            Some(&self.item_condition(item_id)),
+        )
+    }
+
+    /// the column name given to the reserved column `name`
+    fn reserved_column(&self, name: &str) -> &str {
+        &self.reserved_columns[reserved_index(name)]
+    }
+
+    /// whether the column of `items` at `idx` can be written, see [`WRITABLE_COLUMNS`]
+    fn is_writable(&self, idx: usize) -> bool {
+        match column_field_index(idx) {
+            None => WRITABLE_COLUMNS.contains(&RESERVED_COLUMNS[idx].0),
+            Some(field_idx) => match &self.fields[field_idx].kind {
+                FieldKind::Normal(ty) => ty.as_sql_type().is_some(),
+                FieldKind::SingleSelect(_) | FieldKind::Iteration { .. } => true,
+            },
+        }
+    }
+
+    /// the names of the options, or the titles of the iterations, which the field of `items`
+    /// shown as `column` can be set to
+    fn choices(&self, column: &str) -> Option<Vec<String>> {
+        let field = self.fields.iter().find(|field| field.column == column)?;
+        match &field.kind {
+            FieldKind::SingleSelect(options) => {
+                Some(options.iter().map(|option| option.name.clone()).collect())
+            }
+            FieldKind::Iteration {
+                iterations,
+                completed_iterations,
+                ..
+            } => Some(
+                iterations
+                    .iter()
+                    .chain(completed_iterations)
+                    .map(|iteration| iteration.title.clone())
+                    .collect(),
+            ),
+            FieldKind::Normal(_) => None,
+        }
+    }
+
+    /// the error of writing the read-only column of `items` at `idx`
+    fn readonly(&self, idx: usize) -> GlueSQLError {
+        match column_field_index(idx) {
+            None => {
+                GlueSQLError::StorageMsg(format!("readonly column: {}", self.reserved_columns[idx]))
+            }
+            Some(field_idx) => self.fields[field_idx].readonly(),
+        }
+    }
+
+    fn items_schema(&self) -> Schema {
+        let reserved_column_defs = RESERVED_COLUMNS
+            .iter()
+            .zip(&self.reserved_columns)
+            .map(|((_, data_type), name)| column_def(name, data_type.clone(), true));
+        let field_column_defs = self
+            .fields
+            .iter()
+            .map(|field| column_def(&field.column, field.data_type(), true));
+        let column_defs = reserved_column_defs.chain(field_column_defs).collect();
         Schema {
-            table_name: "items".to_string(),
-            column_defs,
-            indexes: vec![],
+            indexes: vec![SchemaIndex {
+                name: ITEMS_INDEX.to_string(),
+                expr: Expr::Identifier(self.reserved_column("id").to_string()),
+                order: SchemaIndexOrd::Both,
+                created: NaiveDateTime::default(),
+            }],
+            ..table_schema("items", column_defs)
+        }
+    }
+
+    /// the rows of `items`, each cloned as it is read rather than all of them up front
+    fn scan_items(&self) -> RowIter<'static> {
+        scan_shared(Arc::clone(&self.items))
+    }
+
+    /// the rows of `items` whose id compares to the value as [`ITEMS_INDEX`] is scanned for
+    /// `WHERE id = '...'`, sorted by id for `ORDER BY id` if `asc` is given. Only the matching
+    /// rows are cloned
+    fn scan_items_by_id(
+        &self,
+        asc: Option<bool>,
+        cmp_value: Option<(&IndexOperator, Value)>,
+    ) -> RowIter<'static> {
+        let range = match &cmp_value {
+            None => Some((Unbounded, Unbounded)),
+            // the planner looks up `IS NULL` as `= NULL` and `IS NOT NULL` as `< NULL`, every
+            // id is less than `NULL`
+            Some((op, Value::Null)) => matches!(op, IndexOperator::Lt | IndexOperator::LtEq)
+                .then_some((Unbounded, Unbounded)),
+            Some((op, Value::Str(id))) => Some(match op {
+                IndexOperator::Eq => (Included(id.as_str()), Included(id.as_str())),
+                IndexOperator::Gt => (Excluded(id.as_str()), Unbounded),
+                IndexOperator::Lt => (Unbounded, Excluded(id.as_str())),
+                IndexOperator::GtEq => (Included(id.as_str()), Unbounded),
+                IndexOperator::LtEq => (Unbounded, Included(id.as_str())),
+            }),
+            // ids compare to no other type
+            Some(_) => None,
+        };
+        let mut indexes: Vec<_> = match range {
+            Some(range) => self
+                .item_positions
+                .range::<str, (Bound<&str>, Bound<&str>)>(range)
+                .map(|(_, idx)| *idx)
+                .collect(),
+            None => vec![],
+        };
+        match asc {
+            Some(true) => {}
+            Some(false) => indexes.reverse(),
+            None => indexes.sort_unstable(),
         }
+        let items = Arc::clone(&self.items);
+        Box::new(
+            indexes
+                .into_iter()
+                .map(move |idx| items[idx].clone())
+                .map(keyed),
+        )
     }
 
-    fn scan_iterations(&self) -> RowIter<String> {
+    fn scan_iterations(&self) -> RowIter<'static> {
+        let today = Local::now().naive_local().date();
         #[allow(clippy::needless_collect)]
         let rows: Vec<_> = self
             .fields
@@ -147,6 +775,7 @@ impl Cache {
             .filter_map(|field| {
                 if let Field {
                     id: field_id,
+                    column: field_name,
                     kind:
                         FieldKind::Iteration {
                             iterations,
@@ -157,40 +786,52 @@ impl Cache {
                 } = field
                 {
                     let iterations = iterations.iter().map(
-                        |FieldIteration {
+                        |iteration @ FieldIteration {
                              id,
                              title,
-                             start_date,
                              duration,
+                             ..
                          }| {
                             let key = id.to_string();
-                            let row = Row(vec![
+                            let start_date = iteration.start_date();
+                            let end_date = iteration.end_date();
+                            let is_current = iteration.contains(today);
+                            let row = vec![
                                 Value::Str(field_id.to_string()),
                                 Value::Str(id.to_string()),
                                 Value::Str(title.to_string()),
-                                Value::Str(start_date.to_string()),
+                                start_date.map_or(Value::Null, Value::Date),
                                 Value::I64(*duration),
                                 Value::Bool(false),
-                            ]);
+                                end_date.map_or(Value::Null, Value::Date),
+                                Value::Bool(is_current),
+                                Value::Str(field_name.to_string()),
+                            ];
                             (key, row)
                         },
                     );
                     let completed_iterations = completed_iterations.iter().map(
-                        |FieldIteration {
+                        |iteration @ FieldIteration {
                              id,
                              title,
-                             start_date,
                              duration,
+                             ..
                          }| {
                             let key = id.to_string();
-                            let row = Row(vec![
+                            let start_date = iteration.start_date();
+                            let end_date = iteration.end_date();
+                            let is_current = iteration.contains(today);
+                            let row = vec![
                                 Value::Str(field_id.to_string()),
                                 Value::Str(id.to_string()),
                                 Value::Str(title.to_string()),
-                                Value::Str(start_date.to_string()),
+                                start_date.map_or(Value::Null, Value::Date),
                                 Value::I64(*duration),
                                 Value::Bool(true),
-                            ]);
+                                end_date.map_or(Value::Null, Value::Date),
+                                Value::Bool(is_current),
+                                Value::Str(field_name.to_string()),
+                            ];
                             (key, row)
                         },
                     );
@@ -200,12 +841,29 @@ impl Cache {
                 }
             })
             .flatten()
-            .map(Ok)
+            .map(keyed)
             .collect();
         Box::new(rows.into_iter())
     }
 
-    fn scan_options(&self) -> RowIter<String> {
+    fn scan_project(&self) -> RowIter<'static> {
+        let project = &self.project;
+        let text = |value: &Option<String>| match value {
+            Some(s) => Value::Str(s.clone()),
+            None => Value::Null,
+        };
+        let row = vec![
+            Value::Str(project.id.clone()),
+            Value::Str(project.title.clone()),
+            text(&project.short_description),
+            text(&project.readme),
+            Value::Bool(project.public),
+            Value::Bool(project.closed),
+        ];
+        Box::new(std::iter::once(keyed((project.id.clone(), row))))
+    }
+
+    fn scan_options(&self) -> RowIter<'static> {
         #[allow(clippy::needless_collect)]
         let rows: Vec<_> = self
             .fields
@@ -217,71 +875,471 @@ impl Cache {
                     ..
                 } = field
                 {
-                    Some(options.iter().map(|FieldOption { id, name }| {
-                        let key = id.to_string();
-                        let row = Row(vec![
-                            Value::Str(field_id.to_string()),
-                            Value::Str(id.to_string()),
-                            Value::Str(name.to_string()),
-                        ]);
-                        (key, row)
-                    }))
+                    Some(options.iter().map(
+                        |FieldOption {
+                             id,
+                             name,
+                             color,
+                             description,
+                         }| {
+                            let key = id.to_string();
+                            let row = vec![
+                                Value::Str(field_id.to_string()),
+                                Value::Str(id.to_string()),
+                                Value::Str(name.to_string()),
+                                Value::Str(color.to_string()),
+                                Value::Str(description.to_string()),
+                            ];
+                            (key, row)
+                        },
+                    ))
                 } else {
                     None
                 }
             })
             .flatten()
-            .map(Ok)
+            .map(keyed)
             .collect();
         Box::new(rows.into_iter())
     }
+
+    /// the index of the single select field which has the option `option_id`, and the option
+    fn find_option(&self, option_id: &str) -> Option<(usize, &FieldOption)> {
+        self.fields
+            .iter()
+            .enumerate()
+            .find_map(|(field_idx, field)| match &field.kind {
+                FieldKind::SingleSelect(options) => options
+                    .iter()
+                    .find(|option| option.id == option_id)
+                    .map(|option| (field_idx, option)),
+                _ => None,
+            })
+    }
+
+    /// ids of the items whose value of the single select field is `option`
+    fn items_with_option<'a>(
+        &'a self,
+        field_idx: usize,
+        option: &'a FieldOption,
+    ) -> impl Iterator<Item = &'a String> {
+        self.items.iter().filter_map(move |(item_id, row)| {
+            match &row[field_column_index(field_idx)] {
+                Value::Str(name) if name == &option.name => Some(item_id),
+                _ => None,
+            }
+        })
+    }
 }
 
-#[allow(warnings)]
-mod generated {
-    type Date = String;
-    include!(concat!(env!("OUT_DIR"), "/list_fields.rs"));
-    include!(concat!(env!("OUT_DIR"), "/list_items.rs"));
-    include!(concat!(env!("OUT_DIR"), "/update_item_field.rs"));
-    include!(concat!(env!("OUT_DIR"), "/delete_item.rs"));
+/// node id of the content of an item
+enum Content {
+    Issue(String),
+    PullRequest(String),
 }
 
-impl ProjectNextStorage {
-    pub fn new(owner: String, project_number: i64) -> Result<Self> {
-        Ok(Self {
-            owner,
-            project_number,
-            cache: Mutex::new(None),
-        })
+impl Content {
+    fn into_id(self) -> String {
+        match self {
+            Content::Issue(id) | Content::PullRequest(id) => id,
+        }
     }
+}
 
-    fn list_fields(&self) -> Result<(String, Vec<Field>)> {
-        use generated::list_fields::*;
-        type SingleSelectFieldOption =
-            ProjectV2ProjectV2FieldsNodesOnProjectV2SingleSelectFieldOptions;
-        impl From<SingleSelectFieldOption> for FieldOption {
-            fn from(SingleSelectFieldOption { id, name }: SingleSelectFieldOption) -> Self {
-                Self { id, name }
-            }
+/// an option of a single select field as sent to `updateProjectV2Field`
+struct OptionEdit {
+    /// `None` for an option which is being added
+    id: Option<String>,
+    name: String,
+    color: String,
+    description: String,
+}
+
+impl From<&FieldOption> for OptionEdit {
+    fn from(option: &FieldOption) -> Self {
+        Self {
+            id: Some(option.id.clone()),
+            name: option.name.clone(),
+            color: option.color.clone(),
+            description: option.description.clone(),
         }
-        type CompletedIteration =
-            ProjectV2ProjectV2FieldsNodesOnProjectV2IterationFieldConfigurationCompletedIterations;
-        impl From<CompletedIteration> for FieldIteration {
-            fn from(
-                CompletedIteration {
-                    id,
-                    title,
-                    duration,
-                    start_date,
-                    ..
-                }: CompletedIteration,
-            ) -> Self {
-                Self {
-                    id,
-                    title,
-                    duration,
-                    start_date,
-                }
+    }
+}
+
+/// pending option lists of the fields touched by one statement, in order of appearance
+#[derive(Default)]
+struct OptionEdits(Vec<(usize, Vec<OptionEdit>)>);
+
+impl OptionEdits {
+    /// the options of the field at `field_idx`, starting from the current ones
+    fn field(&mut self, cache: &Cache, field_idx: usize) -> &mut Vec<OptionEdit> {
+        let pos = match self.0.iter().position(|(idx, _)| *idx == field_idx) {
+            Some(pos) => pos,
+            None => {
+                let options = match &cache.fields[field_idx].kind {
+                    FieldKind::SingleSelect(options) => options.iter().map(Into::into).collect(),
+                    _ => vec![],
+                };
+                self.0.push((field_idx, options));
+                self.0.len() - 1
+            }
+        };
+        &mut self.0[pos].1
+    }
+}
+
+/// Give every field a unique column name, suffixing `_2`, `_3`, ... on collisions. A field
+/// named like a reserved column after the [`CORE_COLUMNS`] keeps its name, and the reserved
+/// column is suffixed instead with a warning. Returns the names of the reserved columns.
+fn assign_columns(fields: &mut [Field], snake_case_columns: bool) -> Vec<String> {
+    let normalize = |name: &str| {
+        if snake_case_columns {
+            to_snake_case(name)
+        } else {
+            sanitize_column_name(name)
+        }
+    };
+    let field_columns: HashSet<String> =
+        fields.iter().map(|field| normalize(&field.name)).collect();
+    let mut taken = HashSet::new();
+    let mut reserved_columns = vec![];
+    for (idx, (name, _)) in RESERVED_COLUMNS.iter().enumerate() {
+        let base = normalize(name);
+        let mut column = base.clone();
+        let mut n = 2;
+        while idx >= CORE_COLUMNS && field_columns.contains(&column) {
+            column = format!("{}_{}", base, n);
+            n += 1;
+        }
+        if column != base {
+            eprintln!(
+                "The built-in column {} is named {}, as a field of the project has its name",
+                base, column
+            );
+        }
+        taken.insert(column.clone());
+        reserved_columns.push(column);
+    }
+    for field in fields {
+        let base = normalize(&field.name);
+        let mut column = base.clone();
+        let mut n = 2;
+        while taken.contains(&column) {
+            column = format!("{}_{}", base, n);
+            n += 1;
+        }
+        taken.insert(column.clone());
+        field.column = column;
+    }
+    reserved_columns
+}
+
+/// the CSV files mounted as read-only tables next to the project tables
+fn mount_csvs(csv_mounts: &[csv::Mount]) -> Result<Scratch> {
+    let mut scratch = Scratch::default();
+    for mount in csv_mounts {
+        if PROJECT_TABLES.contains(&mount.name.as_str()) {
+            return Err(anyhow::anyhow!(
+                "Cannot mount over project table: {}",
+                mount.name
+            ));
+        }
+        let (schema, rows) = mount.load()?;
+        scratch.mount(schema, rows);
+    }
+    Ok(scratch)
+}
+
+/// definition of a column of the tables gh-sql provides
+fn column_def(name: &str, data_type: DataType, nullable: bool) -> ColumnDef {
+    ColumnDef {
+        name: name.to_string(),
+        data_type,
+        nullable,
+        default: None,
+        unique: None,
+        comment: None,
+    }
+}
+
+/// schema of a table gh-sql provides, whose rows have no primary key
+fn table_schema(table_name: &str, column_defs: Vec<ColumnDef>) -> Schema {
+    Schema {
+        table_name: table_name.to_string(),
+        column_defs: Some(column_defs),
+        indexes: vec![],
+        engine: None,
+        foreign_keys: vec![],
+        comment: None,
+    }
+}
+
+/// a row of the tables gh-sql provides as it is scanned, keyed by the id of its node
+fn keyed((id, row): (String, Vec<Value>)) -> GlueSQLResult<(Key, Vec<Value>)> {
+    Ok((Key::Str(id), row))
+}
+
+/// the node id a row given back by GlueSQL was scanned with by [`keyed`]
+fn node_id(key: Key) -> GlueSQLResult<String> {
+    match key {
+        Key::Str(id) => Ok(id),
+        key => Err(GlueSQLError::StorageMsg(format!(
+            "invalid row key: {:?}",
+            key
+        ))),
+    }
+}
+
+/// strip characters which can not be written in a quoted identifier
+fn sanitize_column_name(name: &str) -> String {
+    let name = name.trim().replace(['"', '`'], "_");
+    if name.is_empty() {
+        "field".to_string()
+    } else {
+        name
+    }
+}
+
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len());
+    let mut prev_is_lower = false;
+    for c in name.chars() {
+        if c.is_alphanumeric() {
+            if c.is_uppercase() && prev_is_lower {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+            prev_is_lower = c.is_lowercase() || c.is_numeric();
+        } else {
+            if !snake.is_empty() && !snake.ends_with('_') {
+                snake.push('_');
+            }
+            prev_is_lower = false;
+        }
+    }
+    let snake = snake.trim_end_matches('_');
+    if snake.is_empty() {
+        "field".to_string()
+    } else {
+        snake.to_string()
+    }
+}
+
+#[allow(warnings)]
+mod generated {
+    type Date = String;
+    type URI = String;
+    type DateTime = String;
+    type GitTimestamp = String;
+    include!(concat!(env!("OUT_DIR"), "/list_fields.rs"));
+    include!(concat!(env!("OUT_DIR"), "/list_views.rs"));
+    include!(concat!(env!("OUT_DIR"), "/list_items.rs"));
+    include!(concat!(env!("OUT_DIR"), "/list_item_bodies.rs"));
+    include!(concat!(env!("OUT_DIR"), "/list_item_comments.rs"));
+    include!(concat!(env!("OUT_DIR"), "/list_item_timelines.rs"));
+    include!(concat!(env!("OUT_DIR"), "/list_reactions.rs"));
+    include!(concat!(env!("OUT_DIR"), "/list_content_reactions.rs"));
+    include!(concat!(env!("OUT_DIR"), "/list_item_events.rs"));
+    include!(concat!(env!("OUT_DIR"), "/list_content_events.rs"));
+    include!(concat!(env!("OUT_DIR"), "/list_linked_repositories.rs"));
+    include!(concat!(env!("OUT_DIR"), "/list_issues.rs"));
+    include!(concat!(env!("OUT_DIR"), "/list_pull_requests.rs"));
+    include!(concat!(env!("OUT_DIR"), "/list_assignable_users.rs"));
+    include!(concat!(env!("OUT_DIR"), "/list_labels.rs"));
+    include!(concat!(env!("OUT_DIR"), "/list_content_labels.rs"));
+    include!(concat!(env!("OUT_DIR"), "/list_content_assignees.rs"));
+    include!(concat!(env!("OUT_DIR"), "/update_item_field.rs"));
+    include!(concat!(env!("OUT_DIR"), "/delete_item.rs"));
+    include!(concat!(env!("OUT_DIR"), "/items_updated_at.rs"));
+    include!(concat!(env!("OUT_DIR"), "/content_id.rs"));
+    include!(concat!(env!("OUT_DIR"), "/add_item.rs"));
+    include!(concat!(env!("OUT_DIR"), "/add_draft_item.rs"));
+    include!(concat!(env!("OUT_DIR"), "/update_field.rs"));
+    include!(concat!(env!("OUT_DIR"), "/update_field_options.rs"));
+    include!(concat!(env!("OUT_DIR"), "/delete_field.rs"));
+    include!(concat!(env!("OUT_DIR"), "/owner_id.rs"));
+    include!(concat!(env!("OUT_DIR"), "/create_project.rs"));
+    include!(concat!(env!("OUT_DIR"), "/create_field.rs"));
+    include!(concat!(env!("OUT_DIR"), "/update_project.rs"));
+    include!(concat!(env!("OUT_DIR"), "/repository_id.rs"));
+    include!(concat!(env!("OUT_DIR"), "/convert_draft_item.rs"));
+    include!(concat!(env!("OUT_DIR"), "/close_issue.rs"));
+    include!(concat!(env!("OUT_DIR"), "/reopen_issue.rs"));
+    include!(concat!(env!("OUT_DIR"), "/close_pull_request.rs"));
+    include!(concat!(env!("OUT_DIR"), "/reopen_pull_request.rs"));
+}
+
+/// a timeline event of `listItemEvents` or `listContentEvents` as the event, the actor and
+/// the time of a row of `item_events`, named like the `event` of the REST API (`labeled`,
+/// `closed`, `committed`, ...)
+trait TimelineEvent {
+    fn into_event(self) -> Option<(&'static str, Value, Value)>;
+}
+
+macro_rules! impl_timeline_events {
+    ($($m:ident),*) => {
+        $(
+            impl TimelineEvent for generated::$m::IssueEvent {
+                fn into_event(self) -> Option<(&'static str, Value, Value)> {
+                    use generated::$m::IssueEvent::*;
+                    let (name, login, created_at) = match self {
+                        LabeledEvent(e) => ("labeled", e.actor.map(|a| a.login), e.created_at),
+                        UnlabeledEvent(e) => ("unlabeled", e.actor.map(|a| a.login), e.created_at),
+                        AssignedEvent(e) => ("assigned", e.actor.map(|a| a.login), e.created_at),
+                        UnassignedEvent(e) => ("unassigned", e.actor.map(|a| a.login), e.created_at),
+                        ClosedEvent(e) => ("closed", e.actor.map(|a| a.login), e.created_at),
+                        ReopenedEvent(e) => ("reopened", e.actor.map(|a| a.login), e.created_at),
+                        CrossReferencedEvent(e) => ("cross-referenced", e.actor.map(|a| a.login), e.created_at),
+                        MilestonedEvent(e) => ("milestoned", e.actor.map(|a| a.login), e.created_at),
+                        DemilestonedEvent(e) => ("demilestoned", e.actor.map(|a| a.login), e.created_at),
+                        RenamedTitleEvent(e) => ("renamed", e.actor.map(|a| a.login), e.created_at),
+                        ReferencedEvent(e) => ("referenced", e.actor.map(|a| a.login), e.created_at),
+                        _ => return None,
+                    };
+                    let actor = login.map_or(Value::Null, Value::Str);
+                    Some((name, actor, timestamp(Some(&created_at))))
+                }
+            }
+
+            impl TimelineEvent for generated::$m::PullRequestEvent {
+                fn into_event(self) -> Option<(&'static str, Value, Value)> {
+                    use generated::$m::PullRequestEvent::*;
+                    let (name, login, created_at) = match self {
+                        LabeledEvent(e) => ("labeled", e.actor.map(|a| a.login), e.created_at),
+                        UnlabeledEvent(e) => ("unlabeled", e.actor.map(|a| a.login), e.created_at),
+                        AssignedEvent(e) => ("assigned", e.actor.map(|a| a.login), e.created_at),
+                        UnassignedEvent(e) => ("unassigned", e.actor.map(|a| a.login), e.created_at),
+                        ClosedEvent(e) => ("closed", e.actor.map(|a| a.login), e.created_at),
+                        ReopenedEvent(e) => ("reopened", e.actor.map(|a| a.login), e.created_at),
+                        CrossReferencedEvent(e) => ("cross-referenced", e.actor.map(|a| a.login), e.created_at),
+                        MilestonedEvent(e) => ("milestoned", e.actor.map(|a| a.login), e.created_at),
+                        DemilestonedEvent(e) => ("demilestoned", e.actor.map(|a| a.login), e.created_at),
+                        RenamedTitleEvent(e) => ("renamed", e.actor.map(|a| a.login), e.created_at),
+                        ReferencedEvent(e) => ("referenced", e.actor.map(|a| a.login), e.created_at),
+                        ReviewRequestedEvent(e) => ("review_requested", e.actor.map(|a| a.login), e.created_at),
+                        MergedEvent(e) => ("merged", e.actor.map(|a| a.login), e.created_at),
+                        ReadyForReviewEvent(e) => ("ready_for_review", e.actor.map(|a| a.login), e.created_at),
+                        ConvertToDraftEvent(e) => ("convert_to_draft", e.actor.map(|a| a.login), e.created_at),
+                        PullRequestReview(e) => ("reviewed", e.author.map(|a| a.login), e.created_at),
+                        PullRequestCommit(e) => (
+                            "committed",
+                            e.commit.author.and_then(|a| a.user).map(|u| u.login),
+                            e.commit.committed_date,
+                        ),
+                        _ => return None,
+                    };
+                    let actor = login.map_or(Value::Null, Value::Str);
+                    Some((name, actor, timestamp(Some(&created_at))))
+                }
+            }
+        )*
+    };
+}
+
+impl_timeline_events!(list_item_events, list_content_events);
+
+/// a project made by [`create_project`]
+pub struct NewProject {
+    pub number: i64,
+    pub url: String,
+}
+
+/// create an empty project titled `title` for the user or organization `owner`
+pub fn create_project(owner: &str, title: String) -> Result<NewProject> {
+    let owner_id = {
+        use generated::owner_id::*;
+        let query = include_str!("./owner_id.graphql");
+        let variables = Variables {
+            login: owner.to_string(),
+        };
+        let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+        resp.data
+            .organization
+            .map(|org| org.id)
+            .or_else(|| resp.data.user.map(|user| user.id))
+            .ok_or_else(|| {
+                anyhow::anyhow!("{}", resp.errors.error_msgs())
+                    .context(format!("failed to find {}", owner))
+            })?
+    };
+    use generated::create_project::*;
+    let query = include_str!("./create_project.graphql");
+    let variables = Variables { owner_id, title };
+    let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+    resp.data
+        .create_project_v2
+        .and_then(|payload| payload.project_v2)
+        .map(|project| NewProject {
+            number: project.number,
+            url: project.url,
+        })
+        .ok_or_else(|| anyhow::anyhow!("Error: {}", resp.errors.error_msgs()))
+}
+
+impl ProjectNextStorage {
+    pub fn new(owner: String, project_number: i64, opt: Opt) -> Result<Self> {
+        let scratch = mount_csvs(&opt.csv_mounts)?;
+        Ok(Self {
+            owner,
+            project_number,
+            opt,
+            cache: Arc::new(Mutex::new(None)),
+            scratch,
+            transaction: None,
+        })
+    }
+
+    #[allow(non_local_definitions)]
+    fn list_fields(&self) -> Result<(Project, Vec<Field>)> {
+        use generated::list_fields::*;
+        type SingleSelectFieldOption =
+            ProjectV2ProjectV2FieldsNodesOnProjectV2SingleSelectFieldOptions;
+        impl From<SingleSelectFieldOption> for FieldOption {
+            fn from(
+                SingleSelectFieldOption {
+                    id,
+                    name,
+                    color,
+                    description,
+                }: SingleSelectFieldOption,
+            ) -> Self {
+                let color = match color {
+                    ProjectV2SingleSelectFieldOptionColor::BLUE => "BLUE".to_string(),
+                    ProjectV2SingleSelectFieldOptionColor::GRAY => "GRAY".to_string(),
+                    ProjectV2SingleSelectFieldOptionColor::GREEN => "GREEN".to_string(),
+                    ProjectV2SingleSelectFieldOptionColor::ORANGE => "ORANGE".to_string(),
+                    ProjectV2SingleSelectFieldOptionColor::PINK => "PINK".to_string(),
+                    ProjectV2SingleSelectFieldOptionColor::PURPLE => "PURPLE".to_string(),
+                    ProjectV2SingleSelectFieldOptionColor::RED => "RED".to_string(),
+                    ProjectV2SingleSelectFieldOptionColor::YELLOW => "YELLOW".to_string(),
+                    ProjectV2SingleSelectFieldOptionColor::Other(s) => s,
+                };
+                Self {
+                    id,
+                    name,
+                    color,
+                    description,
+                }
+            }
+        }
+        type CompletedIteration =
+            ProjectV2ProjectV2FieldsNodesOnProjectV2IterationFieldConfigurationCompletedIterations;
+        impl From<CompletedIteration> for FieldIteration {
+            fn from(
+                CompletedIteration {
+                    id,
+                    title,
+                    duration,
+                    start_date,
+                    ..
+                }: CompletedIteration,
+            ) -> Self {
+                Self {
+                    id,
+                    title,
+                    duration,
+                    start_date,
+                }
             }
         }
         type Iteration =
@@ -326,12 +1384,12 @@ impl ProjectNextStorage {
                 }
             }
         }
-        let query = include_str!("list_fields.graphql");
+        let query = with_common_fields(include_str!("list_fields.graphql"));
         let variables = Variables {
             owner: self.owner.clone(),
             project_number: self.project_number,
         };
-        let resp: gh::GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+        let resp: gh::GraphQLResponse<ResponseData> = gh::graphql(&query, &variables)?;
         let project_next = resp
             .data
             .organization
@@ -344,7 +1402,14 @@ impl ProjectNextStorage {
             return Err(anyhow::anyhow!("{}", resp.errors.error_msgs())
                 .context("failed to fetch ProjectV2"));
         };
-        let project_id = project_next.id;
+        let project = Project {
+            id: project_next.id,
+            title: project_next.title,
+            short_description: project_next.short_description,
+            readme: project_next.readme,
+            public: project_next.public,
+            closed: project_next.closed,
+        };
         let field_nodes = project_next.fields.nodes;
         let reserved_names = [
             "Title",
@@ -366,7 +1431,7 @@ impl ProjectNextStorage {
                         if reserved_names.iter().any(|&rname| rname == name) {
                             return None;
                         } else {
-                            return Some(Field { id, name, kind: FieldKind::Normal(data_type.into()) });
+                            return Some(Field { id, column: name.clone(), name, kind: FieldKind::Normal(data_type.into()) });
                         }
                     },
                     ProjectV2IterationField(ProjectV2ProjectV2FieldsNodesOnProjectV2IterationField {
@@ -380,7 +1445,7 @@ impl ProjectNextStorage {
                         },
                         ..
                     }) => {
-                        Field { id, name, kind:
+                        Field { id, column: name.clone(), name, kind:
                             FieldKind::Iteration {
                                 duration,
                                 start_day,
@@ -399,42 +1464,103 @@ impl ProjectNextStorage {
                         ..
                     }) => {
                         let options = options.into_iter().map(Into::into).collect();
-                        Field { id, name, kind: FieldKind::SingleSelect(options) }
+                        Field { id, column: name.clone(), name, kind: FieldKind::SingleSelect(options) }
                     }
                 };
                 Some(field)
             })
             .collect();
-        Ok((project_id, fields))
+        Ok((project, fields))
     }
 
-    fn scan_items(&self, project_id: String, fields: &[Field]) -> Result<Vec<(String, Row)>> {
+    #[allow(non_local_definitions)]
+    /// rows of the items and their `updatedAt`
+    #[allow(clippy::type_complexity)]
+    fn scan_items(
+        &self,
+        project_id: String,
+        fields: &[Field],
+    ) -> Result<(Vec<(String, Vec<Value>)>, HashMap<String, String>)> {
         use generated::list_items::*;
-        trait IntoQuadRow {
-            /// repo, issue number, assignees, labels
-            fn into_row(self) -> (Value, Value, Value, Value);
+        /// the reserved columns which come from the content, but the title
+        struct ContentRow {
+            repo: Value,
+            issue: Value,
+            state: Value,
+            assignees: Value,
+            labels: Value,
+            comments: Value,
+            reactions: Value,
+            milestone_due_on: Value,
+            milestone_state: Value,
+            closed_at: Value,
+            merged_at: Value,
+        }
+        impl ContentRow {
+            fn empty() -> Self {
+                ContentRow {
+                    repo: Value::Null,
+                    issue: Value::Null,
+                    state: Value::Null,
+                    assignees: Value::Null,
+                    labels: Value::Null,
+                    comments: Value::Null,
+                    reactions: Value::Null,
+                    milestone_due_on: Value::Null,
+                    milestone_state: Value::Null,
+                    closed_at: Value::Null,
+                    merged_at: Value::Null,
+                }
+            }
+        }
+        trait IntoContentRow {
+            fn into_row(self) -> ContentRow;
         }
-        impl IntoQuadRow for ListItemsNodeOnProjectV2ItemsNodesContent {
-            fn into_row(self) -> (Value, Value, Value, Value) {
+        impl IntoContentRow for ListItemsNodeOnProjectV2ItemsNodesContent {
+            fn into_row(self) -> ContentRow {
                 match self {
                     ListItemsNodeOnProjectV2ItemsNodesContent::Issue(issue) => issue.into_row(),
-                    ListItemsNodeOnProjectV2ItemsNodesContent::PullRequest(pr) => pr.into_row(),
+                    ListItemsNodeOnProjectV2ItemsNodesContent::PullRequest(pr) => ContentRow {
+                        merged_at: timestamp(pr.merged_at.as_deref()),
+                        ..pr.into_row()
+                    },
                     ListItemsNodeOnProjectV2ItemsNodesContent::DraftIssue(draft) => {
                         draft.into_row()
                     }
                 }
             }
         }
-        macro_rules! impl_into_quad_rows {
+        macro_rules! impl_into_content_rows {
             ($($t:tt),*) => {
-                $(impl_into_quad_row!($t));*
+                $(impl_into_content_row!($t));*
             };
         }
-        macro_rules! impl_into_quad_row {
+        macro_rules! impl_into_content_row {
             ($t:ident) => {
-                impl IntoQuadRow for $t {
-                    fn into_row(self) -> (Value, Value, Value, Value) {
+                impl IntoContentRow for $t {
+                    fn into_row(self) -> ContentRow {
                         let repo = self.repository.name_with_owner;
+                        let state = match serde_json::to_value(&self.state) {
+                            Ok(serde_json::Value::String(state)) => Value::Str(state),
+                            _ => Value::Null,
+                        };
+                        let (milestone_due_on, milestone_state) = match self.milestone {
+                            Some(milestone) => (
+                                milestone
+                                    .due_on
+                                    .as_deref()
+                                    .and_then(|due_on| due_on.get(..10))
+                                    .and_then(|date| {
+                                        NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+                                    })
+                                    .map_or(Value::Null, Value::Date),
+                                match serde_json::to_value(&milestone.state) {
+                                    Ok(serde_json::Value::String(state)) => Value::Str(state),
+                                    _ => Value::Null,
+                                },
+                            ),
+                            None => (Value::Null, Value::Null),
+                        };
                         let assignees = self
                             .assignees
                             .nodes
@@ -451,22 +1577,30 @@ impl ProjectNextStorage {
                             .flatten()
                             .map(|l| Value::Str(l.name))
                             .collect();
-                        (
-                            Value::Str(repo),
-                            Value::I64(self.number as i64),
-                            Value::List(assignees),
-                            Value::List(labels),
-                        )
+                        ContentRow {
+                            repo: Value::Str(repo),
+                            issue: Value::I64(self.number as i64),
+                            state,
+                            assignees: Value::List(assignees),
+                            labels: Value::List(labels),
+                            comments: Value::I64(self.comments.total_count),
+                            reactions: Value::I64(self.reactions.total_count),
+                            milestone_due_on,
+                            milestone_state,
+                            closed_at: timestamp(self.closed_at.as_deref()),
+                            // only pull requests have it
+                            merged_at: Value::Null,
+                        }
                     }
                 }
             };
         }
-        impl_into_quad_rows! {
+        impl_into_content_rows! {
             ListItemsNodeOnProjectV2ItemsNodesContentOnIssue,
             ListItemsNodeOnProjectV2ItemsNodesContentOnPullRequest
         }
-        impl IntoQuadRow for ListItemsNodeOnProjectV2ItemsNodesContentOnDraftIssue {
-            fn into_row(self) -> (Value, Value, Value, Value) {
+        impl IntoContentRow for ListItemsNodeOnProjectV2ItemsNodesContentOnDraftIssue {
+            fn into_row(self) -> ContentRow {
                 let assignees = self
                     .assignees
                     .nodes
@@ -475,12 +1609,11 @@ impl ProjectNextStorage {
                     .flatten()
                     .map(|u| Value::Str(u.login))
                     .collect();
-                (
-                    Value::Null,
-                    Value::Null,
-                    Value::List(assignees),
-                    Value::List(vec![]),
-                )
+                ContentRow {
+                    assignees: Value::List(assignees),
+                    labels: Value::List(vec![]),
+                    ..ContentRow::empty()
+                }
             }
         }
 
@@ -492,6 +1625,41 @@ impl ProjectNextStorage {
                     ListItemsNodeOnProjectV2ItemsNodesContent::PullRequest(p) => &p.title,
                 }
             }
+            /// the id and the cursors after the first page of the labels and of the assignees,
+            /// `None` for a list which has no more
+            fn next_pages(&self) -> (&str, Option<&str>, Option<&str>) {
+                fn next(has_next_page: bool, end_cursor: &Option<String>) -> Option<&str> {
+                    end_cursor.as_deref().filter(|_| has_next_page)
+                }
+                match self {
+                    ListItemsNodeOnProjectV2ItemsNodesContent::DraftIssue(d) => {
+                        let assignees = &d.assignees.page_info;
+                        (
+                            &d.id,
+                            None,
+                            next(assignees.has_next_page, &assignees.end_cursor),
+                        )
+                    }
+                    ListItemsNodeOnProjectV2ItemsNodesContent::Issue(i) => {
+                        let labels = i.labels.as_ref().map(|l| &l.page_info);
+                        let assignees = &i.assignees.page_info;
+                        (
+                            &i.id,
+                            labels.and_then(|l| next(l.has_next_page, &l.end_cursor)),
+                            next(assignees.has_next_page, &assignees.end_cursor),
+                        )
+                    }
+                    ListItemsNodeOnProjectV2ItemsNodesContent::PullRequest(p) => {
+                        let labels = p.labels.as_ref().map(|l| &l.page_info);
+                        let assignees = &p.assignees.page_info;
+                        (
+                            &p.id,
+                            labels.and_then(|l| next(l.has_next_page, &l.end_cursor)),
+                            next(assignees.has_next_page, &assignees.end_cursor),
+                        )
+                    }
+                }
+            }
         }
         impl ListItemsNodeOnProjectV2ItemsNodesFieldValuesNodes {
             fn field(&self) -> &FieldFragment {
@@ -511,7 +1679,7 @@ impl ProjectNextStorage {
             }
             fn as_sql_value(&self) -> Option<Value> {
                 match self {
-                    ListItemsNodeOnProjectV2ItemsNodesFieldValuesNodes::ProjectV2ItemFieldDateValue(f) => f.date.as_ref().map(|s| Value::Str(s.to_owned())),
+                    ListItemsNodeOnProjectV2ItemsNodesFieldValuesNodes::ProjectV2ItemFieldDateValue(f) => f.date.as_deref().and_then(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()).map(Value::Date),
                     ListItemsNodeOnProjectV2ItemsNodesFieldValuesNodes::ProjectV2ItemFieldIterationValue(..) => unreachable!(),
                     ListItemsNodeOnProjectV2ItemsNodesFieldValuesNodes::ProjectV2ItemFieldLabelValue(f) => {
                         let l = f.labels.as_ref()?;
@@ -552,6 +1720,36 @@ impl ProjectNextStorage {
                     }
                 }
             }
+            /// the value of a field of a kind unknown to gh-sql as text, a list as a JSON array
+            fn as_raw_text(&self) -> Value {
+                let value = match self {
+                    Self::ProjectV2ItemFieldSingleSelectValue(f) => f.name.clone().map(Value::Str),
+                    Self::ProjectV2ItemFieldIterationValue(f) => Some(Value::Str(f.title.clone())),
+                    _ => self.as_sql_value(),
+                };
+                match value {
+                    Some(Value::Str(s)) => Value::Str(s),
+                    Some(value) => Value::Str(output::into_json_value(value).to_string()),
+                    None => Value::Null,
+                }
+            }
+            /// the complete list of a Labels or Assignees value which has more than its first
+            /// page, the same as the labels or assignees of the content
+            fn complete_list(&self, content: &ContentRow) -> Option<Value> {
+                match self {
+                    Self::ProjectV2ItemFieldLabelValue(f)
+                        if f.labels.as_ref().is_some_and(|l| l.page_info.has_next_page) =>
+                    {
+                        Some(content.labels.clone())
+                    }
+                    Self::ProjectV2ItemFieldUserValue(f)
+                        if f.users.as_ref().is_some_and(|u| u.page_info.has_next_page) =>
+                    {
+                        Some(content.assignees.clone())
+                    }
+                    _ => None,
+                }
+            }
             fn as_single_select(&self) -> Option<&ListItemsNodeOnProjectV2ItemsNodesFieldValuesNodesOnProjectV2ItemFieldSingleSelectValue>{
                 if let Self::ProjectV2ItemFieldSingleSelectValue(v) = self {
                     Some(v)
@@ -576,6 +1774,30 @@ impl ProjectNextStorage {
                 }
             }
         }
+        impl ListItemsNodeOnProjectV2ItemsNodesFieldValuesNodes {
+            /// the logins of the users and the names of the teams of a Reviewers value
+            fn reviewers(&self) -> Option<(Value, Value)> {
+                use ListItemsNodeOnProjectV2ItemsNodesFieldValuesNodesOnProjectV2ItemFieldReviewerValueReviewersNodes as Reviewer;
+                let Self::ProjectV2ItemFieldReviewerValue(f) = self else {
+                    return None;
+                };
+                let (mut users, mut teams) = (vec![], vec![]);
+                for reviewer in f
+                    .reviewers
+                    .iter()
+                    .flat_map(|l| &l.nodes)
+                    .flatten()
+                    .flatten()
+                {
+                    match reviewer {
+                        Reviewer::User(u) => users.push(Value::Str(u.login.to_owned())),
+                        Reviewer::Team(t) => teams.push(Value::Str(t.name.to_owned())),
+                        _ => {}
+                    }
+                }
+                Some((Value::List(users), Value::List(teams)))
+            }
+        }
         impl ListItemsNodeOnProjectV2ItemsNodesFieldValuesNodesOnProjectV2ItemFieldReviewerValueReviewersNodes {
             fn name(&self) -> Option<&str> {
                 match self {
@@ -586,17 +1808,45 @@ impl ProjectNextStorage {
             }
         }
 
-        let query = include_str!("list_items.graphql");
+        let query = &with_common_fields(include_str!("list_items.graphql"));
         let mut items = vec![];
         let mut after = None;
+        let checkpoint = self
+            .opt
+            .checkpoints
+            .as_deref()
+            .map(|dir| Checkpoint::new(dir, &gh::host(), &project_id));
+        match checkpoint.as_ref().map(Checkpoint::load).transpose() {
+            Ok(Some(Some((nodes, cursor)))) => {
+                eprintln!("Resuming the scan of items after {} item(s)", nodes.len());
+                items = nodes;
+                after = Some(cursor);
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("Failed to read the checkpoint of the scan: {:#}", e),
+        }
         while {
             let variables = Variables {
                 project_id: project_id.clone(),
                 after: after.clone(),
             };
             let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
-            let Some(ListItemsNode::ProjectV2(ListItemsNodeOnProjectV2 { items: ListItemsNodeOnProjectV2Items { page_info, nodes } })) = resp.data.node else { unreachable!("the id can only be for projectV2") };
-            items.extend(nodes.into_iter().flatten().flatten());
+            let Some(ListItemsNode::ProjectV2(ListItemsNodeOnProjectV2 {
+                items: ListItemsNodeOnProjectV2Items { page_info, nodes },
+            })) = resp.data.node
+            else {
+                unreachable!("the id can only be for projectV2")
+            };
+            let nodes: Vec<_> = nodes.into_iter().flatten().flatten().collect();
+            // only scans of several pages are worth resuming
+            if let (Some(checkpoint), Some(end_cursor), true) =
+                (&checkpoint, &page_info.end_cursor, page_info.has_next_page)
+            {
+                if let Err(e) = checkpoint.save(end_cursor, &nodes) {
+                    eprintln!("Failed to save the checkpoint of the scan: {:#}", e);
+                }
+            }
+            items.extend(nodes);
             if let Some(end_cursor) = page_info.end_cursor {
                 after = Some(end_cursor);
                 page_info.has_next_page
@@ -604,6 +1854,33 @@ impl ProjectNextStorage {
                 false
             }
         } {}
+        if let Some(Err(e)) = checkpoint.as_ref().map(Checkpoint::clear) {
+            eprintln!("Failed to remove the checkpoint of the scan: {:#}", e);
+        }
+        // the labels and assignees after the first page of them, by item id
+        let mut next_pages = HashMap::new();
+        if !self.opt.truncate_lists {
+            for item in &items {
+                let Some(content) = &item.content else {
+                    continue;
+                };
+                let (content_id, labels, assignees) = content.next_pages();
+                if labels.is_none() && assignees.is_none() {
+                    continue;
+                }
+                let labels = labels
+                    .map(|after| self.list_content_labels(content_id, after))
+                    .transpose()?;
+                let assignees = assignees
+                    .map(|after| self.list_content_assignees(content_id, after))
+                    .transpose()?;
+                next_pages.insert(item.id.clone(), (labels, assignees));
+            }
+        }
+        let updated_at = items
+            .iter()
+            .map(|item| (item.id.clone(), item.updated_at.clone()))
+            .collect();
         let rows: Vec<_> = items
             .into_iter()
             .map(|item| {
@@ -614,148 +1891,1040 @@ impl ProjectNextStorage {
                     .map(ListItemsNodeOnProjectV2ItemsNodesContent::title)
                     .unwrap_or_default()
                     .to_string();
-                let (repo, issue, assignees, labels) = match item.content {
+                let mut content = match item.content {
                     Some(content) => content.into_row(),
-                    None => (Value::Null, Value::Null, Value::Null, Value::Null),
+                    None => ContentRow::empty(),
                 };
+                if let Some((labels, logins)) = next_pages.remove(&key) {
+                    if let (Value::List(list), Some(labels)) = (&mut content.labels, labels) {
+                        list.extend(labels);
+                    }
+                    if let (Value::List(list), Some(logins)) = (&mut content.assignees, logins) {
+                        list.extend(logins);
+                    }
+                }
+                let (reviewer_users, reviewer_teams) = item
+                    .field_values
+                    .nodes
+                    .iter()
+                    .flatten()
+                    .flatten()
+                    .find_map(ListItemsNodeOnProjectV2ItemsNodesFieldValuesNodes::reviewers)
+                    .unwrap_or((Value::Null, Value::Null));
+                let field_columns: Vec<_> = fields
+                    .iter()
+                    .map(|field| {
+                        let value = item
+                            .field_values
+                            .nodes
+                            .iter()
+                            .flatten()
+                            .flatten()
+                            .find(|value| value.field().id() == field.id);
+                        match value {
+                            Some(value) => match &field.kind {
+                                FieldKind::Normal(FieldType::Other(_)) => value.as_raw_text(),
+                                FieldKind::Normal(..) => match value
+                                    .complete_list(&content)
+                                    .or_else(|| value.as_sql_value())
+                                {
+                                    Some(v) => v,
+                                    None => Value::Null,
+                                },
+                                FieldKind::SingleSelect(_) => {
+                                    if let Some(opt) =
+                                        value.as_single_select().unwrap().name.as_ref()
+                                    {
+                                        Value::Str(opt.to_owned())
+                                    } else {
+                                        Value::Null
+                                    }
+                                }
+                                FieldKind::Iteration {
+                                    iterations,
+                                    completed_iterations,
+                                    ..
+                                } => {
+                                    let value = value.as_iteration().unwrap();
+                                    let title = &value.title;
+                                    if let Some(iter) = iterations
+                                        .iter()
+                                        .chain(completed_iterations.iter())
+                                        .find(|iter| &iter.title == title)
+                                    {
+                                        Value::Str(iter.title.clone())
+                                    } else {
+                                        Value::Str("Unknown".to_string())
+                                    }
+                                }
+                            },
+                            None => Value::Null,
+                        }
+                    })
+                    .collect();
                 let reserved_columns = [
                     Value::Str(key.clone()),
-                    repo,
-                    issue,
+                    content.repo,
+                    content.issue,
                     Value::Str(title),
-                    assignees,
-                    labels,
+                    content.assignees,
+                    content.labels,
+                    content.state,
+                    Value::Null,
+                    content.comments,
+                    content.reactions,
+                    content.milestone_due_on,
+                    content.milestone_state,
+                    reviewer_users,
+                    reviewer_teams,
+                    content.closed_at,
+                    content.merged_at,
+                    Value::Null,
+                    Value::Null,
                 ];
-                let field_columns = fields.iter().map(|field| {
-                    let value = item
-                        .field_values
-                        .nodes
-                        .iter()
-                        .flatten()
-                        .flatten()
-                        .find(|value| value.field().id() == field.id);
-                    match value {
-                        Some(value) => match &field.kind {
-                            FieldKind::Normal(..) => match value.as_sql_value() {
-                                Some(v) => v,
-                                None => Value::Null,
-                            },
-                            FieldKind::SingleSelect(_) => {
-                                if let Some(opt) = value.as_single_select().unwrap().name.as_ref() {
-                                    Value::Str(opt.to_owned())
-                                } else {
-                                    Value::Null
-                                }
-                            }
-                            FieldKind::Iteration {
-                                iterations,
-                                completed_iterations,
-                                ..
-                            } => {
-                                let value = value.as_iteration().unwrap();
-                                let title = &value.title;
-                                if let Some(iter) = iterations
-                                    .iter()
-                                    .chain(completed_iterations.iter())
-                                    .find(|iter| &iter.title == title)
-                                {
-                                    Value::Str(iter.title.clone())
-                                } else {
-                                    Value::Str("Unknown".to_string())
-                                }
-                            }
-                        },
-                        None => Value::Null,
-                    }
-                });
-                let row = Row(reserved_columns.into_iter().chain(field_columns).collect());
+                let row = reserved_columns.into_iter().chain(field_columns).collect();
                 (key, row)
             })
             .collect();
-        Ok(rows)
+        Ok((rows, updated_at))
+    }
+
+    fn project_schema() -> Schema {
+        table_schema(
+            "project",
+            vec![
+                column_def("id", DataType::Text, false),
+                column_def("title", DataType::Text, false),
+                column_def("short_description", DataType::Text, true),
+                column_def("readme", DataType::Text, true),
+                column_def("public", DataType::Boolean, false),
+                column_def("closed", DataType::Boolean, false),
+            ],
+        )
+    }
+
+    fn reactions_schema() -> Schema {
+        table_schema(
+            "reactions",
+            vec![
+                column_def("item_id", DataType::Text, false),
+                column_def("content", DataType::Text, false),
+                // NULL for deleted users
+                column_def("user", DataType::Text, true),
+            ],
+        )
+    }
+
+    fn item_events_schema() -> Schema {
+        table_schema(
+            "item_events",
+            vec![
+                column_def("item_id", DataType::Text, false),
+                column_def("event", DataType::Text, false),
+                column_def("actor", DataType::Text, true),
+                column_def("created_at", DataType::Timestamp, true),
+            ],
+        )
+    }
+
+    fn issues_schema() -> Schema {
+        table_schema(
+            "issues",
+            vec![
+                column_def("repo", DataType::Text, false),
+                column_def("number", DataType::Int, false),
+                column_def("title", DataType::Text, false),
+                column_def("state", DataType::Text, false),
+                // NULL for deleted users
+                column_def("author", DataType::Text, true),
+                column_def("labels", DataType::List, false),
+                column_def("assignees", DataType::List, false),
+                column_def("created_at", DataType::Timestamp, false),
+                column_def("updated_at", DataType::Timestamp, false),
+                column_def("closed_at", DataType::Timestamp, true),
+            ],
+        )
+    }
+
+    fn pull_requests_schema() -> Schema {
+        table_schema(
+            "pull_requests",
+            vec![
+                column_def("repo", DataType::Text, false),
+                column_def("number", DataType::Int, false),
+                column_def("title", DataType::Text, false),
+                column_def("state", DataType::Text, false),
+                // NULL for deleted users
+                column_def("author", DataType::Text, true),
+                // NULL unless the repository requires reviews
+                column_def("review_decision", DataType::Text, true),
+                column_def("updated_at", DataType::Timestamp, false),
+            ],
+        )
+    }
+
+    fn assignable_users_schema() -> Schema {
+        table_schema(
+            "assignable_users",
+            vec![
+                column_def("repo", DataType::Text, false),
+                column_def("login", DataType::Text, false),
+                // NULL unless the user set a display name
+                column_def("name", DataType::Text, true),
+            ],
+        )
+    }
+
+    fn labels_schema() -> Schema {
+        table_schema(
+            "labels",
+            vec![
+                column_def("repo", DataType::Text, false),
+                column_def("name", DataType::Text, false),
+                // hex without `#`, e.g. `d73a4a`
+                column_def("color", DataType::Text, false),
+                column_def("description", DataType::Text, true),
+            ],
+        )
     }
 
     fn iterations_schema() -> Schema {
-        let column_defs = vec![
-            ColumnDef {
-                name: "field_id".to_string(),
-                data_type: DataType::Text,
-                options: vec![],
-            },
-            ColumnDef {
-                name: "id".to_string(),
-                data_type: DataType::Text,
-                options: vec![],
-            },
-            ColumnDef {
-                name: "title".to_string(),
-                data_type: DataType::Text,
-                options: vec![],
-            },
-            ColumnDef {
-                name: "start_date".to_string(),
-                data_type: DataType::Text,
-                options: vec![],
-            },
-            ColumnDef {
-                name: "duration".to_string(),
-                data_type: DataType::Int,
-                options: vec![],
-            },
-            ColumnDef {
-                name: "is_completed".to_string(),
-                data_type: DataType::Boolean,
-                options: vec![],
-            },
-        ];
-        Schema {
-            table_name: "iterations".to_string(),
-            column_defs,
-            indexes: vec![],
-        }
+        table_schema(
+            "iterations",
+            vec![
+                column_def("field_id", DataType::Text, false),
+                column_def("id", DataType::Text, false),
+                column_def("title", DataType::Text, false),
+                column_def("start_date", DataType::Date, true),
+                column_def("duration", DataType::Int, false),
+                column_def("is_completed", DataType::Boolean, false),
+                column_def("end_date", DataType::Date, true),
+                column_def("is_current", DataType::Boolean, false),
+                column_def("field_name", DataType::Text, false),
+            ],
+        )
     }
 
     fn options_schema() -> Schema {
-        let column_defs = vec![
-            ColumnDef {
-                name: "field_id".to_string(),
-                data_type: DataType::Text,
-                options: vec![],
-            },
-            ColumnDef {
-                name: "id".to_string(),
-                data_type: DataType::Text,
-                options: vec![],
-            },
-            ColumnDef {
-                name: "name".to_string(),
-                data_type: DataType::Text,
-                options: vec![],
-            },
-        ];
-        Schema {
-            table_name: "options".to_string(),
-            column_defs,
-            indexes: vec![],
-        }
+        table_schema(
+            "options",
+            vec![
+                column_def("field_id", DataType::Text, false),
+                column_def("id", DataType::Text, true),
+                column_def("name", DataType::Text, false),
+                column_def("color", DataType::Text, true),
+                column_def("description", DataType::Text, true),
+            ],
+        )
     }
 
-    fn fetch_data(&self) -> Result<Cache> {
-        let (project_id, fields) = self.list_fields()?;
-        let items = self.scan_items(project_id.clone(), &fields)?;
-        Ok(Cache {
-            project_id,
-            fields,
-            items,
-        })
+    /// reactions to the issues and pull requests of the project, the first 100 of an item
+    /// with the item and the rest by [`Self::list_content_reactions`]
+    fn list_reactions(&self, project_id: String) -> Result<Vec<(String, Vec<Value>)>> {
+        use generated::list_reactions::*;
+        let query = include_str!("list_reactions.graphql");
+        let mut rows = vec![];
+        let mut after = None;
+        loop {
+            let variables = Variables {
+                project_id: project_id.clone(),
+                after: after.clone(),
+            };
+            let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+            let Some(ListReactionsNode::ProjectV2(ListReactionsNodeOnProjectV2 {
+                items: ListReactionsNodeOnProjectV2Items { page_info, nodes },
+            })) = resp.data.node
+            else {
+                return Err(anyhow::anyhow!("Error: {}", resp.errors.error_msgs()));
+            };
+            for item in nodes.into_iter().flatten().flatten() {
+                let (content_id, has_next_page, end_cursor, nodes) = match item.content {
+                    Some(ListReactionsNodeOnProjectV2ItemsNodesContent::Issue(issue)) => (
+                        issue.id,
+                        issue.reactions.page_info.has_next_page,
+                        issue.reactions.page_info.end_cursor,
+                        issue.reactions.nodes,
+                    ),
+                    Some(ListReactionsNodeOnProjectV2ItemsNodesContent::PullRequest(pr)) => (
+                        pr.id,
+                        pr.reactions.page_info.has_next_page,
+                        pr.reactions.page_info.end_cursor,
+                        pr.reactions.nodes,
+                    ),
+                    _ => continue,
+                };
+                let mut rest = vec![];
+                if let Some(after) = end_cursor.filter(|_| has_next_page) {
+                    rest = self.list_content_reactions(&content_id, &after)?;
+                }
+                let first = nodes.into_iter().flatten().flatten();
+                let first = first.filter_map(|r| reaction(&r.content, r.user.map(|u| u.login)));
+                for (n, (content, user)) in first.chain(rest).enumerate() {
+                    let row = vec![Value::Str(item.id.clone()), Value::Str(content), user];
+                    rows.push((format!("{}/{}", item.id, n), row));
+                }
+            }
+            match page_info.end_cursor {
+                Some(end_cursor) if page_info.has_next_page => after = Some(end_cursor),
+                _ => return Ok(rows),
+            }
+        }
     }
 
-    fn update_item_field(
+    /// the reactions to the issue or pull request `content_id` after the cursor, as the
+    /// content and the user
+    fn list_content_reactions(
         &self,
-        project_id: String,
-        item_id: String,
-        field_id: String,
-        value: ProjectV2FieldValue,
+        content_id: &str,
+        after: &str,
+    ) -> Result<Vec<(String, Value)>> {
+        use generated::list_content_reactions::*;
+        let query = include_str!("list_content_reactions.graphql");
+        let mut reactions = vec![];
+        let mut after = Some(after.to_string());
+        while let Some(cursor) = after.take() {
+            let variables = Variables {
+                id: content_id.to_string(),
+                after: Some(cursor),
+            };
+            let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+            let (has_next_page, end_cursor, nodes) = match resp.data.node {
+                Some(ListContentReactionsNode::Issue(ListContentReactionsNodeOnIssue {
+                    reactions: page,
+                })) => (
+                    page.page_info.has_next_page,
+                    page.page_info.end_cursor,
+                    page.nodes,
+                ),
+                Some(ListContentReactionsNode::PullRequest(
+                    ListContentReactionsNodeOnPullRequest { reactions: page },
+                )) => (
+                    page.page_info.has_next_page,
+                    page.page_info.end_cursor,
+                    page.nodes,
+                ),
+                _ => return Err(anyhow::anyhow!("Error: {}", resp.errors.error_msgs())),
+            };
+            let nodes = nodes.into_iter().flatten().flatten();
+            reactions.extend(nodes.filter_map(|r| reaction(&r.content, r.user.map(|u| u.login))));
+            after = end_cursor.filter(|_| has_next_page);
+        }
+        Ok(reactions)
+    }
+
+    /// `nameWithOwner` of the repositories linked to the project, fetched once per session
+    fn linked_repositories(&self, cache: &mut Cache) -> Result<Vec<String>> {
+        use generated::list_linked_repositories::*;
+        if let Some(repositories) = &cache.repositories {
+            return Ok(repositories.clone());
+        }
+        let query = include_str!("list_linked_repositories.graphql");
+        let variables = Variables {
+            project_id: cache.project.id.clone(),
+        };
+        let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+        let Some(ListLinkedRepositoriesNode::ProjectV2(ListLinkedRepositoriesNodeOnProjectV2 {
+            repositories: ListLinkedRepositoriesNodeOnProjectV2Repositories { nodes },
+        })) = resp.data.node
+        else {
+            return Err(anyhow::anyhow!("Error: {}", resp.errors.error_msgs()));
+        };
+        let repositories: Vec<_> = nodes
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|repo| repo.name_with_owner)
+            .collect();
+        cache.repositories = Some(repositories.clone());
+        Ok(repositories)
+    }
+
+    /// all issues of the repositories, whether they are items or not, keyed by
+    /// `owner/name#number`
+    fn list_issues(&self, repositories: &[String]) -> Result<Vec<(String, Vec<Value>)>> {
+        use generated::list_issues::*;
+        let query = include_str!("list_issues.graphql");
+        let mut rows = vec![];
+        for repo in repositories {
+            let Some((owner, name)) = repo.split_once('/') else {
+                continue;
+            };
+            let mut after = None;
+            loop {
+                let variables = Variables {
+                    owner: owner.to_string(),
+                    name: name.to_string(),
+                    after: after.clone(),
+                };
+                let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+                let Some(ListIssuesRepository {
+                    issues: ListIssuesRepositoryIssues { page_info, nodes },
+                }) = resp.data.repository
+                else {
+                    return Err(anyhow::anyhow!("Error: {}", resp.errors.error_msgs()));
+                };
+                for issue in nodes.into_iter().flatten().flatten() {
+                    let state = match serde_json::to_value(&issue.state) {
+                        Ok(serde_json::Value::String(state)) => state,
+                        _ => continue,
+                    };
+                    let labels = issue
+                        .labels
+                        .into_iter()
+                        .flat_map(|labels| labels.nodes)
+                        .flatten()
+                        .flatten()
+                        .map(|label| Value::Str(label.name))
+                        .collect();
+                    let assignees = issue
+                        .assignees
+                        .nodes
+                        .into_iter()
+                        .flatten()
+                        .flatten()
+                        .map(|user| Value::Str(user.login))
+                        .collect();
+                    let row = vec![
+                        Value::Str(repo.clone()),
+                        Value::I64(issue.number),
+                        Value::Str(issue.title),
+                        Value::Str(state),
+                        issue
+                            .author
+                            .map_or(Value::Null, |author| Value::Str(author.login)),
+                        Value::List(labels),
+                        Value::List(assignees),
+                        timestamp(Some(&issue.created_at)),
+                        timestamp(Some(&issue.updated_at)),
+                        timestamp(issue.closed_at.as_deref()),
+                    ];
+                    rows.push((format!("{}#{}", repo, issue.number), row));
+                }
+                match page_info.end_cursor {
+                    Some(end_cursor) if page_info.has_next_page => after = Some(end_cursor),
+                    _ => break,
+                }
+            }
+        }
+        Ok(rows)
+    }
+
+    /// all pull requests of the repositories, whether they are items or not, keyed by
+    /// `owner/name#number`
+    fn list_pull_requests(&self, repositories: &[String]) -> Result<Vec<(String, Vec<Value>)>> {
+        use generated::list_pull_requests::*;
+        let query = include_str!("list_pull_requests.graphql");
+        let enum_value = |value: serde_json::Result<serde_json::Value>| match value {
+            Ok(serde_json::Value::String(value)) => Value::Str(value),
+            _ => Value::Null,
+        };
+        let mut rows = vec![];
+        for repo in repositories {
+            let Some((owner, name)) = repo.split_once('/') else {
+                continue;
+            };
+            let mut after = None;
+            loop {
+                let variables = Variables {
+                    owner: owner.to_string(),
+                    name: name.to_string(),
+                    after: after.clone(),
+                };
+                let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+                let Some(ListPullRequestsRepository {
+                    pull_requests: ListPullRequestsRepositoryPullRequests { page_info, nodes },
+                }) = resp.data.repository
+                else {
+                    return Err(anyhow::anyhow!("Error: {}", resp.errors.error_msgs()));
+                };
+                for pr in nodes.into_iter().flatten().flatten() {
+                    let review_decision = match &pr.review_decision {
+                        Some(decision) => enum_value(serde_json::to_value(decision)),
+                        None => Value::Null,
+                    };
+                    let row = vec![
+                        Value::Str(repo.clone()),
+                        Value::I64(pr.number),
+                        Value::Str(pr.title),
+                        enum_value(serde_json::to_value(&pr.state)),
+                        pr.author
+                            .map_or(Value::Null, |author| Value::Str(author.login)),
+                        review_decision,
+                        timestamp(Some(&pr.updated_at)),
+                    ];
+                    rows.push((format!("{}#{}", repo, pr.number), row));
+                }
+                match page_info.end_cursor {
+                    Some(end_cursor) if page_info.has_next_page => after = Some(end_cursor),
+                    _ => break,
+                }
+            }
+        }
+        Ok(rows)
+    }
+
+    /// the users who can be assigned to issues and pull requests of the repositories, keyed by
+    /// `owner/name/login`
+    fn list_assignable_users(&self, repositories: &[String]) -> Result<Vec<(String, Vec<Value>)>> {
+        use generated::list_assignable_users::*;
+        let query = include_str!("list_assignable_users.graphql");
+        let mut rows = vec![];
+        for repo in repositories {
+            let Some((owner, name)) = repo.split_once('/') else {
+                continue;
+            };
+            let mut after = None;
+            loop {
+                let variables = Variables {
+                    owner: owner.to_string(),
+                    name: name.to_string(),
+                    after: after.clone(),
+                };
+                let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+                let Some(ListAssignableUsersRepository {
+                    assignable_users:
+                        ListAssignableUsersRepositoryAssignableUsers { page_info, nodes },
+                }) = resp.data.repository
+                else {
+                    return Err(anyhow::anyhow!("Error: {}", resp.errors.error_msgs()));
+                };
+                for user in nodes.into_iter().flatten().flatten() {
+                    let row = vec![
+                        Value::Str(repo.clone()),
+                        Value::Str(user.login.clone()),
+                        user.name.map_or(Value::Null, Value::Str),
+                    ];
+                    rows.push((format!("{}/{}", repo, user.login), row));
+                }
+                match page_info.end_cursor {
+                    Some(end_cursor) if page_info.has_next_page => after = Some(end_cursor),
+                    _ => break,
+                }
+            }
+        }
+        Ok(rows)
+    }
+
+    /// the labels of the repositories, keyed by `owner/name/label`
+    fn list_labels(&self, repositories: &[String]) -> Result<Vec<(String, Vec<Value>)>> {
+        use generated::list_labels::*;
+        let query = include_str!("list_labels.graphql");
+        let mut rows = vec![];
+        for repo in repositories {
+            let Some((owner, name)) = repo.split_once('/') else {
+                continue;
+            };
+            let mut after = None;
+            loop {
+                let variables = Variables {
+                    owner: owner.to_string(),
+                    name: name.to_string(),
+                    after: after.clone(),
+                };
+                let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+                let Some(ListLabelsRepository {
+                    labels: Some(ListLabelsRepositoryLabels { page_info, nodes }),
+                }) = resp.data.repository
+                else {
+                    return Err(anyhow::anyhow!("Error: {}", resp.errors.error_msgs()));
+                };
+                for label in nodes.into_iter().flatten().flatten() {
+                    let row = vec![
+                        Value::Str(repo.clone()),
+                        Value::Str(label.name.clone()),
+                        Value::Str(label.color),
+                        label.description.map_or(Value::Null, Value::Str),
+                    ];
+                    rows.push((format!("{}/{}", repo, label.name), row));
+                }
+                match page_info.end_cursor {
+                    Some(end_cursor) if page_info.has_next_page => after = Some(end_cursor),
+                    _ => break,
+                }
+            }
+        }
+        Ok(rows)
+    }
+
+    /// the names of the labels of an issue or pull request after the cursor `after`, as
+    /// `listItems` only fetches the first page of them
+    fn list_content_labels(&self, content_id: &str, after: &str) -> Result<Vec<Value>> {
+        use generated::list_content_labels::*;
+        let query = include_str!("list_content_labels.graphql");
+        let mut names = vec![];
+        let mut after = Some(after.to_string());
+        while let Some(cursor) = after.take() {
+            let variables = Variables {
+                id: content_id.to_string(),
+                after: Some(cursor),
+            };
+            let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+            let (has_next_page, end_cursor, nodes): (_, _, Vec<_>) = match resp.data.node {
+                Some(ListContentLabelsNode::Issue(ListContentLabelsNodeOnIssue {
+                    labels: Some(labels),
+                })) => (
+                    labels.page_info.has_next_page,
+                    labels.page_info.end_cursor,
+                    labels
+                        .nodes
+                        .into_iter()
+                        .flatten()
+                        .flatten()
+                        .map(|l| l.name)
+                        .collect(),
+                ),
+                Some(ListContentLabelsNode::PullRequest(ListContentLabelsNodeOnPullRequest {
+                    labels: Some(labels),
+                })) => (
+                    labels.page_info.has_next_page,
+                    labels.page_info.end_cursor,
+                    labels
+                        .nodes
+                        .into_iter()
+                        .flatten()
+                        .flatten()
+                        .map(|l| l.name)
+                        .collect(),
+                ),
+                _ => return Err(anyhow::anyhow!("Error: {}", resp.errors.error_msgs())),
+            };
+            names.extend(nodes.into_iter().map(Value::Str));
+            after = end_cursor.filter(|_| has_next_page);
+        }
+        Ok(names)
+    }
+
+    /// the logins of the assignees of an issue, pull request or draft issue after the cursor
+    /// `after`, as `listItems` only fetches the first page of them
+    fn list_content_assignees(&self, content_id: &str, after: &str) -> Result<Vec<Value>> {
+        use generated::list_content_assignees::*;
+        let query = include_str!("list_content_assignees.graphql");
+        let mut logins = vec![];
+        let mut after = Some(after.to_string());
+        while let Some(cursor) = after.take() {
+            let variables = Variables {
+                id: content_id.to_string(),
+                after: Some(cursor),
+            };
+            let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+            let (has_next_page, end_cursor, nodes): (_, _, Vec<_>) = match resp.data.node {
+                Some(ListContentAssigneesNode::Issue(ListContentAssigneesNodeOnIssue {
+                    assignees,
+                })) => (
+                    assignees.page_info.has_next_page,
+                    assignees.page_info.end_cursor,
+                    assignees
+                        .nodes
+                        .into_iter()
+                        .flatten()
+                        .flatten()
+                        .map(|u| u.login)
+                        .collect(),
+                ),
+                Some(ListContentAssigneesNode::PullRequest(
+                    ListContentAssigneesNodeOnPullRequest { assignees },
+                )) => (
+                    assignees.page_info.has_next_page,
+                    assignees.page_info.end_cursor,
+                    assignees
+                        .nodes
+                        .into_iter()
+                        .flatten()
+                        .flatten()
+                        .map(|u| u.login)
+                        .collect(),
+                ),
+                Some(ListContentAssigneesNode::DraftIssue(
+                    ListContentAssigneesNodeOnDraftIssue { assignees },
+                )) => (
+                    assignees.page_info.has_next_page,
+                    assignees.page_info.end_cursor,
+                    assignees
+                        .nodes
+                        .into_iter()
+                        .flatten()
+                        .flatten()
+                        .map(|u| u.login)
+                        .collect(),
+                ),
+                _ => return Err(anyhow::anyhow!("Error: {}", resp.errors.error_msgs())),
+            };
+            logins.extend(nodes.into_iter().map(Value::Str));
+            after = end_cursor.filter(|_| has_next_page);
+        }
+        Ok(logins)
+    }
+
+    /// the bodies of the issues, pull requests and draft issues of the items by item id,
+    /// per 100 items as `listItems` leaves them out
+    fn list_item_bodies(&self, item_ids: &[String]) -> Result<HashMap<String, String>> {
+        use generated::list_item_bodies::*;
+        let query = include_str!("list_item_bodies.graphql");
+        let mut bodies = HashMap::new();
+        for ids in item_ids.chunks(100) {
+            let variables = Variables { ids: ids.to_vec() };
+            let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+            // items deleted since they were listed are null
+            for node in resp.data.nodes.into_iter().flatten() {
+                let ListItemBodiesNodes::ProjectV2Item(item) = node else {
+                    continue;
+                };
+                let body = match item.content {
+                    Some(ListItemBodiesNodesOnProjectV2ItemContent::Issue(issue)) => issue.body,
+                    Some(ListItemBodiesNodesOnProjectV2ItemContent::PullRequest(pr)) => pr.body,
+                    Some(ListItemBodiesNodesOnProjectV2ItemContent::DraftIssue(draft)) => {
+                        draft.body
+                    }
+                    None => continue,
+                };
+                bodies.insert(item.id, body);
+            }
+        }
+        Ok(bodies)
+    }
+
+    /// the body of the latest comment on the issues and pull requests of the items by item id
+    fn list_item_comments(&self, item_ids: &[String]) -> Result<HashMap<String, String>> {
+        use generated::list_item_comments::*;
+        let query = include_str!("list_item_comments.graphql");
+        let mut comments = HashMap::new();
+        for ids in item_ids.chunks(100) {
+            let variables = Variables { ids: ids.to_vec() };
+            let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+            for node in resp.data.nodes.into_iter().flatten() {
+                let ListItemCommentsNodes::ProjectV2Item(item) = node else {
+                    continue;
+                };
+                let body = match item.content {
+                    Some(ListItemCommentsNodesOnProjectV2ItemContent::Issue(issue)) => issue
+                        .comments
+                        .nodes
+                        .into_iter()
+                        .flatten()
+                        .flatten()
+                        .last()
+                        .map(|c| c.body),
+                    Some(ListItemCommentsNodesOnProjectV2ItemContent::PullRequest(pr)) => pr
+                        .comments
+                        .nodes
+                        .into_iter()
+                        .flatten()
+                        .flatten()
+                        .last()
+                        .map(|c| c.body),
+                    _ => None,
+                };
+                if let Some(body) = body {
+                    comments.insert(item.id, body);
+                }
+            }
+        }
+        Ok(comments)
+    }
+
+    /// when the timelines of the issues and pull requests of the items were last updated, by
+    /// item id
+    fn list_item_timelines(&self, item_ids: &[String]) -> Result<HashMap<String, String>> {
+        use generated::list_item_timelines::*;
+        let query = include_str!("list_item_timelines.graphql");
+        let mut updated_at = HashMap::new();
+        for ids in item_ids.chunks(100) {
+            let variables = Variables { ids: ids.to_vec() };
+            let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+            for node in resp.data.nodes.into_iter().flatten() {
+                let ListItemTimelinesNodes::ProjectV2Item(item) = node else {
+                    continue;
+                };
+                let timeline_updated_at = match item.content {
+                    Some(ListItemTimelinesNodesOnProjectV2ItemContent::Issue(issue)) => {
+                        issue.timeline_items.updated_at
+                    }
+                    Some(ListItemTimelinesNodesOnProjectV2ItemContent::PullRequest(pr)) => {
+                        pr.timeline_items.updated_at
+                    }
+                    _ => continue,
+                };
+                updated_at.insert(item.id, timeline_updated_at);
+            }
+        }
+        Ok(updated_at)
+    }
+
+    /// the values of the lazy column `name` of the items by item id, items without one are
+    /// left out
+    fn list_lazy_column(&self, name: &str, item_ids: &[String]) -> Result<HashMap<String, Value>> {
+        let span = tracing::info_span!(
+            "fetch",
+            table = "items",
+            column = name,
+            rows = tracing::field::Empty
+        );
+        let _enter = span.enter();
+        let values: HashMap<_, _> = match name {
+            "Body" => self
+                .list_item_bodies(item_ids)?
+                .into_iter()
+                .map(|(id, body)| (id, Value::Str(body)))
+                .collect(),
+            "LastComment" => self
+                .list_item_comments(item_ids)?
+                .into_iter()
+                .map(|(id, body)| (id, Value::Str(body)))
+                .collect(),
+            "TimelineUpdatedAt" => self
+                .list_item_timelines(item_ids)?
+                .into_iter()
+                .map(|(id, updated_at)| (id, timestamp(Some(&updated_at))))
+                .collect(),
+            _ => unreachable!(),
+        };
+        span.record("rows", values.len());
+        Ok(values)
+    }
+
+    /// the columns of the fields a table view shows, in its order, from the view `name` or the
+    /// first table view
+    fn view_columns(
+        &self,
+        project_id: String,
+        name: Option<&str>,
+        fields: &[Field],
+        reserved_columns: &[String],
+    ) -> Result<Vec<String>> {
+        use generated::list_views::*;
+        let query = include_str!("list_views.graphql");
+        let variables = Variables { project_id };
+        let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+        let Some(ListViewsNode::ProjectV2(ListViewsNodeOnProjectV2 {
+            views: ListViewsNodeOnProjectV2Views { nodes },
+        })) = resp.data.node
+        else {
+            return Err(anyhow::anyhow!("Error: {}", resp.errors.error_msgs()));
+        };
+        let views: Vec<_> = nodes
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|view| matches!(view.layout, ProjectV2ViewLayout::TABLE_LAYOUT))
+            .collect();
+        let view = match name {
+            Some(name) => views.iter().find(|view| view.name == name),
+            None => views.first(),
+        };
+        let Some(view) = view else {
+            let names: Vec<_> = views
+                .iter()
+                .map(|view| format!("\"{}\"", view.name))
+                .collect();
+            return Err(anyhow::anyhow!(
+                "No table view named {}, the project has {}",
+                name.map_or_else(|| "at all".to_string(), |name| format!("\"{}\"", name)),
+                if names.is_empty() {
+                    "none".to_string()
+                } else {
+                    names.join(", ")
+                }
+            ));
+        };
+        let view_fields = view.fields.iter().flat_map(|fields| &fields.nodes);
+        let columns = view_fields
+            .flatten()
+            .flatten()
+            .filter_map(|field| {
+                use ListViewsNodeOnProjectV2ViewsNodesFieldsNodes::*;
+                let (id, name) = match field {
+                    ProjectV2Field(field) => (&field.id, &field.name),
+                    ProjectV2IterationField(field) => (&field.id, &field.name),
+                    ProjectV2SingleSelectField(field) => (&field.id, &field.name),
+                };
+                // fields of the content like Title are the reserved columns
+                match fields.iter().find(|field| &field.id == id) {
+                    Some(field) => Some(field.column.clone()),
+                    None => RESERVED_COLUMNS
+                        .iter()
+                        .position(|(reserved, _)| reserved == name)
+                        .map(|idx| reserved_columns[idx].clone()),
+                }
+            })
+            .collect();
+        Ok(columns)
+    }
+
+    /// timeline events of the issues and pull requests of the project of the types
+    /// [`TimelineEvent`] names, the first 100 of an item with the item and the rest by
+    /// [`Self::list_content_events`]
+    fn list_item_events(&self, project_id: String) -> Result<Vec<(String, Vec<Value>)>> {
+        use generated::list_item_events::*;
+        let query = include_str!("list_item_events.graphql");
+        let mut rows = vec![];
+        let mut after = None;
+        loop {
+            let variables = Variables {
+                project_id: project_id.clone(),
+                after: after.clone(),
+            };
+            let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+            let Some(ListItemEventsNode::ProjectV2(ListItemEventsNodeOnProjectV2 {
+                items: ListItemEventsNodeOnProjectV2Items { page_info, nodes },
+            })) = resp.data.node
+            else {
+                return Err(anyhow::anyhow!("Error: {}", resp.errors.error_msgs()));
+            };
+            for item in nodes.into_iter().flatten().flatten() {
+                let (content_id, mut events, next_page): (_, Vec<_>, _) = match item.content {
+                    Some(ListItemEventsNodeOnProjectV2ItemsNodesContent::Issue(issue)) => (
+                        issue.id,
+                        issue
+                            .timeline_items
+                            .nodes
+                            .into_iter()
+                            .flatten()
+                            .flatten()
+                            .filter_map(TimelineEvent::into_event)
+                            .collect(),
+                        issue
+                            .timeline_items
+                            .page_info
+                            .end_cursor
+                            .filter(|_| issue.timeline_items.page_info.has_next_page),
+                    ),
+                    Some(ListItemEventsNodeOnProjectV2ItemsNodesContent::PullRequest(pr)) => (
+                        pr.id,
+                        pr.timeline_items
+                            .nodes
+                            .into_iter()
+                            .flatten()
+                            .flatten()
+                            .filter_map(TimelineEvent::into_event)
+                            .collect(),
+                        pr.timeline_items
+                            .page_info
+                            .end_cursor
+                            .filter(|_| pr.timeline_items.page_info.has_next_page),
+                    ),
+                    _ => continue,
+                };
+                if let Some(after) = next_page {
+                    events.extend(self.list_content_events(&content_id, &after)?);
+                }
+                for (n, (name, actor, created_at)) in events.into_iter().enumerate() {
+                    let row = vec![
+                        Value::Str(item.id.clone()),
+                        Value::Str(name.to_string()),
+                        actor,
+                        created_at,
+                    ];
+                    rows.push((format!("{}/{}", item.id, n), row));
+                }
+            }
+            match page_info.end_cursor {
+                Some(end_cursor) if page_info.has_next_page => after = Some(end_cursor),
+                _ => return Ok(rows),
+            }
+        }
+    }
+
+    /// the timeline events of the issue or pull request `content_id` after the cursor
+    fn list_content_events(
+        &self,
+        content_id: &str,
+        after: &str,
+    ) -> Result<Vec<(&'static str, Value, Value)>> {
+        use generated::list_content_events::*;
+        let query = include_str!("list_content_events.graphql");
+        let mut events = vec![];
+        let mut after = Some(after.to_string());
+        while let Some(cursor) = after.take() {
+            let variables = Variables {
+                id: content_id.to_string(),
+                after: Some(cursor),
+            };
+            let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+            let (has_next_page, end_cursor) = match resp.data.node {
+                Some(ListContentEventsNode::Issue(ListContentEventsNodeOnIssue {
+                    timeline_items,
+                })) => {
+                    let nodes = timeline_items.nodes.into_iter().flatten().flatten();
+                    events.extend(nodes.filter_map(TimelineEvent::into_event));
+                    let page_info = timeline_items.page_info;
+                    (page_info.has_next_page, page_info.end_cursor)
+                }
+                Some(ListContentEventsNode::PullRequest(ListContentEventsNodeOnPullRequest {
+                    timeline_items,
+                })) => {
+                    let nodes = timeline_items.nodes.into_iter().flatten().flatten();
+                    events.extend(nodes.filter_map(TimelineEvent::into_event));
+                    let page_info = timeline_items.page_info;
+                    (page_info.has_next_page, page_info.end_cursor)
+                }
+                _ => return Err(anyhow::anyhow!("Error: {}", resp.errors.error_msgs())),
+            };
+            after = end_cursor.filter(|_| has_next_page);
+        }
+        Ok(events)
+    }
+
+    /// the fields and the project, which serve `options`, `iterations` and `project`, the
+    /// items are fetched by [`Self::load_items`] once they are needed
+    fn fetch_data(&self) -> Result<Cache> {
+        let span = tracing::info_span!("fetch", table = "fields", rows = tracing::field::Empty);
+        let _enter = span.enter();
+        let (project, mut fields) = self.list_fields()?;
+        let reserved_columns = assign_columns(&mut fields, self.opt.snake_case_columns);
+        let view_columns = match &self.opt.column_order {
+            ColumnOrder::Api => None,
+            ColumnOrder::View(name) => Some(self.view_columns(
+                project.id.clone(),
+                name.as_deref(),
+                &fields,
+                &reserved_columns,
+            )?),
+        };
+        columns::set_wildcard("items", view_columns);
+        columns::set_lazy("items", lazy_columns(&reserved_columns));
+        span.record("rows", fields.len());
+        Ok(Cache {
+            project,
+            reserved_columns,
+            fields,
+            items: Arc::default(),
+            item_positions: BTreeMap::new(),
+            updated_at: HashMap::new(),
+            items_loaded: false,
+            scanned: false,
+            lazy_loaded: HashSet::new(),
+            reactions: None,
+            item_events: None,
+            repositories: None,
+            issues: None,
+            pull_requests: None,
+            assignable_users: None,
+            labels: None,
+        })
+    }
+
+    /// paginate the items into the cache unless they have been
+    fn load_items(&self, cache: &mut Cache) -> Result<()> {
+        if cache.items_loaded {
+            return Ok(());
+        }
+        let span = tracing::info_span!("fetch", table = "items", rows = tracing::field::Empty);
+        let _enter = span.enter();
+        let (items, updated_at) = self.scan_items(cache.project.id.clone(), &cache.fields)?;
+        span.record("rows", items.len());
+        cache.items = Arc::new(items);
+        cache.index_items();
+        cache.updated_at = updated_at;
+        cache.items_loaded = true;
+        Ok(())
+    }
+
+    fn update_item_field(
+        &self,
+        project_id: String,
+        item_id: String,
+        field_id: String,
+        value: ProjectV2FieldValue,
     ) -> Result<()> {
         let query = include_str!("./update_item_field.graphql");
         let variables = Variables {
@@ -772,6 +2941,1184 @@ impl ProjectNextStorage {
         Ok(())
     }
 
+    /// send the changes one by one, continuing or stopping after a failure as `--on-error` says.
+    /// If any failed, a table of every change and its result is printed to stderr, unless
+    /// `--errors json` lists the failed ones in the error.
+    fn send_item_updates(&self, cache: &Cache, updates: Vec<(String, ItemUpdate)>) -> Result<()> {
+        // item id, column and the error, `None` for skipped changes
+        let mut results = vec![];
+        let (mut applied, mut statements) = (vec![], vec![]);
+        let mut aborted = false;
+        let mut send = |item_id: &str, column: &str, f: &mut dyn FnMut() -> Result<()>| {
+            let result = (!aborted).then(|| f().map_err(|e| format!("{:#}", e)));
+            aborted |= self.opt.on_error == OnError::Abort && matches!(result, Some(Err(_)));
+            let updated = matches!(result, Some(Ok(())));
+            results.push((item_id.to_string(), column.to_string(), result));
+            updated
+        };
+        for (item_id, update) in updates {
+            if let Some(repository) = &update.convert_to {
+                let column = cache.reserved_column("Repository");
+                if send(&item_id, column, &mut || {
+                    self.convert_draft_item(item_id.clone(), repository)
+                }) {
+                    let value = Value::Str(repository.clone());
+                    statements.push(cache.audit_item_update(&item_id, column, &value));
+                }
+            }
+            if let Some((repository, number, state)) = &update.state {
+                let column = cache.reserved_column("State");
+                if send(&item_id, column, &mut || {
+                    self.set_state(repository, *number, state)
+                }) {
+                    let value = Value::Str(state.clone());
+                    statements.push(cache.audit_item_update(&item_id, column, &value));
+                }
+            }
+            for change in update.fields {
+                let field = &cache.fields[change.field];
+                let updated = send(&item_id, &field.column, &mut || {
+                    self.update_item_field(
+                        cache.project.id.clone(),
+                        item_id.clone(),
+                        field.id.clone(),
+                        change.input.clone(),
+                    )
+                });
+                if updated {
+                    applied.push(change.to_journal(cache, &item_id));
+                    statements.push(cache.audit_item_update(&item_id, &field.column, &change.new));
+                }
+            }
+        }
+        self.record(applied);
+        self.audit(statements);
+        let failures: Vec<_> = results
+            .iter()
+            .filter_map(|(item_id, column, result)| match result {
+                Some(Err(e)) => Some((item_id.clone(), column.clone(), e.clone())),
+                _ => None,
+            })
+            .collect();
+        if failures.is_empty() {
+            return Ok(());
+        }
+        let applied = results
+            .iter()
+            .filter(|(_, _, result)| matches!(result, Some(Ok(()))))
+            .count();
+        if output::error_format() == output::ErrorFormat::Json {
+            return Err(PartialFailure { applied, failures }.into());
+        }
+        let labels = vec!["id".to_string(), "column".to_string(), "result".to_string()];
+        let rows = results
+            .into_iter()
+            .map(|(item_id, column, result)| {
+                let result = match result {
+                    Some(Ok(())) => "updated".to_string(),
+                    Some(Err(e)) => format!("failed: {}", e),
+                    None => "skipped".to_string(),
+                };
+                vec![Value::Str(item_id), Value::Str(column), Value::Str(result)]
+            })
+            .collect();
+        output::Format::Table.print(io::stderr(), labels, rows, &output::Stats::default())?;
+        Err(PartialFailure { applied, failures }.into())
+    }
+
+    /// convert a draft issue, and close or reopen an issue or pull request, as [`item_update`] planned
+    fn set_content(&self, item_id: &str, update: &ItemUpdate) -> Result<()> {
+        if let Some(repository) = &update.convert_to {
+            self.convert_draft_item(item_id.to_string(), repository)?;
+        }
+        if let Some((repository, number, state)) = &update.state {
+            self.set_state(repository, *number, state)?;
+        }
+        Ok(())
+    }
+
+    /// the issue or pull request `number` in `repository` (`owner/name`)
+    fn content_id(&self, repository: &str, number: i64) -> Result<Content> {
+        use generated::content_id::*;
+        let (owner, name) = repository
+            .split_once('/')
+            .ok_or_else(|| anyhow::anyhow!("Expected `owner/name`: {}", repository))?;
+        let query = include_str!("./content_id.graphql");
+        let variables = Variables {
+            owner: owner.to_string(),
+            name: name.to_string(),
+            number,
+        };
+        let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+        let content = resp
+            .data
+            .repository
+            .and_then(|repository| repository.issue_or_pull_request)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "{}#{} is not found: {}",
+                    repository,
+                    number,
+                    resp.errors.error_msgs()
+                )
+            })?;
+        Ok(match content {
+            ContentIdRepositoryIssueOrPullRequest::Issue(issue) => Content::Issue(issue.id),
+            ContentIdRepositoryIssueOrPullRequest::PullRequest(pr) => Content::PullRequest(pr.id),
+        })
+    }
+
+    /// close or reopen the issue or pull request `number` in `repository`
+    fn set_state(&self, repository: &str, number: i64, state: &str) -> Result<()> {
+        if !matches!(state, "OPEN" | "CLOSED") {
+            return Err(anyhow::anyhow!(
+                "State can only be set to OPEN or CLOSED: {}",
+                state
+            ));
+        }
+        let errors = match (self.content_id(repository, number)?, state) {
+            (Content::Issue(issue_id), "CLOSED") => {
+                use generated::close_issue::*;
+                let query = include_str!("./close_issue.graphql");
+                gh::graphql::<_, ResponseData>(query, &Variables { issue_id })?.errors
+            }
+            (Content::Issue(issue_id), "OPEN") => {
+                use generated::reopen_issue::*;
+                let query = include_str!("./reopen_issue.graphql");
+                gh::graphql::<_, ResponseData>(query, &Variables { issue_id })?.errors
+            }
+            (Content::PullRequest(pull_request_id), "CLOSED") => {
+                use generated::close_pull_request::*;
+                let query = include_str!("./close_pull_request.graphql");
+                gh::graphql::<_, ResponseData>(query, &Variables { pull_request_id })?.errors
+            }
+            (Content::PullRequest(pull_request_id), "OPEN") => {
+                use generated::reopen_pull_request::*;
+                let query = include_str!("./reopen_pull_request.graphql");
+                gh::graphql::<_, ResponseData>(query, &Variables { pull_request_id })?.errors
+            }
+            _ => unreachable!(),
+        };
+        if !errors.errors.is_empty() {
+            return Err(anyhow::anyhow!("Error: {}", errors.error_msgs()));
+        }
+        Ok(())
+    }
+
+    /// turn the draft issue item `item_id` into an issue of `repository` (`owner/name`)
+    fn convert_draft_item(&self, item_id: String, repository: &str) -> Result<()> {
+        let (owner, name) = repository
+            .split_once('/')
+            .ok_or_else(|| anyhow::anyhow!("Expected `owner/name`: {}", repository))?;
+        let repository_id = {
+            use generated::repository_id::*;
+            let query = include_str!("./repository_id.graphql");
+            let variables = Variables {
+                owner: owner.to_string(),
+                name: name.to_string(),
+            };
+            let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+            resp.data
+                .repository
+                .map(|repository| repository.id)
+                .ok_or_else(|| {
+                    anyhow::anyhow!("{} is not found: {}", repository, resp.errors.error_msgs())
+                })?
+        };
+        use generated::convert_draft_item::*;
+        let query = include_str!("./convert_draft_item.graphql");
+        let variables = Variables {
+            item_id,
+            repository_id,
+        };
+        let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+        resp.data
+            .convert_project_v2_draft_issue_item_to_issue
+            .and_then(|payload| payload.item)
+            .map(|_| ())
+            .ok_or_else(|| anyhow::anyhow!("Error: {}", resp.errors.error_msgs()))
+    }
+
+    fn add_item(&self, project_id: String, content_id: String) -> Result<String> {
+        use generated::add_item::*;
+        let query = include_str!("./add_item.graphql");
+        let variables = Variables {
+            project_id,
+            content_id,
+        };
+        let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+        resp.data
+            .add_project_v2_item_by_id
+            .and_then(|payload| payload.item)
+            .map(|item| item.id)
+            .ok_or_else(|| anyhow::anyhow!("Error: {}", resp.errors.error_msgs()))
+    }
+
+    fn add_draft_item(&self, project_id: String, title: String) -> Result<String> {
+        use generated::add_draft_item::*;
+        let query = include_str!("./add_draft_item.graphql");
+        let variables = Variables { project_id, title };
+        let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+        resp.data
+            .add_project_v2_draft_issue
+            .and_then(|payload| payload.project_item)
+            .map(|item| item.id)
+            .ok_or_else(|| anyhow::anyhow!("Error: {}", resp.errors.error_msgs()))
+    }
+
+    /// add issues and pull requests (`Repository`, `Issue`) or drafts (`Title`) as items,
+    /// then set the given field values. Issues and pull requests already in the project
+    /// aren't added again, only their field values are set
+    #[allow(clippy::result_large_err)]
+    fn insert_items(&self, cache: &Cache, rows: Vec<Vec<Value>>) -> GlueSQLResult<()> {
+        let storage_err = |e: anyhow::Error| storage_error(e);
+        let readonly = |idx: usize| cache.readonly(idx);
+        // the items added by `owner/name#number`, as `INSERT ... SELECT` may select one twice
+        let mut added_items = HashMap::new();
+        let (mut added, mut present) = (0, 0);
+        for values in rows {
+            let (reserved, fields) = values.split_at(RESERVED_COLUMNS.len());
+            let inputs = cache
+                .fields
+                .iter()
+                .zip(fields)
+                .filter(|(_, value)| !value.is_null())
+                .map(|(field, value)| Ok((field, field_value_input(field, value)?)))
+                .collect::<GlueSQLResult<Vec<_>>>()?;
+            let item_id = match reserved {
+                [Value::Null, Value::Str(repository), Value::I64(number), readonly_values @ ..] => {
+                    if let Some(idx) = readonly_values.iter().position(|value| !is_empty(value)) {
+                        return Err(readonly(3 + idx));
+                    }
+                    let key = format!("{}#{}", repository, number);
+                    let item_id = cache
+                        .item_of(repository, *number)
+                        .or_else(|| added_items.get(&key).map(String::as_str));
+                    if let Some(item_id) = item_id {
+                        present += 1;
+                        item_id.to_string()
+                    } else {
+                        let content_id = self
+                            .content_id(repository, *number)
+                            .map_err(storage_err)?
+                            .into_id();
+                        let item_id = self
+                            .add_item(cache.project.id.clone(), content_id)
+                            .map_err(storage_err)?;
+                        added_items.insert(key, item_id.clone());
+                        added += 1;
+                        item_id
+                    }
+                }
+                [Value::Null, Value::Null, Value::Null, Value::Str(title), readonly_values @ ..] => {
+                    if let Some(idx) = readonly_values.iter().position(|value| !is_empty(value)) {
+                        return Err(readonly(4 + idx));
+                    }
+                    added += 1;
+                    self.add_draft_item(cache.project.id.clone(), title.clone())
+                        .map_err(storage_err)?
+                }
+                [id, ..] if !id.is_null() => return Err(readonly(0)),
+                _ => {
+                    return Err(GlueSQLError::StorageMsg(
+                        "either Repository and Issue, or Title is required".to_string(),
+                    ))
+                }
+            };
+            for (field, input) in inputs {
+                let project_id = cache.project.id.clone();
+                self.update_item_field(project_id, item_id.clone(), field.id.clone(), input)
+                    .map_err(|e| {
+                        storage_err(anyhow::anyhow!("{} {}: {:#}", item_id, field.column, e))
+                    })?;
+            }
+            let values: Vec<_> = cache
+                .reserved_columns
+                .iter()
+                .chain(cache.fields.iter().map(|field| &field.column))
+                .map(String::as_str)
+                .zip(&values)
+                .filter(|(_, value)| !value.is_null())
+                .collect();
+            self.audit(vec![audit::insert("items", &values)]);
+        }
+        if added + present > 0 {
+            eprintln!(
+                "{} item(s) added, {} already in the project",
+                added, present
+            );
+        }
+        Ok(())
+    }
+
+    /// fetch the items and the lazy columns the statement names for a scan of `items`
+    fn prepare_items(&self, cache: &mut Cache) -> Result<()> {
+        if cache.items_loaded {
+            gh::count_cache_hit();
+        }
+        self.load_items(cache)?;
+        for name in LAZY_COLUMNS {
+            if cache.lazy_loaded.contains(name) || !columns::is_named(cache.reserved_column(name)) {
+                continue;
+            }
+            let item_ids: Vec<_> = cache.items.iter().map(|(id, _)| id.clone()).collect();
+            let mut values = self.list_lazy_column(name, &item_ids)?;
+            let idx = reserved_index(name);
+            for (id, row) in Arc::make_mut(&mut cache.items) {
+                if let Some(value) = values.remove(id) {
+                    row[idx] = value;
+                }
+            }
+            cache.lazy_loaded.insert(name);
+        }
+        Ok(())
+    }
+
+    /// take the cache out so that the next statement sees the changes made with it, the
+    /// items may not have been fetched
+    fn take_metadata(&self) -> Result<Cache> {
+        let cache = self.cache.lock().unwrap().take();
+        match cache {
+            Some(cache) => Ok(cache),
+            None => self.fetch_data(),
+        }
+    }
+
+    /// take the cache out like [`Self::take_metadata`], with the items
+    fn take_cache(&self) -> Result<Cache> {
+        let mut cache = self.take_metadata()?;
+        self.load_items(&mut cache)?;
+        Ok(cache)
+    }
+
+    /// the title and the number of fields of the project, to check that it can be read
+    pub fn check_access(&self) -> Result<(String, usize)> {
+        let (project, fields) = self.list_fields()?;
+        Ok((project.title, fields.len()))
+    }
+
+    /// every table with the columns an `UPDATE` (or `INSERT` for scratch tables) can write
+    pub fn describe(&self) -> Result<Vec<TableDescription>> {
+        let cache = self.take_metadata()?;
+        let items = cache.items_schema();
+        let mut items = TableDescription::new(&items, |idx| {
+            let options =
+                column_field_index(idx).and_then(|field_idx| match &cache.fields[field_idx].kind {
+                    FieldKind::SingleSelect(options) => {
+                        Some(options.iter().map(|option| option.name.clone()).collect())
+                    }
+                    _ => None,
+                });
+            (cache.is_writable(idx), options)
+        });
+        for (column, field) in items.columns[RESERVED_COLUMNS.len()..]
+            .iter_mut()
+            .zip(&cache.fields)
+        {
+            column.unknown_kind = field.unknown_kind().map(str::to_string);
+        }
+        *self.cache.lock().unwrap() = Some(cache);
+
+        let mut tables = vec![
+            items,
+            TableDescription::new(&Self::options_schema(), |idx| (idx >= 2, None)),
+            TableDescription::new(&Self::iterations_schema(), |_| (false, None)),
+            TableDescription::new(&Self::project_schema(), |idx| (idx > 0, None)),
+            TableDescription::new(&Self::reactions_schema(), |_| (false, None)),
+            TableDescription::new(&Self::item_events_schema(), |_| (false, None)),
+            TableDescription::new(&Self::issues_schema(), |_| (false, None)),
+            TableDescription::new(&Self::pull_requests_schema(), |_| (false, None)),
+            TableDescription::new(&Self::assignable_users_schema(), |_| (false, None)),
+            TableDescription::new(&Self::labels_schema(), |_| (false, None)),
+        ];
+        tables.extend(
+            self.scratch
+                .tables()
+                .iter()
+                .map(|(schema, readonly)| TableDescription::new(schema, |_| (!readonly, None))),
+        );
+        Ok(tables)
+    }
+
+    /// apply `updates` with up to `batch_size` mutations per request, returning the
+    /// error of every update which failed
+    pub fn apply_updates(
+        &self,
+        updates: &[FieldUpdate],
+        batch_size: usize,
+    ) -> Result<Vec<Option<String>>> {
+        let cache = self.take_cache()?;
+        let mut results = vec![None; updates.len()];
+        let (mut indexes, mut inputs, mut changes, mut statements) =
+            (vec![], vec![], vec![], vec![]);
+        for (i, update) in updates.iter().enumerate() {
+            let Some(row) = cache.item(&update.id) else {
+                results[i] = Some(format!("item not found: {}", update.id));
+                continue;
+            };
+            let Some(field_idx) = cache
+                .fields
+                .iter()
+                .position(|field| field.name == update.field || field.column == update.field)
+            else {
+                results[i] = Some(format!("field not found: {}", update.field));
+                continue;
+            };
+            let field = &cache.fields[field_idx];
+            match field_value_input(field, &update.value) {
+                Ok(value) => {
+                    indexes.push(i);
+                    inputs.push((update.id.clone(), field.id.clone(), value));
+                    let old = &row[field_column_index(field_idx)];
+                    changes.push(journal::Change::new(
+                        &update.id,
+                        &field.name,
+                        old,
+                        &update.value,
+                    ));
+                    statements.push(cache.audit_item_update(
+                        &update.id,
+                        &field.column,
+                        &update.value,
+                    ));
+                }
+                Err(e) => results[i] = Some(e.to_string()),
+            }
+        }
+        let errors = self.update_item_fields(&cache.project.id, &inputs, batch_size)?;
+        self.record(succeeded(changes, &errors));
+        self.audit(succeeded(statements, &errors));
+        for (i, error) in indexes.into_iter().zip(errors) {
+            results[i] = error;
+        }
+        Ok(results)
+    }
+
+    /// set the fields back to the old values of `changes`, returning the error of every change
+    /// which failed. A field changed again since then is not reverted unless `--force` is given.
+    pub fn undo(
+        &self,
+        changes: &[journal::Change],
+        batch_size: usize,
+    ) -> Result<Vec<Option<String>>> {
+        let cache = self.take_cache()?;
+        let mut results = vec![None; changes.len()];
+        let (mut indexes, mut inputs, mut statements) = (vec![], vec![], vec![]);
+        for (i, change) in changes.iter().enumerate() {
+            let Some(row) = cache.item(&change.id) else {
+                results[i] = Some(format!("item not found: {}", change.id));
+                continue;
+            };
+            let Some(field_idx) = cache
+                .fields
+                .iter()
+                .position(|field| field.name == change.field)
+            else {
+                results[i] = Some(format!("field not found: {}", change.field));
+                continue;
+            };
+            let field = &cache.fields[field_idx];
+            let current = &row[field_column_index(field_idx)];
+            let current = journal::Change::new(&change.id, &field.name, current, current).new;
+            if current != change.new && !self.opt.force {
+                results[i] = Some(format!(
+                    "changed to {:?} since, pass --force to revert it anyway",
+                    current.unwrap_or_default()
+                ));
+                continue;
+            }
+            let old = change.old.clone().map_or(Value::Null, Value::Str);
+            match field_value_input(field, &old) {
+                Ok(value) => {
+                    indexes.push(i);
+                    inputs.push((change.id.clone(), field.id.clone(), value));
+                    statements.push(cache.audit_item_update(&change.id, &field.column, &old));
+                }
+                Err(e) => results[i] = Some(e.to_string()),
+            }
+        }
+        let errors = self.update_item_fields(&cache.project.id, &inputs, batch_size)?;
+        self.audit(succeeded(statements, &errors));
+        for (i, error) in indexes.into_iter().zip(errors) {
+            results[i] = error;
+        }
+        Ok(results)
+    }
+
+    /// append the mutations to the audit file, which can't fail the statement any more
+    fn audit(&self, statements: Vec<String>) {
+        let Some(audit) = &self.opt.audit else {
+            return;
+        };
+        if let Err(e) = audit.record(statements) {
+            eprintln!("Failed to write the audit file: {:#}", e);
+        }
+    }
+
+    /// append the applied changes to the journal, which can't fail the statement any more
+    fn record(&self, changes: Vec<journal::Change>) {
+        let Some(journal) = &self.opt.journal else {
+            return;
+        };
+        if let Err(e) = journal.record(&self.owner, self.project_number, changes) {
+            eprintln!("Failed to record the changes for undo: {:#}", e);
+        }
+    }
+
+    /// set `(item id, field id, value)`s with up to `batch_size` mutations per request,
+    /// returning the error of each
+    fn update_item_fields(
+        &self,
+        project_id: &str,
+        inputs: &[(String, String, ProjectV2FieldValue)],
+        batch_size: usize,
+    ) -> Result<Vec<Option<String>>> {
+        let mut results = vec![];
+        for batch in inputs.chunks(batch_size.max(1)) {
+            let mut query = String::from("mutation batchUpdateItemFields($projectId: ID!");
+            let mut body = String::new();
+            let mut variables = serde_json::Map::new();
+            variables.insert("projectId".to_string(), project_id.into());
+            for (n, (item_id, field_id, value)) in batch.iter().enumerate() {
+                query.push_str(&format!(
+                    ", $item{n}: ID!, $field{n}: ID!, $value{n}: ProjectV2FieldValue!"
+                ));
+                body.push_str(&format!(
+                    "  update{n}: updateProjectV2ItemFieldValue(input: {{ projectId: $projectId, itemId: $item{n}, fieldId: $field{n}, value: $value{n} }}) {{ projectV2Item {{ id }} }}\n"
+                ));
+                variables.insert(format!("item{n}"), item_id.clone().into());
+                variables.insert(format!("field{n}"), field_id.clone().into());
+                variables.insert(format!("value{n}"), serde_json::to_value(value)?);
+            }
+            query.push_str(") {\n");
+            query.push_str(&body);
+            query.push('}');
+            let resp: Result<GraphQLResponse<Option<serde_json::Value>>> =
+                gh::graphql(&query, &variables);
+            for n in 0..batch.len() {
+                let alias = format!("update{n}");
+                results.push(match &resp {
+                    Err(e) => Some(format!("{:#}", e)),
+                    Ok(resp) => {
+                        let errors: Vec<_> = resp
+                            .errors
+                            .errors
+                            .iter()
+                            .filter(|e| {
+                                matches!(e.path.first(), Some(gh::ObjectPath::String(s)) if s == &alias)
+                            })
+                            .map(|e| e.unaliased().to_string())
+                            .collect();
+                        let updated = resp
+                            .data
+                            .as_ref()
+                            .is_some_and(|data| !data[&alias].is_null());
+                        if !errors.is_empty() {
+                            Some(errors.join(" / "))
+                        } else if !updated {
+                            Some(format!("Error: {}", resp.errors.error_msgs()))
+                        } else {
+                            None
+                        }
+                    }
+                });
+            }
+        }
+        Ok(results)
+    }
+
+    fn update_field(&self, field_id: String, name: String) -> Result<()> {
+        use generated::update_field::*;
+        let query = include_str!("./update_field.graphql");
+        let variables = Variables { field_id, name };
+        let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+        if !resp.errors.errors.is_empty() {
+            return Err(anyhow::anyhow!("Error: {}", resp.errors.error_msgs()));
+        }
+        Ok(())
+    }
+
+    fn delete_field(&self, field_id: String) -> Result<()> {
+        use generated::delete_field::*;
+        let query = include_str!("./delete_field.graphql");
+        let variables = Variables { field_id };
+        let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+        if !resp.errors.errors.is_empty() {
+            return Err(anyhow::anyhow!("Error: {}", resp.errors.error_msgs()));
+        }
+        Ok(())
+    }
+
+    /// ask before deleting a field together with its values unless `--yes` is given
+    fn confirm_drop(&self, field: &Field, values: usize) -> Result<()> {
+        if self.opt.yes {
+            return Ok(());
+        }
+        if !self.opt.interactive || !io::stdin().is_terminal() {
+            return Err(anyhow::anyhow!(
+                "Refusing to drop field \"{}\" without confirmation, pass --yes",
+                field.name
+            ));
+        }
+        eprint!(
+            "Drop field \"{}\" and its values on {} item(s)? [y/N] ",
+            field.name, values
+        );
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if matches!(answer.trim(), "y" | "Y" | "yes") {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("Canceled"))
+        }
+    }
+
+    /// Replace the options of a single select field. GitHub may give the options new ids,
+    /// so the items are moved back to the options they had.
+    fn set_options(&self, cache: &Cache, field_idx: usize, options: &[OptionEdit]) -> Result<()> {
+        let field = &cache.fields[field_idx];
+        let new_options = self.update_field_options(field.id.clone(), options)?;
+        for (option, new_option) in options.iter().zip(&new_options) {
+            let Some((_, org_option)) = option.id.as_deref().and_then(|id| cache.find_option(id))
+            else {
+                continue;
+            };
+            if &org_option.id == new_option {
+                continue;
+            }
+            for item_id in cache.items_with_option(field_idx, org_option) {
+                let value = ProjectV2FieldValue {
+                    single_select_option_id: Some(new_option.clone()),
+                    ..Default::default()
+                };
+                self.update_item_field(
+                    cache.project.id.clone(),
+                    item_id.clone(),
+                    field.id.clone(),
+                    value,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// replace the options of a single select field, returning the ids of the new options
+    fn update_field_options(
+        &self,
+        field_id: String,
+        options: &[OptionEdit],
+    ) -> Result<Vec<String>> {
+        use generated::update_field_options::*;
+        let query = include_str!("./update_field_options.graphql");
+        let variables = Variables {
+            field_id,
+            options: options
+                .iter()
+                .map(|option| {
+                    Ok(ProjectV2SingleSelectFieldOptionInput {
+                        color: option_color(&option.color)?,
+                        description: option.description.clone(),
+                        name: option.name.clone(),
+                    })
+                })
+                .collect::<Result<_>>()?,
+        };
+        let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+        match resp
+            .data
+            .update_project_v2_field
+            .and_then(|payload| payload.project_v2_field)
+        {
+            Some(
+                UpdateFieldOptionsUpdateProjectV2FieldProjectV2Field::ProjectV2SingleSelectField(
+                    field,
+                ),
+            ) => Ok(field.options.into_iter().map(|option| option.id).collect()),
+            _ => Err(anyhow::anyhow!("Error: {}", resp.errors.error_msgs())),
+        }
+    }
+
+    /// Create the custom fields of this project in the project `project_number` of the same
+    /// owner. A single select field which exists there already, like "Status", gets the options
+    /// of this one. Returns the names of the fields which could not be copied.
+    pub fn copy_fields_to(&self, project_number: i64) -> Result<Vec<String>> {
+        use generated::create_field::{self, ProjectV2CustomFieldType};
+        let (_, fields) = self.list_fields()?;
+        let target = Self::new(
+            self.owner.clone(),
+            project_number,
+            Opt {
+                snake_case_columns: false,
+                csv_mounts: vec![],
+                yes: false,
+                interactive: false,
+                force: false,
+                on_error: OnError::Abort,
+                journal: None,
+                audit: None,
+                column_order: ColumnOrder::Api,
+                checkpoints: None,
+                truncate_lists: false,
+            },
+        )?;
+        let (target_project, target_fields) = target.list_fields()?;
+        let mut skipped = vec![];
+        for field in &fields {
+            let options: Vec<OptionEdit> = match &field.kind {
+                FieldKind::SingleSelect(options) => options.iter().map(Into::into).collect(),
+                _ => vec![],
+            };
+            if let Some(existing) = target_fields.iter().find(|f| f.name == field.name) {
+                match (&field.kind, &existing.kind) {
+                    (FieldKind::SingleSelect(_), FieldKind::SingleSelect(_)) => {
+                        self.update_field_options(existing.id.clone(), &options)?;
+                    }
+                    _ => skipped.push(field.name.clone()),
+                }
+                continue;
+            }
+            let data_type = match &field.kind {
+                FieldKind::Normal(FieldType::DATE) => ProjectV2CustomFieldType::DATE,
+                FieldKind::Normal(FieldType::NUMBER) => ProjectV2CustomFieldType::NUMBER,
+                FieldKind::Normal(FieldType::TEXT) => ProjectV2CustomFieldType::TEXT,
+                FieldKind::SingleSelect(_) => ProjectV2CustomFieldType::SINGLE_SELECT,
+                // the API can not create iteration fields, nor gh-sql the kinds it doesn't know
+                FieldKind::Iteration { .. } | FieldKind::Normal(FieldType::Other(_)) => {
+                    skipped.push(field.name.clone());
+                    continue;
+                }
+                // built-in fields exist in every project
+                FieldKind::Normal(_) => continue,
+            };
+            let options = match &field.kind {
+                FieldKind::SingleSelect(_) => Some(
+                    options
+                        .iter()
+                        .map(|option| {
+                            Ok(create_field::ProjectV2SingleSelectFieldOptionInput {
+                                color: option_color(&option.color)?,
+                                description: option.description.clone(),
+                                name: option.name.clone(),
+                            })
+                        })
+                        .collect::<Result<_>>()?,
+                ),
+                _ => None,
+            };
+            let query = include_str!("./create_field.graphql");
+            let variables = create_field::Variables {
+                project_id: target_project.id.clone(),
+                name: field.name.clone(),
+                data_type,
+                options,
+            };
+            let resp: GraphQLResponse<create_field::ResponseData> = gh::graphql(query, &variables)?;
+            if resp.data.create_project_v2_field.is_none() {
+                return Err(anyhow::anyhow!("Error: {}", resp.errors.error_msgs())
+                    .context(format!("failed to create field \"{}\"", field.name)));
+            }
+        }
+        Ok(skipped)
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn save_options(&self, cache: &Cache, edits: OptionEdits) -> GlueSQLResult<()> {
+        for (field_idx, options) in edits.0 {
+            self.set_options(cache, field_idx, &options)
+                .map_err(storage_error)?;
+        }
+        Ok(())
+    }
+
+    /// add options to single select fields, `color` defaults to GRAY
+    #[allow(clippy::result_large_err)]
+    fn insert_options(&self, cache: &Cache, rows: Vec<Vec<Value>>) -> GlueSQLResult<()> {
+        let mut edits = OptionEdits::default();
+        let mut statements = vec![];
+        for values in rows {
+            let [Value::Str(field_id), id, Value::Str(name), color, description] =
+                values.as_slice()
+            else {
+                return Err(GlueSQLError::StorageMsg(
+                    "field_id and name are required".to_string(),
+                ));
+            };
+            if !id.is_null() {
+                return Err(GlueSQLError::StorageMsg("readonly column: id".to_string()));
+            }
+            let Some(field_idx) = cache.fields.iter().position(|field| {
+                &field.id == field_id && matches!(field.kind, FieldKind::SingleSelect(_))
+            }) else {
+                return Err(GlueSQLError::StorageMsg(format!(
+                    "single select field not found: {}",
+                    field_id
+                )));
+            };
+            edits.field(cache, field_idx).push(OptionEdit {
+                id: None,
+                name: name.clone(),
+                color: option_text(color, "GRAY"),
+                description: option_text(description, ""),
+            });
+            let values: Vec<_> = [
+                ("field_id", &values[0]),
+                ("name", &values[2]),
+                ("color", color),
+                ("description", description),
+            ]
+            .into_iter()
+            .filter(|(_, value)| !value.is_null())
+            .collect();
+            statements.push(audit::insert("options", &values));
+        }
+        self.save_options(cache, edits)?;
+        self.audit(statements);
+        Ok(())
+    }
+
+    /// rename, recolor or redescribe options
+    #[allow(clippy::result_large_err)]
+    fn update_options(&self, cache: &Cache, rows: Vec<(String, Vec<Value>)>) -> GlueSQLResult<()> {
+        let mut edits = OptionEdits::default();
+        let mut statements = vec![];
+        for (option_id, values) in rows {
+            let Some((field_idx, org_option)) = cache.find_option(&option_id) else {
+                continue;
+            };
+            let [field_id, id, name, color, description] = values.as_slice() else {
+                unreachable!()
+            };
+            let field = &cache.fields[field_idx];
+            for (column, value, org_value) in
+                [("field_id", field_id, &field.id), ("id", id, &option_id)]
+            {
+                if !matches!(value, Value::Str(s) if s == org_value) {
+                    return Err(GlueSQLError::StorageMsg(format!(
+                        "readonly column: {}",
+                        column
+                    )));
+                }
+            }
+            let name: String = name.into();
+            let color = option_text(color, "GRAY");
+            let description = option_text(description, "");
+            if name == org_option.name
+                && color == org_option.color
+                && description == org_option.description
+            {
+                continue;
+            }
+            statements.push(audit::update(
+                "options",
+                &[
+                    ("name", &Value::Str(name.clone())),
+                    ("color", &Value::Str(color.clone())),
+                    ("description", &Value::Str(description.clone())),
+                ],
+                Some(&audit::eq("id", &Value::Str(option_id.clone()))),
+            ));
+            let options = edits.field(cache, field_idx);
+            if let Some(option) = options
+                .iter_mut()
+                .find(|option| option.id.as_deref() == Some(option_id.as_str()))
+            {
+                option.name = name;
+                option.color = color;
+                option.description = description;
+            }
+        }
+        self.save_options(cache, edits)?;
+        self.audit(statements);
+        Ok(())
+    }
+
+    /// remove options which no item uses
+    #[allow(clippy::result_large_err)]
+    fn delete_options(&self, cache: &Cache, keys: Vec<String>) -> GlueSQLResult<()> {
+        let mut edits = OptionEdits::default();
+        let mut statements = vec![];
+        for option_id in keys {
+            let Some((field_idx, option)) = cache.find_option(&option_id) else {
+                continue;
+            };
+            let used = cache.items_with_option(field_idx, option).count();
+            if used > 0 {
+                return Err(GlueSQLError::StorageMsg(format!(
+                    "option \"{}\" is used by {} item(s)",
+                    option.name, used
+                )));
+            }
+            edits
+                .field(cache, field_idx)
+                .retain(|option| option.id.as_deref() != Some(option_id.as_str()));
+            let condition = audit::eq("id", &Value::Str(option_id.clone()));
+            statements.push(audit::delete("options", &condition));
+        }
+        self.save_options(cache, edits)?;
+        self.audit(statements);
+        Ok(())
+    }
+
+    /// apply the changed columns of the row of `project` with `updateProjectV2`,
+    /// NULL clears the description and the README
+    #[allow(clippy::result_large_err)]
+    fn update_project(&self, cache: &Cache, rows: Vec<(String, Vec<Value>)>) -> GlueSQLResult<()> {
+        #[derive(Serialize, Default)]
+        #[serde(rename_all = "camelCase")]
+        struct Input {
+            project_id: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            title: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            short_description: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            readme: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            public: Option<bool>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            closed: Option<bool>,
+        }
+        #[derive(Serialize)]
+        struct Variables {
+            input: Input,
+        }
+        let project = &cache.project;
+        let text = |value: &Value| match value {
+            Value::Null => String::new(),
+            value => value.into(),
+        };
+        let changed = |new: String, org: &Option<String>| {
+            (&new != org.as_ref().unwrap_or(&String::new())).then_some(new)
+        };
+        for (_, values) in rows {
+            let [id, title, short_description, readme, public, closed] = values.as_slice() else {
+                unreachable!()
+            };
+            if !matches!(id, Value::Str(id) if id == &project.id) {
+                return Err(GlueSQLError::StorageMsg("readonly column: id".to_string()));
+            }
+            let flag = |value: &Value, org: bool| match value {
+                Value::Bool(b) if *b != org => Some(*b),
+                _ => None,
+            };
+            let input = Input {
+                project_id: project.id.clone(),
+                title: changed(text(title), &Some(project.title.clone())),
+                short_description: changed(text(short_description), &project.short_description),
+                readme: changed(text(readme), &project.readme),
+                public: flag(public, project.public),
+                closed: flag(closed, project.closed),
+            };
+            if input.title.is_none()
+                && input.short_description.is_none()
+                && input.readme.is_none()
+                && input.public.is_none()
+                && input.closed.is_none()
+            {
+                continue;
+            }
+            let text_value = |text: &Option<String>| match text.as_deref() {
+                Some("") => Some(Value::Null),
+                text => text.map(|text| Value::Str(text.to_string())),
+            };
+            let values = [
+                ("title", text_value(&input.title)),
+                ("short_description", text_value(&input.short_description)),
+                ("readme", text_value(&input.readme)),
+                ("public", input.public.map(Value::Bool)),
+                ("closed", input.closed.map(Value::Bool)),
+            ];
+            let values: Vec<_> = values
+                .iter()
+                .filter_map(|(column, value)| Some((*column, value.as_ref()?)))
+                .collect();
+            let statement = audit::update("project", &values, None);
+            let query = include_str!("./update_project.graphql");
+            let resp: GraphQLResponse<generated::update_project::ResponseData> =
+                gh::graphql(query, &Variables { input }).map_err(storage_error)?;
+            if resp.data.update_project_v2.is_none() {
+                return Err(GlueSQLError::StorageMsg(format!(
+                    "Error: {}",
+                    resp.errors.error_msgs()
+                )));
+            }
+            self.audit(vec![statement]);
+        }
+        Ok(())
+    }
+
+    /// validate the updates and apply them to the cache, so that the transaction reads them
+    #[allow(clippy::result_large_err)]
+    fn buffer_item_updates(&mut self, rows: Vec<(String, Vec<Value>)>) -> GlueSQLResult<()> {
+        let transaction = self.transaction.as_mut().unwrap();
+        let mut cache = self.cache.lock().unwrap();
+        let cache = cache.as_mut().unwrap();
+        for (item_id, new_row) in rows {
+            let Some(idx) = cache.item_position(&item_id) else {
+                continue;
+            };
+            item_update(cache, &cache.items[idx].1, &new_row)?;
+            Arc::make_mut(&mut cache.items)[idx].1 = new_row;
+            if !transaction.updated.contains(&item_id) {
+                transaction.updated.push(item_id);
+            }
+        }
+        Ok(())
+    }
+
+    fn buffer_item_deletes(&mut self, keys: Vec<String>) {
+        let transaction = self.transaction.as_mut().unwrap();
+        let mut cache = self.cache.lock().unwrap();
+        let cache = cache.as_mut().unwrap();
+        Arc::make_mut(&mut cache.items).retain(|(id, _)| !keys.contains(id));
+        cache.index_items();
+        transaction.deleted.extend(keys);
+    }
+
+    fn begin_transaction(&mut self) -> Result<()> {
+        if self.transaction.is_some() {
+            return Err(anyhow::anyhow!("a transaction is already in progress"));
+        }
+        let mut cache = self.cache.lock().unwrap();
+        if cache.is_none() {
+            *cache = Some(self.fetch_data()?);
+        }
+        self.load_items(cache.as_mut().unwrap())?;
+        self.transaction = Some(TransactionBuffer {
+            base_items: cache.as_ref().unwrap().items.clone(),
+            scratch: self.scratch.clone(),
+            updated: vec![],
+            deleted: vec![],
+            inserted: vec![],
+            writes: vec![],
+        });
+        Ok(())
+    }
+
+    /// send the buffered writes: field values in batches, then deletions, insertions and
+    /// the writes to `options` and `project` in order
+    fn commit_transaction(&mut self) -> Result<()> {
+        let transaction = self
+            .transaction
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("no transaction is in progress"))?;
+        let count = transaction.len();
+        let mut cache = self.take_cache()?;
+        let items = std::mem::replace(&mut cache.items, transaction.base_items);
+        let storage_err = |e: GlueSQLError| anyhow::anyhow!("{}", e);
+        self.check_conflicts(
+            &cache,
+            transaction.updated.iter().chain(&transaction.deleted),
+        )?;
+
+        let (mut inputs, mut changes, mut statements) = (vec![], vec![], vec![]);
+        for item_id in &transaction.updated {
+            if transaction.deleted.contains(item_id) {
+                continue;
+            }
+            let (Some(org_row), Some((_, new_row))) = (
+                cache.item(item_id),
+                items.iter().find(|(id, _)| id == item_id),
+            ) else {
+                continue;
+            };
+            let update = item_update(&cache, org_row, new_row).map_err(storage_err)?;
+            self.set_content(item_id, &update)?;
+            let mut content = vec![];
+            if let Some(repository) = &update.convert_to {
+                content.push(("Repository", Value::Str(repository.clone())));
+            }
+            if let Some((_, _, state)) = &update.state {
+                content.push(("State", Value::Str(state.clone())));
+            }
+            self.audit(
+                content
+                    .iter()
+                    .map(|(name, value)| {
+                        cache.audit_item_update(item_id, cache.reserved_column(name), value)
+                    })
+                    .collect(),
+            );
+            for change in update.fields {
+                let field = &cache.fields[change.field];
+                changes.push(change.to_journal(&cache, item_id));
+                statements.push(cache.audit_item_update(item_id, &field.column, &change.new));
+                inputs.push((item_id.clone(), field.id.clone(), change.input));
+            }
+        }
+        let results = self.update_item_fields(&cache.project.id, &inputs, COMMIT_BATCH_SIZE)?;
+        self.record(succeeded(changes, &results));
+        self.audit(succeeded(statements, &results));
+        let errors: Vec<_> = results
+            .into_iter()
+            .zip(&inputs)
+            .filter_map(|(error, (item_id, field_id, _))| {
+                let column = cache
+                    .fields
+                    .iter()
+                    .find(|field| &field.id == field_id)
+                    .map_or(field_id.as_str(), |field| field.column.as_str());
+                Some(format!("  - {} {}: {}", item_id, column, error?))
+            })
+            .collect();
+        if !errors.is_empty() {
+            return Err(anyhow::anyhow!(
+                "{} of {} field value(s) failed to update, deletions, insertions and option or project edits were not sent:\n{}",
+                errors.len(),
+                inputs.len(),
+                errors.join("\n")
+            ));
+        }
+        for item_id in transaction.deleted {
+            let condition = cache.item_condition(&item_id);
+            self.delete_item_field(cache.project.id.clone(), item_id)?;
+            self.audit(vec![audit::delete("items", &condition)]);
+        }
+        if !transaction.inserted.is_empty() {
+            self.insert_items(&cache, transaction.inserted)
+                .map_err(storage_err)?;
+        }
+        for write in transaction.writes {
+            let cache = self.take_cache()?;
+            match write {
+                Write::InsertOptions(rows) => self.insert_options(&cache, rows),
+                Write::UpdateOptions(rows) => self.update_options(&cache, rows),
+                Write::DeleteOptions(keys) => self.delete_options(&cache, keys),
+                Write::UpdateProject(rows) => self.update_project(&cache, rows),
+            }
+            .map_err(storage_err)?;
+        }
+        tracing::info!(changes = count, "committed");
+        Ok(())
+    }
+
+    /// discard the buffered writes, restoring the cache and the scratch tables
+    fn rollback_transaction(&mut self) -> Result<()> {
+        let transaction = self
+            .transaction
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("no transaction is in progress"))?;
+        if let Some(cache) = self.cache.lock().unwrap().as_mut() {
+            cache.items = transaction.base_items;
+            cache.index_items();
+            // the rows the transaction began with may not have them
+            cache.lazy_loaded.clear();
+        }
+        self.scratch = transaction.scratch;
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn check_no_transaction(&self, operation: &str) -> GlueSQLResult<()> {
+        if self.transaction.is_some() {
+            return Err(GlueSQLError::StorageMsg(format!(
+                "{} can not be done in a transaction",
+                operation
+            )));
+        }
+        Ok(())
+    }
+
     fn delete_item_field(&self, project_id: String, item_id: String) -> Result<()> {
         use generated::delete_item::*;
         #[derive(Deserialize)]
@@ -787,45 +4134,465 @@ impl ProjectNextStorage {
         }
         Ok(())
     }
+
+    /// re-fetch `updatedAt` of the items and refuse to write if someone else changed or
+    /// deleted them since the cache was filled, unless `--force` is given
+    fn check_conflicts<'a>(
+        &self,
+        cache: &Cache,
+        item_ids: impl IntoIterator<Item = &'a String>,
+    ) -> Result<()> {
+        use generated::items_updated_at::*;
+        if self.opt.force {
+            return Ok(());
+        }
+        let item_ids: Vec<_> = item_ids
+            .into_iter()
+            .filter(|id| cache.updated_at.contains_key(*id))
+            .cloned()
+            .collect();
+        let query = include_str!("./items_updated_at.graphql");
+        let mut conflicts = vec![];
+        // `nodes` takes up to 100 ids
+        for ids in item_ids.chunks(100) {
+            let variables = Variables { ids: ids.to_vec() };
+            let resp: GraphQLResponse<ResponseData> = gh::graphql(query, &variables)?;
+            if !resp.errors.errors.is_empty() {
+                return Err(anyhow::anyhow!("Error: {}", resp.errors.error_msgs()));
+            }
+            let current: HashMap<_, _> = resp
+                .data
+                .nodes
+                .into_iter()
+                .flatten()
+                .filter_map(|node| match node {
+                    ItemsUpdatedAtNodes::ProjectV2Item(item) => Some((item.id, item.updated_at)),
+                    _ => None,
+                })
+                .collect();
+            for id in ids {
+                match current.get(id) {
+                    Some(updated_at) if updated_at == &cache.updated_at[id] => {}
+                    Some(updated_at) => {
+                        conflicts.push(format!("{} (updated at {})", id, updated_at))
+                    }
+                    None => conflicts.push(format!("{} (deleted)", id)),
+                }
+            }
+        }
+        if !conflicts.is_empty() {
+            return Err(anyhow::anyhow!(
+                "{} item(s) changed since they were fetched, nothing was written; run the statement again to see the changes, or pass --force to overwrite them: {}",
+                conflicts.len(),
+                conflicts.join(", ")
+            ));
+        }
+        Ok(())
+    }
 }
 
-#[async_trait(?Send)]
-impl Store<String> for ProjectNextStorage {
-    async fn fetch_schema(&self, table_name: &str) -> GlueSQLResult<Option<Schema>> {
+impl Store for ProjectNextStorage {
+    fn fetch_schema(&self, table_name: &str) -> GlueSQLResult<Option<Schema>> {
+        if !PROJECT_TABLES.contains(&table_name) {
+            return Ok(self.scratch.schema(table_name));
+        }
         let mut cache = self.cache.lock().unwrap();
         if cache.is_none() {
-            *cache = Some(
-                self.fetch_data()
-                    .map_err(|e| GlueSQLError::Storage(e.into()))?,
-            );
+            *cache = Some(self.fetch_data().map_err(storage_error)?);
         }
         let cache = cache.as_ref().unwrap();
         Ok(match table_name {
             "items" => Some(cache.items_schema()),
             "options" => Some(Self::options_schema()),
             "iterations" => Some(Self::iterations_schema()),
+            "project" => Some(Self::project_schema()),
+            "reactions" => Some(Self::reactions_schema()),
+            "item_events" => Some(Self::item_events_schema()),
+            "issues" => Some(Self::issues_schema()),
+            "pull_requests" => Some(Self::pull_requests_schema()),
+            "assignable_users" => Some(Self::assignable_users_schema()),
+            "labels" => Some(Self::labels_schema()),
             _ => None,
         })
     }
 
-    async fn scan_data(&self, table_name: &str) -> GlueSQLResult<RowIter<String>> {
+    /// the project tables and then the scratch tables, for `GLUE_TABLES` and the foreign keys
+    /// `DELETE` checks
+    fn fetch_all_schemas(&self) -> GlueSQLResult<Vec<Schema>> {
+        let mut schemas = vec![];
+        for table_name in PROJECT_TABLES {
+            schemas.extend(self.fetch_schema(table_name)?);
+        }
+        schemas.extend(self.scratch.tables().into_iter().map(|(schema, _)| schema));
+        Ok(schemas)
+    }
+
+    fn fetch_data(&self, table_name: &str, key: &Key) -> GlueSQLResult<Option<Vec<Value>>> {
+        for row in self.scan_data(table_name)? {
+            let (row_key, row) = row?;
+            if &row_key == key {
+                return Ok(Some(row));
+            }
+        }
+        Ok(None)
+    }
+
+    fn scan_data<'a>(&'a self, table_name: &str) -> GlueSQLResult<RowIter<'a>> {
+        if !PROJECT_TABLES.contains(&table_name) {
+            return self.scratch.scan(table_name);
+        }
         let mut cache = self.cache.lock().unwrap();
         if cache.is_none() {
-            *cache = Some(
-                self.fetch_data()
-                    .map_err(|e| GlueSQLError::Storage(e.into()))?,
-            );
+            *cache = Some(self.fetch_data().map_err(storage_error)?);
+        }
+        let cache = cache.as_mut().unwrap();
+        // the tables fetched with `listFields`, the others count their own cache hits
+        if matches!(table_name, "options" | "iterations" | "project") {
+            if cache.scanned {
+                gh::count_cache_hit();
+            }
+            cache.scanned = true;
         }
-        let cache = cache.as_ref().unwrap();
         match table_name {
-            "items" => Ok(Box::new(cache.items.clone().into_iter().map(Ok))),
+            "items" => {
+                self.prepare_items(cache).map_err(storage_error)?;
+                Ok(cache.scan_items())
+            }
             "options" => Ok(cache.scan_options()),
             "iterations" => Ok(cache.scan_iterations()),
+            "project" => Ok(cache.scan_project()),
+            "reactions" => scan_fetched(
+                cache,
+                "reactions",
+                |c| &mut c.reactions,
+                |cache| self.list_reactions(cache.project.id.clone()),
+            ),
+            "item_events" => scan_fetched(
+                cache,
+                "item_events",
+                |c| &mut c.item_events,
+                |cache| self.list_item_events(cache.project.id.clone()),
+            ),
+            "issues" => scan_fetched(
+                cache,
+                "issues",
+                |c| &mut c.issues,
+                |cache| {
+                    self.linked_repositories(cache)
+                        .and_then(|repos| self.list_issues(&repos))
+                },
+            ),
+            "pull_requests" => scan_fetched(
+                cache,
+                "pull_requests",
+                |c| &mut c.pull_requests,
+                |cache| {
+                    self.linked_repositories(cache)
+                        .and_then(|repos| self.list_pull_requests(&repos))
+                },
+            ),
+            "assignable_users" => scan_fetched(
+                cache,
+                "assignable_users",
+                |c| &mut c.assignable_users,
+                |cache| {
+                    self.linked_repositories(cache)
+                        .and_then(|repos| self.list_assignable_users(&repos))
+                },
+            ),
+            "labels" => scan_fetched(
+                cache,
+                "labels",
+                |c| &mut c.labels,
+                |cache| {
+                    self.linked_repositories(cache)
+                        .and_then(|repos| self.list_labels(&repos))
+                },
+            ),
             _ => unreachable!(),
         }
     }
 }
 
+/// the rows of `table` in the cache, which `fetch` fetches on the first scan and the
+/// following scans share as a cache hit
+fn scan_fetched(
+    cache: &mut Cache,
+    table: &'static str,
+    rows: fn(&mut Cache) -> &mut Option<SharedRows>,
+    fetch: impl FnOnce(&mut Cache) -> Result<Vec<(String, Vec<Value>)>>,
+) -> GlueSQLResult<RowIter<'static>> {
+    if let Some(rows) = rows(cache) {
+        gh::count_cache_hit();
+        return Ok(scan_shared(Arc::clone(rows)));
+    }
+    let span = tracing::info_span!("fetch", table, rows = tracing::field::Empty);
+    let _enter = span.enter();
+    let fetched = Arc::new(fetch(cache).map_err(storage_error)?);
+    span.record("rows", fetched.len());
+    *rows(cache) = Some(Arc::clone(&fetched));
+    Ok(scan_shared(fetched))
+}
+
+/// the rows shared with the cache, cloned one at a time as they are read
+fn scan_shared(rows: SharedRows) -> RowIter<'static> {
+    Box::new((0..rows.len()).map(move |idx| rows[idx].clone()).map(keyed))
+}
+
+/// a reaction as its content, spelled like `THUMBS_UP`, and the login of its user
+fn reaction<C: Serialize>(content: &C, login: Option<String>) -> Option<(String, Value)> {
+    match serde_json::to_value(content) {
+        Ok(serde_json::Value::String(content)) => {
+            Some((content, login.map_or(Value::Null, Value::Str)))
+        }
+        _ => None,
+    }
+}
+
+/// an RFC 3339 timestamp of the API as a UTC `TIMESTAMP`
+fn timestamp(value: Option<&str>) -> Value {
+    value
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .map_or(Value::Null, |t| Value::Timestamp(t.naive_utc()))
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::List(list) => list.is_empty(),
+        _ => false,
+    }
+}
+
+/// the text of an option column, `default` for NULL
+fn option_text(value: &Value, default: &str) -> String {
+    match value {
+        Value::Null => default.to_string(),
+        value => value.into(),
+    }
+}
+
+/// the color of an option, which is spelled the same in every generated module
+fn option_color<C: DeserializeOwned>(color: &str) -> Result<C> {
+    const COLORS: [&str; 8] = [
+        "BLUE", "GRAY", "GREEN", "ORANGE", "PINK", "PURPLE", "RED", "YELLOW",
+    ];
+    let color = color.to_uppercase();
+    if !COLORS.contains(&color.as_str()) {
+        return Err(anyhow::anyhow!(
+            "Unknown color: {}, expected one of {}",
+            color,
+            COLORS.join(", ")
+        ));
+    }
+    Ok(serde_json::from_value(serde_json::Value::String(color))?)
+}
+
+/// what an `UPDATE` of an item from `org_row` to `new_row` sends
+struct ItemUpdate {
+    /// the repository a draft issue is converted into
+    convert_to: Option<String>,
+    /// `(repository, number, state)` of the issue or pull request to close or reopen
+    state: Option<(String, i64, String)>,
+    /// the changed fields
+    fields: Vec<FieldChange>,
+}
+
+/// a field value changed from `old` to `new`, `field` indexes `Cache::fields`
+struct FieldChange {
+    field: usize,
+    old: Value,
+    new: Value,
+    input: ProjectV2FieldValue,
+}
+
+impl FieldChange {
+    fn to_journal(&self, cache: &Cache, item_id: &str) -> journal::Change {
+        let name = &cache.fields[self.field].name;
+        journal::Change::new(item_id, name, &self.old, &self.new)
+    }
+}
+
+/// the elements whose `errors` are `None`
+fn succeeded<T>(elements: Vec<T>, errors: &[Option<String>]) -> Vec<T> {
+    elements
+        .into_iter()
+        .zip(errors)
+        .filter(|(_, error)| error.is_none())
+        .map(|(element, _)| element)
+        .collect()
+}
+
+/// check an update of an item without sending anything
+#[allow(clippy::result_large_err)]
+fn item_update(cache: &Cache, org_row: &[Value], new_row: &[Value]) -> GlueSQLResult<ItemUpdate> {
+    let reserved = RESERVED_COLUMNS.len();
+    let changed = |(new_value, org_value): &(&Value, &Value)| {
+        !(new_value.is_null() && org_value.is_null()) && new_value != org_value
+    };
+    let mut update = ItemUpdate {
+        convert_to: None,
+        state: None,
+        fields: vec![],
+    };
+    for (col_idx, (new_value, org_value)) in new_row[..reserved]
+        .iter()
+        .zip(org_row[..reserved].iter())
+        .enumerate()
+        .filter(|(_, values)| changed(values))
+    {
+        let (name, _) = RESERVED_COLUMNS[col_idx];
+        if !cache.is_writable(col_idx) {
+            // fetched after `BEGIN`, so missing from the rows the transaction began with
+            if LAZY_COLUMNS.contains(&name) && org_value.is_null() {
+                continue;
+            }
+            return Err(cache.readonly(col_idx));
+        }
+        match (name, new_value, org_value, org_row) {
+            // setting the repository of a draft issue converts it into an issue
+            ("Repository", Value::Str(repository), Value::Null, _) => {
+                update.convert_to = Some(repository.clone());
+            }
+            // closing or reopening the issue or pull request
+            (
+                "State",
+                Value::Str(state),
+                Value::Str(org_state),
+                [_, Value::Str(repository), Value::I64(number), ..],
+            ) => {
+                if !state.eq_ignore_ascii_case(org_state) {
+                    update.state = Some((repository.clone(), *number, state.to_uppercase()));
+                }
+            }
+            ("State", Value::Str(_), Value::Str(_), _) => {}
+            ("Repository", ..) => {
+                return Err(GlueSQLError::StorageMsg(format!(
+                    "{} can only be set on draft issues",
+                    cache.reserved_columns[col_idx]
+                )))
+            }
+            ("State", _, Value::Null, _) => {
+                return Err(GlueSQLError::StorageMsg(format!(
+                    "{} can only be set on issues and pull requests",
+                    cache.reserved_columns[col_idx]
+                )))
+            }
+            _ => {
+                return Err(GlueSQLError::StorageMsg(format!(
+                    "{} can not be set to {}",
+                    cache.reserved_columns[col_idx],
+                    output::value_to_string(new_value)
+                )))
+            }
+        }
+    }
+    for (field_idx, (new_value, org_value)) in new_row[reserved..]
+        .iter()
+        .zip(org_row[reserved..].iter())
+        .enumerate()
+        .filter(|(_, values)| changed(values))
+    {
+        if !cache.is_writable(reserved + field_idx) {
+            return Err(cache.readonly(reserved + field_idx));
+        }
+        let field = &cache.fields[field_idx];
+        update.fields.push(FieldChange {
+            field: field_idx,
+            old: org_value.clone(),
+            new: new_value.clone(),
+            input: field_value_input(field, new_value)?,
+        });
+    }
+    Ok(update)
+}
+
+/// the input of `updateProjectV2ItemFieldValue` which sets `field` to `new_value`
+#[allow(clippy::result_large_err)]
+fn field_value_input(field: &Field, new_value: &Value) -> GlueSQLResult<ProjectV2FieldValue> {
+    if matches!(new_value, Value::Null) {
+        return Ok(Default::default());
+    }
+    Ok(match &field.kind {
+        FieldKind::Normal(ty) => {
+            let Some(ty) = ty.as_sql_type() else {
+                return Err(field.readonly());
+            };
+
+            fn into_update_input(ty: &DataType, new_value: &Value) -> Option<ProjectV2FieldValue> {
+                Some(match ty {
+                    DataType::Date => ProjectV2FieldValue {
+                        date: Some(match new_value {
+                            Value::Str(s) => s.to_owned(),
+                            Value::Date(d) => d.format("%Y-%m-%d").to_string(),
+                            _ => None?,
+                        }),
+                        ..Default::default()
+                    },
+                    DataType::Float => ProjectV2FieldValue {
+                        number: new_value
+                            .cast(&DataType::Float)
+                            .ok()
+                            .and_then(|v| (&v).try_into().ok()),
+                        ..Default::default()
+                    },
+                    DataType::Text => ProjectV2FieldValue {
+                        text: new_value.cast(&DataType::Text).ok().map(|v| v.into()),
+                        ..Default::default()
+                    },
+                    _ => None?,
+                })
+            }
+
+            let Some(new_value_input) = into_update_input(&ty, new_value) else {
+                return Err(GlueSQLError::Value(Box::new(
+                    ValueError::IncompatibleDataType {
+                        data_type: ty,
+                        value: new_value.clone(),
+                    },
+                )));
+            };
+            new_value_input
+        }
+        FieldKind::SingleSelect(options) => {
+            let new_str: String = new_value.into();
+            if let Some(opt) = options.iter().find(|opt| opt.name == new_str) {
+                ProjectV2FieldValue {
+                    single_select_option_id: Some(opt.id.to_owned()),
+                    ..Default::default()
+                }
+            } else {
+                return Err(GlueSQLError::StorageMsg(format!(
+                    "option not found: {}",
+                    new_str
+                )));
+            }
+        }
+        FieldKind::Iteration {
+            iterations,
+            completed_iterations,
+            ..
+        } => {
+            let new_str: String = new_value.into();
+            if let Some(opt) = iterations
+                .iter()
+                .chain(completed_iterations.iter())
+                .find(|opt| opt.title == new_str)
+            {
+                ProjectV2FieldValue {
+                    iteration_id: Some(opt.id.to_owned()),
+                    ..Default::default()
+                }
+            } else {
+                return Err(GlueSQLError::StorageMsg(format!(
+                    "iteration not found: {}",
+                    new_str
+                )));
+            }
+        }
+    })
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Variables {
@@ -835,7 +4602,7 @@ pub struct Variables {
     pub value: ProjectV2FieldValue,
 }
 
-#[derive(Serialize, Default)]
+#[derive(Serialize, Default, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ProjectV2FieldValue {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -850,193 +4617,363 @@ pub struct ProjectV2FieldValue {
     text: Option<String>,
 }
 
-#[async_trait(?Send)]
-impl StoreMut<String> for ProjectNextStorage {
-    async fn insert_schema(self, _schema: &Schema) -> gluesql::result::MutResult<Self, ()> {
-        todo!()
+impl StoreMut for ProjectNextStorage {
+    fn insert_schema(&mut self, schema: &Schema) -> GlueSQLResult<()> {
+        self.scratch.create(schema);
+        Ok(())
     }
 
-    async fn delete_schema(self, _table_name: &str) -> gluesql::result::MutResult<Self, ()> {
-        todo!()
+    fn delete_schema(&mut self, table_name: &str) -> GlueSQLResult<()> {
+        if PROJECT_TABLES.contains(&table_name) {
+            return Err(GlueSQLError::StorageMsg(
+                "cannot drop project table".to_string(),
+            ));
+        }
+        self.scratch.drop(table_name)
     }
 
-    async fn insert_data(
-        self,
-        _table_name: &str,
-        _rows: Vec<Row>,
-    ) -> gluesql::result::MutResult<Self, ()> {
-        todo!()
+    fn append_data(&mut self, table_name: &str, rows: Vec<Vec<Value>>) -> GlueSQLResult<()> {
+        if let Some(transaction) = &mut self.transaction {
+            match table_name {
+                "items" => {
+                    transaction.inserted.extend(rows);
+                    return Ok(());
+                }
+                "options" => {
+                    transaction.writes.push(Write::InsertOptions(rows));
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+        if table_name == "items" {
+            let cache = self.take_cache().map_err(storage_error)?;
+            return self.insert_items(&cache, rows);
+        }
+        if table_name == "options" {
+            let cache = self.take_cache().map_err(storage_error)?;
+            return self.insert_options(&cache, rows);
+        }
+        if PROJECT_TABLES.contains(&table_name) {
+            return Err(GlueSQLError::StorageMsg("readonly table".to_string()));
+        }
+        self.scratch.insert(table_name, rows)
     }
 
-    async fn update_data(
-        self,
-        table_name: &str,
-        rows: Vec<(String, Row)>,
-    ) -> gluesql::result::MutResult<Self, ()> {
+    /// `UPDATE` writes the rows back by the keys they were scanned with
+    fn insert_data(&mut self, table_name: &str, rows: Vec<(Key, Vec<Value>)>) -> GlueSQLResult<()> {
+        if !PROJECT_TABLES.contains(&table_name) {
+            return self.scratch.update(table_name, rows);
+        }
+        let rows = rows
+            .into_iter()
+            .map(|(key, row)| Ok((node_id(key)?, row)))
+            .collect::<GlueSQLResult<Vec<_>>>()?;
+        if let (Some(transaction), "options" | "project") = (&mut self.transaction, table_name) {
+            transaction.writes.push(match table_name {
+                "options" => Write::UpdateOptions(rows),
+                _ => Write::UpdateProject(rows),
+            });
+            return Ok(());
+        }
+        if table_name == "options" || table_name == "project" {
+            let cache = self.take_cache().map_err(storage_error)?;
+            return if table_name == "options" {
+                self.update_options(&cache, rows)
+            } else {
+                self.update_project(&cache, rows)
+            };
+        }
         if table_name != "items" {
-            return Err((self, GlueSQLError::StorageMsg("readonly table".to_string())));
+            return Err(GlueSQLError::StorageMsg("readonly table".to_string()));
+        }
+        if self.transaction.is_some() {
+            return self.buffer_item_updates(rows);
         }
         let mut cache_guard = self.cache.lock().unwrap();
         let cache = cache_guard.take().unwrap();
         drop(cache_guard);
-        let schema = cache.items_schema();
+        self.check_conflicts(&cache, rows.iter().map(|(item_id, _)| item_id))
+            .map_err(storage_error)?;
+        let mut updates = vec![];
         for (item_id, new_row) in rows {
-            if let Some((_, org_row)) = cache.items.iter().find(|(org_id, _)| org_id == &item_id) {
-                const RESERVED_COLS: usize = 6; // FIXME
-                for (col_idx, (new_value, org_value)) in new_row.0[..RESERVED_COLS]
-                    .iter()
-                    .zip(org_row.0[..RESERVED_COLS].iter())
-                    .enumerate()
-                {
-                    if new_value.is_null() && org_value.is_null() {
-                        continue;
-                    }
-                    if new_value == org_value {
-                        continue;
-                    }
-                    let col_name = &schema.column_defs[col_idx].name;
-                    return Err((
-                        self,
-                        GlueSQLError::StorageMsg(format!("readonly column: {}", col_name)),
-                    ));
-                }
-                for (field_idx, (new_value, org_value)) in new_row.0[RESERVED_COLS..]
-                    .iter()
-                    .zip(org_row.0[RESERVED_COLS..].iter())
-                    .enumerate()
-                {
-                    if new_value.is_null() && org_value.is_null() {
-                        continue;
-                    }
-                    if new_value == org_value {
-                        continue;
-                    }
-                    let field = &cache.fields[field_idx];
-                    let new_value_input = if !matches!(new_value, Value::Null) {
-                        match &field.kind {
-                            FieldKind::Normal(ty) => {
-                                let Some(ty) = ty.as_sql_type() else {
-                                    return Err((
-                                        self,
-                                        GlueSQLError::StorageMsg(format!("readonly column: {:?}", ty)),
-                                    ));
-                                };
-
-                                fn into_update_input(
-                                    ty: &DataType,
-                                    new_value: &Value,
-                                ) -> Option<ProjectV2FieldValue> {
-                                    Some(match ty {
-                                        DataType::Date => ProjectV2FieldValue {
-                                            date: Some(match new_value {
-                                                Value::Str(s) => s.to_owned(),
-                                                Value::Date(d) => d.format("%Y-%m-%d").to_string(),
-                                                _ => None?,
-                                            }),
-                                            ..Default::default()
-                                        },
-                                        DataType::Float => ProjectV2FieldValue {
-                                            number: new_value
-                                                .cast(&DataType::Float)
-                                                .ok()
-                                                .and_then(|v| (&v).try_into().ok()),
-                                            ..Default::default()
-                                        },
-                                        DataType::Text => ProjectV2FieldValue {
-                                            text: new_value
-                                                .cast(&DataType::Text)
-                                                .ok()
-                                                .map(|v| v.into()),
-                                            ..Default::default()
-                                        },
-                                        _ => None?,
-                                    })
-                                }
-
-                                let Some(new_value_input) = into_update_input(&ty, new_value) else {
-                                    return Err((
-                                        self,
-                                        GlueSQLError::Value(ValueError::IncompatibleDataType {
-                                            data_type: ty,
-                                            value: new_value.clone(),
-                                        }),
-                                    ));
-                                };
-                                new_value_input
-                            }
-                            FieldKind::SingleSelect(options) => {
-                                let new_str: String = new_value.into();
-                                if let Some(opt) = options.iter().find(|opt| opt.name == new_str) {
-                                    ProjectV2FieldValue {
-                                        single_select_option_id: Some(opt.id.to_owned()),
-                                        ..Default::default()
-                                    }
-                                } else {
-                                    return Err((
-                                        self,
-                                        GlueSQLError::Value(ValueError::ImpossibleCast),
-                                    ));
-                                }
-                            }
-                            FieldKind::Iteration {
-                                iterations,
-                                completed_iterations,
-                                ..
-                            } => {
-                                let new_str: String = new_value.into();
-                                if let Some(opt) = iterations
-                                    .iter()
-                                    .chain(completed_iterations.iter())
-                                    .find(|opt| opt.title == new_str)
-                                {
-                                    ProjectV2FieldValue {
-                                        iteration_id: Some(opt.id.to_owned()),
-                                        ..Default::default()
-                                    }
-                                } else {
-                                    return Err((
-                                        self,
-                                        GlueSQLError::Value(ValueError::ImpossibleCast),
-                                    ));
-                                }
-                            }
-                        }
-                    } else {
-                        Default::default()
-                    };
-                    if let Err(e) = self.update_item_field(
-                        cache.project_id.clone(),
-                        item_id.clone(),
-                        field.id.clone(),
-                        new_value_input,
-                    ) {
-                        return Err((self, GlueSQLError::Storage(e.into())));
-                    }
-                }
+            if let Some(org_row) = cache.item(&item_id) {
+                updates.push((item_id, item_update(&cache, org_row, &new_row)?));
             }
         }
-        Ok((self, ()))
+        self.send_item_updates(&cache, updates)
+            .map_err(storage_error)
     }
 
-    async fn delete_data(
-        self,
-        table_name: &str,
-        keys: Vec<String>,
-    ) -> gluesql::result::MutResult<Self, ()> {
+    fn delete_data(&mut self, table_name: &str, keys: Vec<Key>) -> GlueSQLResult<()> {
+        if !PROJECT_TABLES.contains(&table_name) {
+            return self.scratch.delete(table_name, keys);
+        }
+        let keys = keys
+            .into_iter()
+            .map(node_id)
+            .collect::<GlueSQLResult<Vec<_>>>()?;
+        if let (Some(transaction), "options") = (&mut self.transaction, table_name) {
+            transaction.writes.push(Write::DeleteOptions(keys));
+            return Ok(());
+        }
+        if table_name == "options" {
+            let cache = self.take_cache().map_err(storage_error)?;
+            return self.delete_options(&cache, keys);
+        }
         if table_name != "items" {
-            return Err((self, GlueSQLError::StorageMsg("readonly table".to_string())));
+            return Err(GlueSQLError::StorageMsg("readonly table".to_string()));
+        }
+        if self.transaction.is_some() {
+            self.buffer_item_deletes(keys);
+            return Ok(());
         }
         let mut cache_guard = self.cache.lock().unwrap();
         let cache = cache_guard.take().unwrap();
         drop(cache_guard);
+        self.check_conflicts(&cache, &keys).map_err(storage_error)?;
         for item_id in keys {
-            if let Err(e) = self.delete_item_field(cache.project_id.clone(), item_id) {
-                return Err((self, GlueSQLError::Storage(e.into())));
+            let condition = cache.item_condition(&item_id);
+            self.delete_item_field(cache.project.id.clone(), item_id)
+                .map_err(storage_error)?;
+            self.audit(vec![audit::delete("items", &condition)]);
+        }
+        Ok(())
+    }
+}
+
+impl AlterTable for ProjectNextStorage {
+    fn rename_schema(&mut self, table_name: &str, new_table_name: &str) -> GlueSQLResult<()> {
+        if PROJECT_TABLES.contains(&table_name) || PROJECT_TABLES.contains(&new_table_name) {
+            return Err(GlueSQLError::StorageMsg(
+                "cannot rename project table".to_string(),
+            ));
+        }
+        self.scratch.rename(table_name, new_table_name)
+    }
+
+    fn rename_column(
+        &mut self,
+        table_name: &str,
+        old_column_name: &str,
+        new_column_name: &str,
+    ) -> GlueSQLResult<()> {
+        if !PROJECT_TABLES.contains(&table_name) {
+            return self
+                .scratch
+                .rename_column(table_name, old_column_name, new_column_name);
+        }
+        self.check_no_transaction("ALTER TABLE")?;
+        if table_name != "items" {
+            return Err(GlueSQLError::StorageMsg("readonly table".to_string()));
+        }
+        let cache = self.take_cache().map_err(storage_error)?;
+        if cache.reserved_columns.iter().any(|c| c == old_column_name) {
+            return Err(GlueSQLError::StorageMsg(format!(
+                "readonly column: {}",
+                old_column_name
+            )));
+        }
+        let Some(field) = cache.fields.iter().find(|f| f.column == old_column_name) else {
+            return Err(AlterTableError::RenamingColumnNotFound.into());
+        };
+        self.update_field(field.id.clone(), new_column_name.to_string())
+            .map_err(storage_error)?;
+        self.audit(vec![format!(
+            "ALTER TABLE items RENAME COLUMN {} TO {}",
+            schema::identifier(old_column_name),
+            schema::identifier(new_column_name)
+        )]);
+        Ok(())
+    }
+
+    fn add_column(&mut self, table_name: &str, column_def: &ColumnDef) -> GlueSQLResult<()> {
+        if PROJECT_TABLES.contains(&table_name) {
+            self.check_no_transaction("ALTER TABLE")?;
+            return Err(GlueSQLError::StorageMsg(
+                "ADD COLUMN is not supported on project tables".to_string(),
+            ));
+        }
+        self.scratch.add_column(table_name, column_def)
+    }
+
+    fn drop_column(
+        &mut self,
+        table_name: &str,
+        column_name: &str,
+        if_exists: bool,
+    ) -> GlueSQLResult<()> {
+        if !PROJECT_TABLES.contains(&table_name) {
+            return self.scratch.drop_column(table_name, column_name, if_exists);
+        }
+        self.check_no_transaction("ALTER TABLE")?;
+        if table_name != "items" {
+            return Err(GlueSQLError::StorageMsg("readonly table".to_string()));
+        }
+        let cache = self.take_cache().map_err(storage_error)?;
+        if cache.reserved_columns.iter().any(|c| c == column_name) {
+            return Err(GlueSQLError::StorageMsg(format!(
+                "readonly column: {}",
+                column_name
+            )));
+        }
+        let Some(field_idx) = cache.fields.iter().position(|f| f.column == column_name) else {
+            if if_exists {
+                return Ok(());
+            }
+            return Err(AlterTableError::DroppingColumnNotFound(column_name.to_string()).into());
+        };
+        let field = &cache.fields[field_idx];
+        let values = cache
+            .items
+            .iter()
+            .filter(|(_, row)| !row[field_column_index(field_idx)].is_null())
+            .count();
+        self.confirm_drop(field, values)
+            .and_then(|()| self.delete_field(field.id.clone()))
+            .map_err(storage_error)?;
+        self.audit(vec![format!(
+            "ALTER TABLE items DROP COLUMN {}",
+            schema::identifier(column_name)
+        )]);
+        Ok(())
+    }
+}
+
+impl Transaction for ProjectNextStorage {
+    /// every statement begins with `autocommit`, which is declined so that writes outside of
+    /// `BEGIN` are sent right away
+    fn begin(&mut self, autocommit: bool) -> GlueSQLResult<bool> {
+        if autocommit {
+            return Ok(false);
+        }
+        self.begin_transaction().map_err(storage_error)?;
+        Ok(false)
+    }
+
+    fn rollback(&mut self) -> GlueSQLResult<()> {
+        self.rollback_transaction().map_err(storage_error)
+    }
+
+    fn commit(&mut self) -> GlueSQLResult<()> {
+        self.commit_transaction().map_err(storage_error)
+    }
+}
+
+impl Index for ProjectNextStorage {
+    fn scan_indexed_data<'a>(
+        &'a self,
+        table_name: &str,
+        index_name: &str,
+        asc: Option<bool>,
+        cmp_value: Option<(&IndexOperator, Value)>,
+    ) -> GlueSQLResult<RowIter<'a>> {
+        if (table_name, index_name) != ("items", ITEMS_INDEX) {
+            return Err(IndexError::IndexNameDoesNotExist(index_name.to_string()).into());
+        }
+        let mut cache = self.cache.lock().unwrap();
+        if cache.is_none() {
+            *cache = Some(self.fetch_data().map_err(storage_error)?);
+        }
+        let cache = cache.as_mut().unwrap();
+        self.prepare_items(cache).map_err(storage_error)?;
+        Ok(cache.scan_items_by_id(asc, cmp_value))
+    }
+}
+
+impl IndexMut for ProjectNextStorage {
+    fn create_index(
+        &mut self,
+        _table_name: &str,
+        _index_name: &str,
+        _column: &OrderByExpr,
+    ) -> GlueSQLResult<()> {
+        Err(GlueSQLError::StorageMsg(
+            "CREATE INDEX is not supported".to_string(),
+        ))
+    }
+
+    fn drop_index(&mut self, table_name: &str, index_name: &str) -> GlueSQLResult<()> {
+        Err(GlueSQLError::StorageMsg(format!(
+            "index {} of {} can not be dropped",
+            index_name, table_name
+        )))
+    }
+}
+
+impl Metadata for ProjectNextStorage {}
+impl CustomFunction for ProjectNextStorage {}
+impl CustomFunctionMut for ProjectNextStorage {}
+
+impl Planner for ProjectNextStorage {
+    /// the default planning and [`plan_index`], which looks up items by [`ITEMS_INDEX`]
+    fn plan(&self, statement: StatementPlan) -> GlueSQLResult<StatementPlan> {
+        let schema_map = fetch_schema_map(self, &statement)?;
+        validate(&schema_map, &statement)?;
+        let statement = plan_schemaless(&schema_map, statement)?;
+        let statement = plan_primary_key(&schema_map, statement);
+        let statement = plan_index(&schema_map, statement);
+        let statement = plan_hash_join(&schema_map, statement);
+        Ok(plan_aggregate(statement))
+    }
+}
+
+impl Session for ProjectNextStorage {
+    /// fetch the other project before switching, so a failure keeps the current one
+    fn connect(&mut self, owner: String, project_number: i64) -> Result<()> {
+        if self.transaction.is_some() {
+            return Err(anyhow::anyhow!(
+                "a transaction is in progress, COMMIT or ROLLBACK first"
+            ));
+        }
+        let previous = (
+            std::mem::replace(&mut self.owner, owner),
+            std::mem::replace(&mut self.project_number, project_number),
+        );
+        match self.fetch_data() {
+            Ok(cache) => {
+                *self.cache.lock().unwrap() = Some(cache);
+                Ok(())
+            }
+            Err(e) => {
+                (self.owner, self.project_number) = previous;
+                Err(e)
             }
         }
-        Ok((self, ()))
+    }
+
+    fn project(&self) -> (&str, i64) {
+        (&self.owner, self.project_number)
+    }
+
+    fn in_transaction(&self) -> bool {
+        self.transaction.is_some()
+    }
+
+    fn choices(&self, column: &str) -> Option<Vec<String>> {
+        self.cache.lock().unwrap().as_ref()?.choices(column)
+    }
+
+    /// the project is fetched once for both, the scratch tables are copied
+    fn reader(&self) -> Option<Self> {
+        Some(Self {
+            owner: self.owner.clone(),
+            project_number: self.project_number,
+            opt: self.opt.clone(),
+            cache: Arc::clone(&self.cache),
+            scratch: self.scratch.clone(),
+            transaction: None,
+        })
     }
 }
 
-impl GStore<String> for ProjectNextStorage {}
-impl GStoreMut<String> for ProjectNextStorage {}
+impl Drop for ProjectNextStorage {
+    fn drop(&mut self) {
+        if let Some(transaction) = &self.transaction {
+            eprintln!(
+                "The transaction was not committed, {} buffered write(s) were discarded",
+                transaction.len()
+            );
+        }
+    }
+}
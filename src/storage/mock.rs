@@ -0,0 +1,901 @@
+//! A project held in memory and loaded from a JSON fixture, to run statements without GitHub.
+//!
+//! ```json
+//! {
+//!   "owner": "octo-org",
+//!   "number": 1,
+//!   "project": { "title": "Roadmap" },
+//!   "fields": [
+//!     { "id": "F1", "name": "Status", "dataType": "SINGLE_SELECT",
+//!       "options": [{ "id": "O1", "name": "Todo" }, { "id": "O2", "name": "Done" }] },
+//!     { "id": "F2", "name": "Sprint", "dataType": "ITERATION",
+//!       "iterations": [{ "id": "S1", "title": "Sprint 1", "startDate": "2024-01-01", "duration": 14 }] },
+//!     { "id": "F3", "name": "Estimate", "dataType": "NUMBER" }
+//!   ],
+//!   "items": [
+//!     { "id": "I1", "repository": "octo-org/app", "issue": 1, "title": "Fix login",
+//!       "state": "OPEN", "assignees": ["alice"], "body": "Steps to reproduce: ...",
+//!       "milestone": { "dueOn": "2024-03-01", "state": "OPEN" },
+//!       "fields": { "Status": "Todo", "Estimate": 3 } },
+//!     { "id": "I2", "title": "Idea: dark mode" }
+//!   ]
+//! }
+//! ```
+//!
+//! Writes change the project in memory only, and are gone when the process exits. `ROLLBACK`
+//! puts back the project as it was at `BEGIN`.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use gluesql::core::{
+    ast::{ColumnDef, IndexOperator, OrderByExpr},
+    data::{Key, Schema, Value},
+    error::{Error as GlueSQLError, Result as GlueSQLResult},
+    plan::StatementPlan,
+    planner::{
+        fetch_schema_map, plan_aggregate, plan_hash_join, plan_index, plan_primary_key,
+        plan_schemaless, validate,
+    },
+    store::{
+        AlterTable, CustomFunction, CustomFunctionMut, Index, IndexError, IndexMut, Metadata,
+        Planner, RowIter, Store, StoreMut, Transaction,
+    },
+};
+use serde::Deserialize;
+
+use super::{
+    assign_columns, field_column_index, field_value_input, is_empty, item_update, lazy_columns,
+    mount_csvs, node_id, option_text, Cache, Field, FieldIteration, FieldKind, FieldOption,
+    FieldType, Opt, Project, ProjectNextStorage, ITEMS_INDEX, LAZY_COLUMNS, PROJECT_TABLES,
+    RESERVED_COLUMNS,
+};
+use crate::{columns, prompt::Session, scratch::Scratch};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Fixture {
+    #[serde(default = "default_owner")]
+    owner: String,
+    #[serde(default = "default_number")]
+    number: i64,
+    #[serde(default)]
+    project: FixtureProject,
+    #[serde(default)]
+    fields: Vec<FixtureField>,
+    #[serde(default)]
+    items: Vec<FixtureItem>,
+}
+
+fn default_owner() -> String {
+    "mock".to_string()
+}
+
+fn default_number() -> i64 {
+    1
+}
+
+#[derive(Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct FixtureProject {
+    id: String,
+    title: String,
+    short_description: Option<String>,
+    readme: Option<String>,
+    public: bool,
+    closed: bool,
+}
+
+impl Default for FixtureProject {
+    fn default() -> Self {
+        Self {
+            id: "PVT_mock".to_string(),
+            title: "Mock".to_string(),
+            short_description: None,
+            readme: None,
+            public: false,
+            closed: false,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FixtureField {
+    id: String,
+    name: String,
+    /// `ProjectV2FieldType`, e.g. `TEXT`, `NUMBER`, `DATE`, `SINGLE_SELECT` or `ITERATION`
+    data_type: String,
+    #[serde(default)]
+    options: Vec<FixtureOption>,
+    #[serde(default)]
+    iterations: Vec<FixtureIteration>,
+}
+
+#[derive(Deserialize)]
+struct FixtureOption {
+    id: String,
+    name: String,
+    #[serde(default = "default_color")]
+    color: String,
+    #[serde(default)]
+    description: String,
+}
+
+fn default_color() -> String {
+    "GRAY".to_string()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FixtureIteration {
+    id: String,
+    title: String,
+    start_date: String,
+    duration: i64,
+    #[serde(default)]
+    completed: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FixtureMilestone {
+    due_on: Option<NaiveDate>,
+    state: Option<String>,
+}
+
+/// an issue or pull request if `repository` and `issue` are given, otherwise a draft issue
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FixtureItem {
+    id: String,
+    repository: Option<String>,
+    issue: Option<i64>,
+    title: String,
+    state: Option<String>,
+    #[serde(default)]
+    assignees: Vec<String>,
+    #[serde(default)]
+    labels: Vec<String>,
+    body: Option<String>,
+    #[serde(default)]
+    comments: i64,
+    #[serde(default)]
+    reactions: i64,
+    milestone: Option<FixtureMilestone>,
+    /// logins of the users of `Reviewers`
+    reviewer_users: Option<Vec<String>>,
+    /// names of the teams of `Reviewers`
+    reviewer_teams: Option<Vec<String>>,
+    closed_at: Option<DateTime<FixedOffset>>,
+    merged_at: Option<DateTime<FixedOffset>>,
+    last_comment: Option<String>,
+    timeline_updated_at: Option<DateTime<FixedOffset>>,
+    /// values by field name
+    #[serde(default)]
+    fields: HashMap<String, serde_json::Value>,
+}
+
+impl FixtureField {
+    fn into_field(self) -> Field {
+        let kind = match self.data_type.as_str() {
+            "SINGLE_SELECT" => FieldKind::SingleSelect(
+                self.options
+                    .into_iter()
+                    .map(|option| FieldOption {
+                        id: option.id,
+                        name: option.name,
+                        color: option.color,
+                        description: option.description,
+                    })
+                    .collect(),
+            ),
+            "ITERATION" => {
+                let (completed, iterations): (Vec<_>, Vec<_>) =
+                    self.iterations.into_iter().partition(|i| i.completed);
+                let into_iterations = |iterations: Vec<FixtureIteration>| {
+                    iterations
+                        .into_iter()
+                        .map(|iteration| FieldIteration {
+                            id: iteration.id,
+                            title: iteration.title,
+                            duration: iteration.duration,
+                            start_date: iteration.start_date,
+                        })
+                        .collect()
+                };
+                FieldKind::Iteration {
+                    duration: iterations.first().map_or(14, |i| i.duration),
+                    start_day: 1,
+                    iterations: into_iterations(iterations),
+                    completed_iterations: into_iterations(completed),
+                }
+            }
+            "ASSIGNEES" => FieldKind::Normal(FieldType::ASSIGNEES),
+            "DATE" => FieldKind::Normal(FieldType::DATE),
+            "LABELS" => FieldKind::Normal(FieldType::LABELS),
+            "LINKED_PULL_REQUESTS" => FieldKind::Normal(FieldType::LINKED_PULL_REQUESTS),
+            "MILESTONE" => FieldKind::Normal(FieldType::MILESTONE),
+            "NUMBER" => FieldKind::Normal(FieldType::NUMBER),
+            "REPOSITORY" => FieldKind::Normal(FieldType::REPOSITORY),
+            "REVIEWERS" => FieldKind::Normal(FieldType::REVIEWERS),
+            "TEXT" => FieldKind::Normal(FieldType::TEXT),
+            "TITLE" => FieldKind::Normal(FieldType::TITLE),
+            "TRACKED_BY" => FieldKind::Normal(FieldType::TRACKED_BY),
+            "TRACKS" => FieldKind::Normal(FieldType::TRACKS),
+            other => FieldKind::Normal(FieldType::Other(other.to_string())),
+        };
+        Field {
+            id: self.id,
+            name: self.name,
+            column: String::new(),
+            kind,
+        }
+    }
+}
+
+/// a timestamp in UTC, like those scanned from GitHub
+fn timestamp(value: Option<DateTime<FixedOffset>>) -> Value {
+    value.map_or(Value::Null, |t| Value::Timestamp(t.naive_utc()))
+}
+
+/// the value of `field` in the fixture as it is scanned from GitHub
+fn field_value(field: &Field, value: serde_json::Value) -> Result<Value> {
+    use serde_json::Value as Json;
+    let text = |value: Json| match value {
+        Json::String(s) => Ok(s),
+        value => Err(anyhow!("expected a string, got {}", value)),
+    };
+    if value.is_null() {
+        return Ok(Value::Null);
+    }
+    Ok(match &field.kind {
+        FieldKind::SingleSelect(options) => {
+            let name = text(value)?;
+            if !options.iter().any(|option| option.name == name) {
+                return Err(anyhow!("no option named {}", name));
+            }
+            Value::Str(name)
+        }
+        FieldKind::Iteration {
+            iterations,
+            completed_iterations,
+            ..
+        } => {
+            let title = text(value)?;
+            if !iterations
+                .iter()
+                .chain(completed_iterations)
+                .any(|i| i.title == title)
+            {
+                return Err(anyhow!("no iteration titled {}", title));
+            }
+            Value::Str(title)
+        }
+        FieldKind::Normal(FieldType::DATE) => {
+            let date = text(value)?;
+            Value::Date(NaiveDate::parse_from_str(&date, "%Y-%m-%d")?)
+        }
+        FieldKind::Normal(FieldType::NUMBER) => Value::F64(
+            value
+                .as_f64()
+                .ok_or_else(|| anyhow!("expected a number, got {}", value))?,
+        ),
+        FieldKind::Normal(_) => match value {
+            Json::Array(values) => Value::List(
+                values
+                    .into_iter()
+                    .map(|value| text(value).map(Value::Str))
+                    .collect::<Result<_>>()?,
+            ),
+            value => Value::Str(text(value)?),
+        },
+    })
+}
+
+/// a project read from a fixture instead of GitHub, behind the `mock` feature
+pub struct MockProjectStorage {
+    owner: String,
+    project_number: i64,
+    cache: Cache,
+    scratch: Scratch,
+    /// items and options inserted so far, numbering their ids
+    inserted: usize,
+    /// the project and scratch tables as they were at `BEGIN`, put back on `ROLLBACK`
+    transaction: Option<(Cache, Scratch)>,
+}
+
+impl MockProjectStorage {
+    pub fn load(path: &Path, opt: Opt) -> Result<Self> {
+        let content =
+            fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let fixture: Fixture = serde_json::from_slice(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        Self::from_fixture(fixture, opt)
+    }
+
+    fn from_fixture(fixture: Fixture, opt: Opt) -> Result<Self> {
+        let mut fields: Vec<_> = fixture
+            .fields
+            .into_iter()
+            .map(FixtureField::into_field)
+            .collect();
+        let reserved_columns = assign_columns(&mut fields, opt.snake_case_columns);
+        // fetched anyway, but left out of `SELECT *` like with GitHub
+        columns::set_lazy("items", lazy_columns(&reserved_columns));
+        let mut items = vec![];
+        for mut item in fixture.items {
+            let (repository, issue, state, comments, reactions) =
+                match (item.repository, item.issue) {
+                    (Some(repository), Some(issue)) => (
+                        Value::Str(repository),
+                        Value::I64(issue),
+                        Value::Str(item.state.unwrap_or_else(|| "OPEN".to_string())),
+                        Value::I64(item.comments),
+                        Value::I64(item.reactions),
+                    ),
+                    _ => (
+                        Value::Null,
+                        Value::Null,
+                        Value::Null,
+                        Value::Null,
+                        Value::Null,
+                    ),
+                };
+            let (milestone_due_on, milestone_state) = match item.milestone {
+                Some(milestone) => (
+                    milestone.due_on.map_or(Value::Null, Value::Date),
+                    Value::Str(milestone.state.unwrap_or_else(|| "OPEN".to_string())),
+                ),
+                None => (Value::Null, Value::Null),
+            };
+            let strings =
+                |values: Vec<String>| Value::List(values.into_iter().map(Value::Str).collect());
+            let mut row = vec![
+                Value::Str(item.id.clone()),
+                repository,
+                issue,
+                Value::Str(item.title),
+                strings(item.assignees),
+                strings(item.labels),
+                state,
+                item.body.map_or(Value::Null, Value::Str),
+                comments,
+                reactions,
+                milestone_due_on,
+                milestone_state,
+                item.reviewer_users.map_or(Value::Null, strings),
+                item.reviewer_teams.map_or(Value::Null, strings),
+                timestamp(item.closed_at),
+                timestamp(item.merged_at),
+                item.last_comment.map_or(Value::Null, Value::Str),
+                timestamp(item.timeline_updated_at),
+            ];
+            for field in &fields {
+                let value = item.fields.remove(&field.name).unwrap_or_default();
+                row.push(
+                    field_value(field, value)
+                        .with_context(|| format!("item {}, field {}", item.id, field.name))?,
+                );
+            }
+            if let Some(name) = item.fields.keys().next() {
+                return Err(anyhow!("item {}: no field named {}", item.id, name));
+            }
+            items.push((item.id, row));
+        }
+        let project = fixture.project;
+        let mut storage = Self {
+            owner: fixture.owner,
+            project_number: fixture.number,
+            cache: Cache {
+                project: Project {
+                    id: project.id,
+                    title: project.title,
+                    short_description: project.short_description,
+                    readme: project.readme,
+                    public: project.public,
+                    closed: project.closed,
+                },
+                reserved_columns,
+                fields,
+                items: Arc::new(items),
+                item_positions: BTreeMap::new(),
+                updated_at: HashMap::new(),
+                items_loaded: true,
+                scanned: true,
+                lazy_loaded: LAZY_COLUMNS.into_iter().collect(),
+                reactions: Some(Arc::default()),
+                item_events: Some(Arc::default()),
+                repositories: Some(vec![]),
+                issues: Some(Arc::default()),
+                pull_requests: Some(Arc::default()),
+                assignable_users: Some(Arc::default()),
+                labels: Some(Arc::default()),
+            },
+            scratch: mount_csvs(&opt.csv_mounts)?,
+            inserted: 0,
+            transaction: None,
+        };
+        storage.cache.index_items();
+        Ok(storage)
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn insert_items(&mut self, rows: Vec<Vec<Value>>) -> GlueSQLResult<()> {
+        let readonly = |cache: &Cache, idx: usize| cache.readonly(idx);
+        let (mut added, mut present) = (0, 0);
+        for mut values in rows {
+            let fields = values.split_off(RESERVED_COLUMNS.len());
+            for (field, value) in self.cache.fields.iter().zip(&fields) {
+                field_value_input(field, value)?;
+            }
+            let (repository, issue, title, state) = match values.as_slice() {
+                [Value::Null, Value::Str(repository), Value::I64(number), readonly_values @ ..] => {
+                    if let Some(idx) = readonly_values.iter().position(|value| !is_empty(value)) {
+                        return Err(readonly(&self.cache, 3 + idx));
+                    }
+                    if let Some(item_id) = self.cache.item_of(repository, *number) {
+                        let item_id = item_id.to_string();
+                        let idx = self.cache.item_position(&item_id).unwrap();
+                        let (_, row) = &mut Arc::make_mut(&mut self.cache.items)[idx];
+                        for (cell, value) in row[RESERVED_COLUMNS.len()..].iter_mut().zip(fields) {
+                            if !value.is_null() {
+                                *cell = value;
+                            }
+                        }
+                        present += 1;
+                        continue;
+                    }
+                    (
+                        Value::Str(repository.clone()),
+                        Value::I64(*number),
+                        Value::Str(format!("{}#{}", repository, number)),
+                        Value::Str("OPEN".to_string()),
+                    )
+                }
+                [Value::Null, Value::Null, Value::Null, Value::Str(title), readonly_values @ ..] => {
+                    if let Some(idx) = readonly_values.iter().position(|value| !is_empty(value)) {
+                        return Err(readonly(&self.cache, 4 + idx));
+                    }
+                    (
+                        Value::Null,
+                        Value::Null,
+                        Value::Str(title.clone()),
+                        Value::Null,
+                    )
+                }
+                [id, ..] if !id.is_null() => return Err(readonly(&self.cache, 0)),
+                _ => {
+                    return Err(GlueSQLError::StorageMsg(
+                        "either Repository and Issue, or Title is required".to_string(),
+                    ))
+                }
+            };
+            added += 1;
+            self.inserted += 1;
+            let item_id = format!("PVTI_mock{}", self.inserted);
+            let row = [
+                Value::Str(item_id.clone()),
+                repository,
+                issue,
+                title,
+                Value::List(vec![]),
+                Value::List(vec![]),
+                state,
+                Value::Null,
+                Value::Null,
+                Value::Null,
+                Value::Null,
+                Value::Null,
+                Value::Null,
+                Value::Null,
+                Value::Null,
+                Value::Null,
+            ]
+            .into_iter()
+            .chain(fields)
+            .collect();
+            Arc::make_mut(&mut self.cache.items).push((item_id, row));
+            self.cache.index_items();
+        }
+        if added + present > 0 {
+            eprintln!(
+                "{} item(s) added, {} already in the project",
+                added, present
+            );
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn update_items(&mut self, rows: Vec<(String, Vec<Value>)>) -> GlueSQLResult<()> {
+        for (item_id, mut new_row) in rows {
+            let Some(idx) = self.cache.item_position(&item_id) else {
+                continue;
+            };
+            let update = item_update(&self.cache, &self.cache.items[idx].1, &new_row)?;
+            if let Some((_, _, state)) = update.state {
+                new_row[6] = Value::Str(state);
+            }
+            Arc::make_mut(&mut self.cache.items)[idx].1 = new_row;
+        }
+        Ok(())
+    }
+
+    /// the single select field of `field_id`
+    #[allow(clippy::result_large_err)]
+    fn options_mut(&mut self, field_id: &str) -> GlueSQLResult<(usize, &mut Vec<FieldOption>)> {
+        self.cache
+            .fields
+            .iter_mut()
+            .enumerate()
+            .find_map(|(field_idx, field)| match &mut field.kind {
+                FieldKind::SingleSelect(options) if field.id == field_id => {
+                    Some((field_idx, options))
+                }
+                _ => None,
+            })
+            .ok_or_else(|| {
+                GlueSQLError::StorageMsg(format!("single select field not found: {}", field_id))
+            })
+    }
+
+    /// set the value of the single select field at `field_idx` from `old` to `new` in every item
+    fn rename_option(&mut self, field_idx: usize, old: &str, new: Value) {
+        for (_, row) in Arc::make_mut(&mut self.cache.items) {
+            let value = &mut row[field_column_index(field_idx)];
+            if matches!(value, Value::Str(name) if name == old) {
+                *value = new.clone();
+            }
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn insert_options(&mut self, rows: Vec<Vec<Value>>) -> GlueSQLResult<()> {
+        for values in rows {
+            let [Value::Str(field_id), id, Value::Str(name), color, description] =
+                values.as_slice()
+            else {
+                return Err(GlueSQLError::StorageMsg(
+                    "field_id and name are required".to_string(),
+                ));
+            };
+            if !id.is_null() {
+                return Err(GlueSQLError::StorageMsg("readonly column: id".to_string()));
+            }
+            let option_id = format!("mock{}", self.inserted + 1);
+            let (_, options) = self.options_mut(field_id)?;
+            options.push(FieldOption {
+                id: option_id,
+                name: name.clone(),
+                color: option_text(color, "GRAY").to_uppercase(),
+                description: option_text(description, ""),
+            });
+            self.inserted += 1;
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn update_options(&mut self, rows: Vec<(String, Vec<Value>)>) -> GlueSQLResult<()> {
+        for (option_id, values) in rows {
+            let [Value::Str(field_id), _, Value::Str(name), color, description] = values.as_slice()
+            else {
+                return Err(GlueSQLError::StorageMsg("name is required".to_string()));
+            };
+            let (field_idx, options) = self.options_mut(field_id)?;
+            let Some(option) = options.iter_mut().find(|option| option.id == option_id) else {
+                continue;
+            };
+            let old = std::mem::replace(&mut option.name, name.clone());
+            option.color = option_text(color, "GRAY").to_uppercase();
+            option.description = option_text(description, "");
+            self.rename_option(field_idx, &old, Value::Str(name.clone()));
+        }
+        Ok(())
+    }
+
+    fn delete_options(&mut self, keys: Vec<String>) {
+        for option_id in keys {
+            let Some((field_idx, option)) = self.cache.find_option(&option_id) else {
+                continue;
+            };
+            let name = option.name.clone();
+            if let FieldKind::SingleSelect(options) = &mut self.cache.fields[field_idx].kind {
+                options.retain(|option| option.id != option_id);
+            }
+            self.rename_option(field_idx, &name, Value::Null);
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn update_project(&mut self, rows: Vec<(String, Vec<Value>)>) -> GlueSQLResult<()> {
+        let text = |value: &Value| (!value.is_null()).then(|| String::from(value));
+        for (_, values) in rows {
+            let [id, Value::Str(title), short_description, readme, Value::Bool(public), Value::Bool(closed)] =
+                values.as_slice()
+            else {
+                return Err(GlueSQLError::StorageMsg(
+                    "title, public and closed are required".to_string(),
+                ));
+            };
+            let project = &mut self.cache.project;
+            if !matches!(id, Value::Str(id) if id == &project.id) {
+                return Err(GlueSQLError::StorageMsg("readonly column: id".to_string()));
+            }
+            project.title = title.clone();
+            project.short_description = text(short_description);
+            project.readme = text(readme);
+            project.public = *public;
+            project.closed = *closed;
+        }
+        Ok(())
+    }
+}
+
+/// the error of writes the mock doesn't support
+fn unsupported(operation: &str) -> GlueSQLError {
+    GlueSQLError::StorageMsg(format!(
+        "{} is not supported by the mock storage",
+        operation
+    ))
+}
+
+impl Store for MockProjectStorage {
+    fn fetch_schema(&self, table_name: &str) -> GlueSQLResult<Option<Schema>> {
+        Ok(match table_name {
+            "items" => Some(self.cache.items_schema()),
+            "options" => Some(ProjectNextStorage::options_schema()),
+            "iterations" => Some(ProjectNextStorage::iterations_schema()),
+            "project" => Some(ProjectNextStorage::project_schema()),
+            "reactions" => Some(ProjectNextStorage::reactions_schema()),
+            "item_events" => Some(ProjectNextStorage::item_events_schema()),
+            "issues" => Some(ProjectNextStorage::issues_schema()),
+            "pull_requests" => Some(ProjectNextStorage::pull_requests_schema()),
+            "assignable_users" => Some(ProjectNextStorage::assignable_users_schema()),
+            "labels" => Some(ProjectNextStorage::labels_schema()),
+            _ => self.scratch.schema(table_name),
+        })
+    }
+
+    fn fetch_all_schemas(&self) -> GlueSQLResult<Vec<Schema>> {
+        let mut schemas = vec![];
+        for table_name in PROJECT_TABLES {
+            schemas.extend(self.fetch_schema(table_name)?);
+        }
+        schemas.extend(self.scratch.tables().into_iter().map(|(schema, _)| schema));
+        Ok(schemas)
+    }
+
+    fn fetch_data(&self, table_name: &str, key: &Key) -> GlueSQLResult<Option<Vec<Value>>> {
+        for row in self.scan_data(table_name)? {
+            let (row_key, row) = row?;
+            if &row_key == key {
+                return Ok(Some(row));
+            }
+        }
+        Ok(None)
+    }
+
+    fn scan_data<'a>(&'a self, table_name: &str) -> GlueSQLResult<RowIter<'a>> {
+        match table_name {
+            "items" => Ok(self.cache.scan_items()),
+            "options" => Ok(self.cache.scan_options()),
+            "iterations" => Ok(self.cache.scan_iterations()),
+            "project" => Ok(self.cache.scan_project()),
+            "reactions" | "item_events" | "issues" | "pull_requests" | "assignable_users"
+            | "labels" => Ok(Box::new(std::iter::empty())),
+            _ => self.scratch.scan(table_name),
+        }
+    }
+}
+
+impl StoreMut for MockProjectStorage {
+    fn insert_schema(&mut self, schema: &Schema) -> GlueSQLResult<()> {
+        self.scratch.create(schema);
+        Ok(())
+    }
+
+    fn delete_schema(&mut self, table_name: &str) -> GlueSQLResult<()> {
+        if PROJECT_TABLES.contains(&table_name) {
+            return Err(GlueSQLError::StorageMsg(
+                "cannot drop project table".to_string(),
+            ));
+        }
+        self.scratch.drop(table_name)
+    }
+
+    fn append_data(&mut self, table_name: &str, rows: Vec<Vec<Value>>) -> GlueSQLResult<()> {
+        match table_name {
+            "items" => self.insert_items(rows),
+            "options" => self.insert_options(rows),
+            _ if PROJECT_TABLES.contains(&table_name) => {
+                Err(GlueSQLError::StorageMsg("readonly table".to_string()))
+            }
+            _ => self.scratch.insert(table_name, rows),
+        }
+    }
+
+    fn insert_data(&mut self, table_name: &str, rows: Vec<(Key, Vec<Value>)>) -> GlueSQLResult<()> {
+        if !PROJECT_TABLES.contains(&table_name) {
+            return self.scratch.update(table_name, rows);
+        }
+        let rows = rows
+            .into_iter()
+            .map(|(key, row)| Ok((node_id(key)?, row)))
+            .collect::<GlueSQLResult<Vec<_>>>()?;
+        match table_name {
+            "items" => self.update_items(rows),
+            "options" => self.update_options(rows),
+            "project" => self.update_project(rows),
+            _ => Err(GlueSQLError::StorageMsg("readonly table".to_string())),
+        }
+    }
+
+    fn delete_data(&mut self, table_name: &str, keys: Vec<Key>) -> GlueSQLResult<()> {
+        if !PROJECT_TABLES.contains(&table_name) {
+            return self.scratch.delete(table_name, keys);
+        }
+        let keys = keys
+            .into_iter()
+            .map(node_id)
+            .collect::<GlueSQLResult<Vec<_>>>()?;
+        match table_name {
+            "items" => {
+                Arc::make_mut(&mut self.cache.items).retain(|(item_id, _)| !keys.contains(item_id));
+                self.cache.index_items();
+                Ok(())
+            }
+            "options" => {
+                self.delete_options(keys);
+                Ok(())
+            }
+            _ => Err(GlueSQLError::StorageMsg("readonly table".to_string())),
+        }
+    }
+}
+
+impl AlterTable for MockProjectStorage {
+    fn rename_schema(&mut self, table_name: &str, new_table_name: &str) -> GlueSQLResult<()> {
+        if PROJECT_TABLES.contains(&table_name) || PROJECT_TABLES.contains(&new_table_name) {
+            return Err(GlueSQLError::StorageMsg(
+                "cannot rename project table".to_string(),
+            ));
+        }
+        self.scratch.rename(table_name, new_table_name)
+    }
+
+    fn rename_column(
+        &mut self,
+        table_name: &str,
+        old_column_name: &str,
+        new_column_name: &str,
+    ) -> GlueSQLResult<()> {
+        if PROJECT_TABLES.contains(&table_name) {
+            return Err(unsupported("ALTER TABLE of project tables"));
+        }
+        self.scratch
+            .rename_column(table_name, old_column_name, new_column_name)
+    }
+
+    fn add_column(&mut self, table_name: &str, column_def: &ColumnDef) -> GlueSQLResult<()> {
+        if PROJECT_TABLES.contains(&table_name) {
+            return Err(unsupported("ALTER TABLE of project tables"));
+        }
+        self.scratch.add_column(table_name, column_def)
+    }
+
+    fn drop_column(
+        &mut self,
+        table_name: &str,
+        column_name: &str,
+        if_exists: bool,
+    ) -> GlueSQLResult<()> {
+        if PROJECT_TABLES.contains(&table_name) {
+            return Err(unsupported("ALTER TABLE of project tables"));
+        }
+        self.scratch.drop_column(table_name, column_name, if_exists)
+    }
+}
+
+impl Transaction for MockProjectStorage {
+    /// writes outside of `BEGIN` apply right away, like with GitHub
+    fn begin(&mut self, autocommit: bool) -> GlueSQLResult<bool> {
+        if autocommit {
+            return Ok(false);
+        }
+        if self.transaction.is_some() {
+            return Err(GlueSQLError::StorageMsg(
+                "a transaction is already in progress".to_string(),
+            ));
+        }
+        self.transaction = Some((self.cache.clone(), self.scratch.clone()));
+        Ok(false)
+    }
+
+    fn rollback(&mut self) -> GlueSQLResult<()> {
+        let (cache, scratch) = self.transaction.take().ok_or_else(no_transaction)?;
+        self.cache = cache;
+        self.scratch = scratch;
+        Ok(())
+    }
+
+    fn commit(&mut self) -> GlueSQLResult<()> {
+        self.transaction.take().ok_or_else(no_transaction)?;
+        Ok(())
+    }
+}
+
+fn no_transaction() -> GlueSQLError {
+    GlueSQLError::StorageMsg("no transaction is in progress".to_string())
+}
+
+impl Index for MockProjectStorage {
+    fn scan_indexed_data<'a>(
+        &'a self,
+        table_name: &str,
+        index_name: &str,
+        asc: Option<bool>,
+        cmp_value: Option<(&IndexOperator, Value)>,
+    ) -> GlueSQLResult<RowIter<'a>> {
+        if (table_name, index_name) != ("items", ITEMS_INDEX) {
+            return Err(IndexError::IndexNameDoesNotExist(index_name.to_string()).into());
+        }
+        Ok(self.cache.scan_items_by_id(asc, cmp_value))
+    }
+}
+
+impl IndexMut for MockProjectStorage {
+    fn create_index(
+        &mut self,
+        _table_name: &str,
+        _index_name: &str,
+        _column: &OrderByExpr,
+    ) -> GlueSQLResult<()> {
+        Err(unsupported("CREATE INDEX"))
+    }
+
+    fn drop_index(&mut self, _table_name: &str, _index_name: &str) -> GlueSQLResult<()> {
+        Err(unsupported("DROP INDEX"))
+    }
+}
+
+impl Metadata for MockProjectStorage {}
+impl CustomFunction for MockProjectStorage {}
+impl CustomFunctionMut for MockProjectStorage {}
+
+impl Planner for MockProjectStorage {
+    fn plan(&self, statement: StatementPlan) -> GlueSQLResult<StatementPlan> {
+        let schema_map = fetch_schema_map(self, &statement)?;
+        validate(&schema_map, &statement)?;
+        let statement = plan_schemaless(&schema_map, statement)?;
+        let statement = plan_primary_key(&schema_map, statement);
+        let statement = plan_index(&schema_map, statement);
+        let statement = plan_hash_join(&schema_map, statement);
+        Ok(plan_aggregate(statement))
+    }
+}
+
+impl Session for MockProjectStorage {
+    fn connect(&mut self, _owner: String, _project_number: i64) -> Result<()> {
+        Err(anyhow!("The mock storage holds a single project"))
+    }
+
+    fn project(&self) -> (&str, i64) {
+        (&self.owner, self.project_number)
+    }
+
+    fn in_transaction(&self) -> bool {
+        self.transaction.is_some()
+    }
+
+    fn choices(&self, column: &str) -> Option<Vec<String>> {
+        self.cache.choices(column)
+    }
+}
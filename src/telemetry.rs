@@ -0,0 +1,117 @@
+use std::{io, str::FromStr};
+
+use anyhow::{anyhow, Error, Result};
+use tracing::Level;
+use tracing_subscriber::{
+    filter::Targets,
+    fmt::{self, format::FmtSpan},
+    prelude::*,
+};
+
+/// how finished spans are written to stderr
+#[derive(Debug, Clone, Copy)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            other => Err(anyhow!("Unknown log format: {}", other)),
+        }
+    }
+}
+
+pub struct Opt {
+    pub log: Option<LogFormat>,
+    /// base URL of an OTLP/HTTP collector, spans are posted to `/v1/traces`
+    #[cfg(feature = "otlp")]
+    pub otlp_endpoint: Option<String>,
+}
+
+/// kept by `run` until it returns, when the spans not exported yet are sent
+pub struct Telemetry {
+    #[cfg(feature = "otlp")]
+    provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+}
+
+#[cfg(feature = "otlp")]
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if let Some(provider) = self.provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Failed to export spans: {}", e);
+            }
+        }
+    }
+}
+
+/// log the spans of gh-sql (statements, GraphQL requests and fetches) with their duration
+/// when they close, and export them over OTLP. The spans of dependencies like reqwest are
+/// left out, including those of the export itself
+pub fn init(opt: Opt) -> Result<Telemetry> {
+    #[cfg(feature = "otlp")]
+    let provider = opt
+        .otlp_endpoint
+        .as_deref()
+        .map(otlp_provider)
+        .transpose()?;
+    #[cfg(feature = "otlp")]
+    let otlp = provider.as_ref().map(|provider| {
+        use opentelemetry::trace::TracerProvider as _;
+        tracing_opentelemetry::layer().with_tracer(provider.tracer("gh-sql"))
+    });
+    #[cfg(not(feature = "otlp"))]
+    let otlp: Option<tracing_subscriber::layer::Identity> = None;
+    let telemetry = Telemetry {
+        #[cfg(feature = "otlp")]
+        provider,
+    };
+    if opt.log.is_none() && otlp.is_none() {
+        return Ok(telemetry);
+    }
+    let text = matches!(opt.log, Some(LogFormat::Text)).then(|| {
+        fmt::layer()
+            .with_writer(io::stderr)
+            .with_span_events(FmtSpan::CLOSE)
+    });
+    let json = matches!(opt.log, Some(LogFormat::Json)).then(|| {
+        fmt::layer()
+            .json()
+            .with_writer(io::stderr)
+            .with_span_events(FmtSpan::CLOSE)
+    });
+    tracing_subscriber::registry()
+        .with(Targets::new().with_target("gh_sql", Level::TRACE))
+        .with(text)
+        .with(json)
+        .with(otlp)
+        .try_init()?;
+    Ok(telemetry)
+}
+
+/// a provider exporting spans in batches to `endpoint`, as the service `OTEL_SERVICE_NAME`
+/// or `gh-sql`. `OTEL_EXPORTER_OTLP_HEADERS` is sent with every export
+#[cfg(feature = "otlp")]
+fn otlp_provider(endpoint: &str) -> Result<opentelemetry_sdk::trace::SdkTracerProvider> {
+    use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+    use opentelemetry_sdk::{trace::SdkTracerProvider, Resource};
+
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+        .build()?;
+    let mut resource = Resource::builder();
+    if std::env::var_os("OTEL_SERVICE_NAME").is_none() {
+        resource = resource.with_service_name("gh-sql");
+    }
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(resource.build())
+        .build())
+}
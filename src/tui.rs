@@ -0,0 +1,678 @@
+use std::{cmp::Ordering, fmt::Debug, fs::File};
+
+use anyhow::{anyhow, Result};
+use gluesql::core::{
+    ast::DataType,
+    executor::Payload,
+    prelude::{Glue, Value},
+    store::{GStore, GStoreMut, Planner},
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    crossterm::{
+        event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+        execute,
+        terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+    },
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Cell, List, ListState, Paragraph, Row, Table, TableState},
+    Frame,
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    audit, browser,
+    output::{display_width, error_to_string, value_to_string},
+    prompt::Session,
+    sql,
+};
+
+/// the widest a column is drawn, longer values are cut with `…` and shown whole by Enter
+const MAX_COLUMN_WIDTH: usize = 40;
+
+/// the terminal, which is opened so that the statements can be piped in
+const TTY: &str = if cfg!(windows) { "CONOUT$" } else { "/dev/tty" };
+
+const HELP: &str = "↑↓←→ move  s sort  / filter  Enter row  e edit  o open  q quit";
+
+/// how the cells edited in [`browse`] are written to the project
+pub trait Edit {
+    /// the values a cell of the column of `items` is picked from, `None` if it is typed
+    fn choices(&self, column: &str) -> Option<Vec<String>>;
+    /// set the column of the item `id` to `input` with an `UPDATE` and read the value back
+    fn update(&mut self, id: &str, column: &str, input: &str) -> Result<Value>;
+}
+
+impl<S> Edit for Glue<S>
+where
+    S: GStore + GStoreMut + Planner + Session,
+{
+    fn choices(&self, column: &str) -> Option<Vec<String>> {
+        self.storage.choices(column)
+    }
+
+    fn update(&mut self, id: &str, column: &str, input: &str) -> Result<Value> {
+        let schema = self
+            .storage
+            .fetch_schema("items")
+            .map_err(|e| anyhow!("{}", e))?
+            .ok_or_else(|| anyhow!("No items table to edit"))?;
+        let data_type = schema
+            .column_defs
+            .iter()
+            .flatten()
+            .find(|def| def.name == column)
+            .map(|def| &def.data_type)
+            .ok_or_else(|| anyhow!("{} is not a column of items", column))?;
+        let value = match data_type {
+            _ if input.is_empty() => Value::Null,
+            DataType::Int => Value::I64(
+                input
+                    .parse()
+                    .map_err(|_| anyhow!("Not an integer: {}", input))?,
+            ),
+            DataType::Float => Value::F64(
+                input
+                    .parse()
+                    .map_err(|_| anyhow!("Not a number: {}", input))?,
+            ),
+            DataType::Boolean => Value::Bool(
+                input
+                    .to_lowercase()
+                    .parse()
+                    .map_err(|_| anyhow!("Not true or false: {}", input))?,
+            ),
+            // dates and the others are cast from text by GlueSQL
+            _ => Value::Str(input.to_string()),
+        };
+        let condition = audit::eq("id", &Value::Str(id.to_string()));
+        let statement = audit::update("items", &[(column, &value)], Some(&condition));
+        let payload =
+            sql::execute_str(self, &statement).map_err(|e| anyhow!("{}", error_to_string(e)))?;
+        if let Payload::Update(0) = payload {
+            return Err(anyhow!("No item of id {}", id));
+        }
+        let (labels, rows) =
+            sql::select(self, &format!("SELECT * FROM items WHERE {}", condition))?;
+        let idx = labels.iter().position(|label| label == column);
+        match (idx, rows.into_iter().next()) {
+            (Some(idx), Some(mut row)) => Ok(row.swap_remove(idx)),
+            _ => Err(anyhow!("The item {} was not found after the update", id)),
+        }
+    }
+}
+
+/// show a result as a table on the whole terminal until `q`: ↑↓←→ (or hjkl) move, `s`
+/// sorts by the selected column, `/` filters the rows, Enter shows the selected row with
+/// one value per line, `o` opens its issue or pull request in the browser and `e` edits
+/// the selected cell of an item, which needs the `id` column, with `edit`
+pub fn browse(labels: Vec<String>, rows: Vec<Vec<Value>>, edit: &mut dyn Edit) -> Result<()> {
+    let mut terminal = Terminal::enter()?;
+    let mut view = View::new(labels, rows);
+    loop {
+        terminal.0.draw(|frame| view.draw(frame))?;
+        // a resize is drawn again as well
+        let Event::Key(event) = event::read()? else {
+            continue;
+        };
+        let Some(key) = Key::of(event) else {
+            continue;
+        };
+        let size = terminal.0.size()?;
+        if !view.handle(key, (size.width as usize, size.height as usize), edit) {
+            return Ok(());
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Enter,
+    Escape,
+    Backspace,
+    Char(char),
+}
+
+impl Key {
+    /// the key of a press, `None` for the releases some terminals report too and the keys
+    /// which do nothing
+    fn of(event: KeyEvent) -> Option<Self> {
+        if event.kind == KeyEventKind::Release {
+            return None;
+        }
+        Some(match event.code {
+            KeyCode::Up => Key::Up,
+            KeyCode::Down => Key::Down,
+            KeyCode::Left => Key::Left,
+            KeyCode::Right => Key::Right,
+            KeyCode::PageUp => Key::PageUp,
+            KeyCode::PageDown => Key::PageDown,
+            KeyCode::Home => Key::Home,
+            KeyCode::End => Key::End,
+            KeyCode::Enter => Key::Enter,
+            KeyCode::Esc => Key::Escape,
+            KeyCode::Backspace => Key::Backspace,
+            KeyCode::Char('c') if event.modifiers.contains(KeyModifiers::CONTROL) => {
+                Key::Char('\u{3}')
+            }
+            KeyCode::Char(c) => Key::Char(c),
+            _ => return None,
+        })
+    }
+}
+
+/// what the keys act on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Table,
+    /// the selected row with one value per line, scrolled down by the number of lines
+    Row(usize),
+    /// typing the filter
+    Filter,
+    /// picking the value of the selected cell, the index of `choices` under the cursor
+    Pick(usize),
+    /// typing the value of the selected cell into `input`
+    Input,
+}
+
+/// a result and how it is shown
+struct View {
+    labels: Vec<String>,
+    rows: Vec<Vec<Value>>,
+    /// the width of every column, up to [`MAX_COLUMN_WIDTH`]
+    widths: Vec<usize>,
+    /// indexes of the rows which match the filter, in the order of the sort
+    shown: Vec<usize>,
+    /// the selected row, an index of `shown`, and column
+    row: usize,
+    column: usize,
+    /// the first row and column on the screen
+    top: usize,
+    left: usize,
+    /// the sorted column and whether it is descending
+    sort: Option<(usize, bool)>,
+    /// rows without a value containing it, ignoring case, are hidden
+    filter: String,
+    mode: Mode,
+    /// what the selected cell can be set to in [`Mode::Pick`], the first one clears it
+    choices: Vec<String>,
+    input: String,
+    /// shown in the status line until the next key
+    message: Option<String>,
+}
+
+impl View {
+    fn new(labels: Vec<String>, rows: Vec<Vec<Value>>) -> Self {
+        let mut view = Self {
+            widths: vec![],
+            shown: (0..rows.len()).collect(),
+            labels,
+            rows,
+            row: 0,
+            column: 0,
+            top: 0,
+            left: 0,
+            sort: None,
+            filter: String::new(),
+            mode: Mode::Table,
+            choices: vec![],
+            input: String::new(),
+            message: None,
+        };
+        view.measure();
+        view
+    }
+
+    fn measure(&mut self) {
+        self.widths = self
+            .labels
+            .iter()
+            .enumerate()
+            .map(|(idx, label)| {
+                self.rows
+                    .iter()
+                    .map(|row| display_width(&cell(&row[idx])))
+                    .fold(display_width(label), usize::max)
+                    .min(MAX_COLUMN_WIDTH)
+            })
+            .collect();
+    }
+
+    /// filter and sort the rows again, keeping the selected row if it is still shown
+    fn refresh(&mut self) {
+        let selected = self.shown.get(self.row).copied();
+        let filter = self.filter.to_lowercase();
+        self.shown = (0..self.rows.len())
+            .filter(|&idx| {
+                filter.is_empty()
+                    || self.rows[idx]
+                        .iter()
+                        .any(|value| cell(value).to_lowercase().contains(&filter))
+            })
+            .collect();
+        if let Some((column, descending)) = self.sort {
+            let rows = &self.rows;
+            // NULLs last either way, the sort is stable for equal values
+            self.shown
+                .sort_by(|&a, &b| match (&rows[a][column], &rows[b][column]) {
+                    (Value::Null, Value::Null) => Ordering::Equal,
+                    (Value::Null, _) => Ordering::Greater,
+                    (_, Value::Null) => Ordering::Less,
+                    (a, b) => {
+                        let ordering = a
+                            .evaluate_cmp(b)
+                            .unwrap_or_else(|| value_to_string(a).cmp(&value_to_string(b)));
+                        if descending {
+                            ordering.reverse()
+                        } else {
+                            ordering
+                        }
+                    }
+                });
+        }
+        self.row = selected
+            .and_then(|selected| self.shown.iter().position(|&idx| idx == selected))
+            .unwrap_or(0);
+    }
+
+    /// act on a key, `false` to quit
+    fn handle(&mut self, key: Key, (_, height): (usize, usize), edit: &mut dyn Edit) -> bool {
+        self.message = None;
+        let page = height.saturating_sub(2).max(1);
+        match self.mode {
+            Mode::Pick(choice) => match key {
+                Key::Up | Key::Char('k') => self.mode = Mode::Pick(choice.saturating_sub(1)),
+                Key::Down | Key::Char('j') => {
+                    self.mode = Mode::Pick((choice + 1).min(self.choices.len() - 1))
+                }
+                Key::Enter => {
+                    self.mode = Mode::Table;
+                    let input = self.choices[choice].clone();
+                    self.update(edit, &input);
+                }
+                Key::Escape | Key::Char('q') => self.mode = Mode::Table,
+                _ => {}
+            },
+            Mode::Input => match key {
+                Key::Enter => {
+                    self.mode = Mode::Table;
+                    let input = std::mem::take(&mut self.input);
+                    self.update(edit, input.trim());
+                }
+                Key::Escape => self.mode = Mode::Table,
+                Key::Backspace => {
+                    self.input.pop();
+                }
+                Key::Char(c) if !c.is_control() => self.input.push(c),
+                _ => {}
+            },
+            Mode::Filter => match key {
+                Key::Enter => self.mode = Mode::Table,
+                Key::Escape => {
+                    self.filter.clear();
+                    self.mode = Mode::Table;
+                    self.refresh();
+                }
+                Key::Backspace => {
+                    self.filter.pop();
+                    self.refresh();
+                }
+                Key::Char(c) if !c.is_control() => {
+                    self.filter.push(c);
+                    self.refresh();
+                }
+                _ => {}
+            },
+            Mode::Row(scroll) => match key {
+                Key::Up | Key::Char('k') => self.mode = Mode::Row(scroll.saturating_sub(1)),
+                Key::Down | Key::Char('j') => self.mode = Mode::Row(scroll + 1),
+                Key::Char('o') => self.open(),
+                Key::Char('q') | Key::Enter | Key::Escape | Key::Backspace => {
+                    self.mode = Mode::Table
+                }
+                _ => {}
+            },
+            Mode::Table => match key {
+                Key::Up | Key::Char('k') => self.row = self.row.saturating_sub(1),
+                Key::Down | Key::Char('j') => self.row += 1,
+                Key::Left | Key::Char('h') => self.column = self.column.saturating_sub(1),
+                Key::Right | Key::Char('l') => self.column += 1,
+                Key::PageUp => self.row = self.row.saturating_sub(page),
+                Key::PageDown | Key::Char(' ') => self.row += page,
+                Key::Home | Key::Char('g') => self.row = 0,
+                Key::End | Key::Char('G') => self.row = self.shown.len(),
+                Key::Enter if !self.shown.is_empty() => self.mode = Mode::Row(0),
+                Key::Char('/') => self.mode = Mode::Filter,
+                Key::Char('s') if !self.labels.is_empty() => {
+                    // ascending, descending, then as the result was
+                    self.sort = match self.sort {
+                        Some((column, false)) if column == self.column => Some((column, true)),
+                        Some((column, true)) if column == self.column => None,
+                        _ => Some((self.column, false)),
+                    };
+                    self.refresh();
+                }
+                Key::Char('o') => self.open(),
+                Key::Char('e') => self.start_edit(edit),
+                Key::Char('q') | Key::Char('\u{3}') | Key::Escape => return false,
+                _ => {}
+            },
+        }
+        self.row = self.row.min(self.shown.len().saturating_sub(1));
+        self.column = self.column.min(self.labels.len().saturating_sub(1));
+        true
+    }
+
+    /// the id of the selected item, to edit its cells
+    fn selected_id(&self) -> Result<String> {
+        let idx = self
+            .shown
+            .get(self.row)
+            .ok_or_else(|| anyhow!("No row to edit"))?;
+        let id = self.labels.iter().position(|label| label == "id");
+        match id.map(|id| &self.rows[*idx][id]) {
+            Some(Value::Str(id)) => Ok(id.clone()),
+            _ => Err(anyhow!("Select the id column of items to edit")),
+        }
+    }
+
+    /// pick the value of a single select or iteration field, type the others
+    fn start_edit(&mut self, edit: &dyn Edit) {
+        if let Err(e) = self.selected_id() {
+            self.message = Some(e.to_string());
+            return;
+        }
+        let current = cell(&self.rows[self.shown[self.row]][self.column]);
+        match edit.choices(&self.labels[self.column]) {
+            Some(choices) => {
+                self.choices = vec![String::new()];
+                self.choices.extend(choices);
+                let choice = self.choices.iter().position(|choice| choice == &current);
+                self.mode = Mode::Pick(choice.unwrap_or(0));
+            }
+            None => {
+                self.input = current;
+                self.mode = Mode::Input;
+            }
+        }
+    }
+
+    fn update(&mut self, edit: &mut dyn Edit, input: &str) {
+        let (idx, column) = (self.shown[self.row], self.column);
+        let result = self
+            .selected_id()
+            .and_then(|id| Ok((edit.update(&id, &self.labels[column], input)?, id)));
+        match result {
+            Ok((value, id)) => {
+                self.rows[idx][column] = value;
+                self.measure();
+                self.refresh();
+                self.message = Some(format!("Set {} of {}", self.labels[column], id));
+            }
+            Err(e) => self.message = Some(format!("{:#}", e)),
+        }
+    }
+
+    fn open(&mut self) {
+        let Some(&idx) = self.shown.get(self.row) else {
+            return;
+        };
+        let urls = browser::urls(&self.labels, &self.rows[idx..=idx]);
+        if let Err(e) = browser::open(&urls, true) {
+            self.message = Some(format!("{:#}", e));
+        }
+    }
+
+    /// the whole screen, the status line at the bottom
+    fn draw(&mut self, frame: &mut Frame) {
+        let [body, status] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        match self.mode {
+            Mode::Row(scroll) => self.draw_row(frame, body, scroll),
+            Mode::Pick(choice) => self.draw_choices(frame, body, choice),
+            Mode::Table | Mode::Filter | Mode::Input => self.draw_table(frame, body),
+        }
+        let text = truncate(&self.status(), status.width as usize);
+        frame.render_widget(Paragraph::new(text).reversed(), status);
+    }
+
+    fn status(&self) -> String {
+        if let Some(message) = &self.message {
+            return format!(" {}", message);
+        }
+        match self.mode {
+            Mode::Filter => return format!(" /{}", self.filter),
+            Mode::Input => {
+                return format!(
+                    " {} = {}▏  |  Enter set  Esc cancel  empty clears",
+                    self.labels[self.column], self.input
+                )
+            }
+            Mode::Pick(_) => return " ↑↓ choose  Enter set  Esc cancel".to_string(),
+            Mode::Table | Mode::Row(_) => {}
+        }
+        let mut status = format!(
+            " {}/{}",
+            (self.row + 1).min(self.shown.len()),
+            self.shown.len()
+        );
+        if self.shown.len() != self.rows.len() {
+            status.push_str(&format!(" of {}", self.rows.len()));
+        }
+        status.push_str(" rows");
+        if !self.filter.is_empty() {
+            status.push_str(&format!("  /{}", self.filter));
+        }
+        if let Some((column, descending)) = self.sort {
+            let arrow = if descending { "↓" } else { "↑" };
+            status.push_str(&format!("  {} {}", self.labels[column], arrow));
+        }
+        format!("{}  |  {}", status, HELP)
+    }
+
+    fn draw_table(&mut self, frame: &mut Frame, area: Rect) {
+        let width = area.width as usize;
+        let rows = (area.height as usize).saturating_sub(1).max(1);
+        // scroll to the selected row and column
+        self.top = self
+            .top
+            .min(self.row)
+            .max((self.row + 1).saturating_sub(rows));
+        self.left = self.left.min(self.column);
+        while self.left < self.column && self.right_edge(self.left, self.column) > width {
+            self.left += 1;
+        }
+        let mut columns = vec![];
+        let mut used = 0;
+        for column in self.left..self.labels.len() {
+            if !columns.is_empty() && used + self.widths[column] > width {
+                break;
+            }
+            columns.push(column);
+            used += self.widths[column] + 3;
+        }
+        // a column of `│` between every two, with a space on either side
+        let row = |text: &dyn Fn(usize) -> String| {
+            Row::new(columns.iter().enumerate().flat_map(|(n, &column)| {
+                let separator = (n > 0).then(|| Cell::from("│"));
+                let text = truncate(&text(column), self.widths[column]);
+                separator.into_iter().chain([Cell::from(text)])
+            }))
+        };
+        let widths = columns.iter().enumerate().flat_map(|(n, &column)| {
+            let separator = (n > 0).then_some(Constraint::Length(1));
+            separator
+                .into_iter()
+                .chain([Constraint::Length(self.widths[column] as u16)])
+        });
+        let header = row(&|column| self.labels[column].clone());
+        let body = self
+            .shown
+            .iter()
+            .skip(self.top)
+            .take(rows)
+            .map(|&idx| row(&|column| cell(&self.rows[idx][column])));
+        let table = Table::new(body, widths)
+            .header(header.style(Style::new().bold().underlined()))
+            .column_spacing(1)
+            .row_highlight_style(Style::new().reversed())
+            .cell_highlight_style(Style::new().underlined());
+        let selected = columns.iter().position(|&column| column == self.column);
+        let mut state = TableState::default()
+            .with_selected((!self.shown.is_empty()).then(|| self.row - self.top))
+            .with_selected_column(selected.map(|position| position * 2));
+        frame.render_stateful_widget(table, area, &mut state);
+    }
+
+    /// where the selected column ends if the columns are drawn from `left`
+    fn right_edge(&self, left: usize, column: usize) -> usize {
+        self.widths[left..=column]
+            .iter()
+            .map(|width| width + 3)
+            .sum::<usize>()
+            - 3
+    }
+
+    fn draw_choices(&self, frame: &mut Frame, area: Rect, choice: usize) {
+        let [title, list] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).areas(area);
+        let id = self.selected_id().unwrap_or_default();
+        let text = format!("{} of {}", self.labels[self.column], id);
+        frame.render_widget(Paragraph::new(text).bold(), title);
+        let items = self.choices.iter().map(|text| {
+            let text = if text.is_empty() { "(empty)" } else { text };
+            truncate(&format!("  {}", text), list.width as usize)
+        });
+        let list_widget = List::new(items).highlight_style(Style::new().reversed());
+        let mut state = ListState::default().with_selected(Some(choice));
+        frame.render_stateful_widget(list_widget, list, &mut state);
+    }
+
+    fn draw_row(&self, frame: &mut Frame, area: Rect, scroll: usize) {
+        let row = &self.rows[self.shown[self.row]];
+        let label_width = self
+            .labels
+            .iter()
+            .map(|label| display_width(label))
+            .max()
+            .unwrap_or(0);
+        let value_width = (area.width as usize).saturating_sub(label_width + 3).max(1);
+        let mut lines = vec![];
+        for (label, value) in self.labels.iter().zip(row) {
+            let text = value_to_string(value);
+            let mut wrapped = text
+                .lines()
+                .flat_map(|line| wrap(line, value_width))
+                .peekable();
+            if wrapped.peek().is_none() {
+                lines.push(Line::from(pad(label, label_width).bold()));
+            }
+            for (n, part) in wrapped.enumerate() {
+                let label = if n == 0 { label.as_str() } else { "" };
+                lines.push(Line::from(vec![
+                    pad(label, label_width).bold(),
+                    Span::raw(" │ "),
+                    Span::raw(part),
+                ]));
+            }
+        }
+        let body = area.height as usize;
+        let scroll = scroll.min(lines.len().saturating_sub(body));
+        let lines: Vec<_> = lines.into_iter().skip(scroll).take(body).collect();
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+}
+
+/// a value as drawn in a cell, on a single line
+fn cell(value: &Value) -> String {
+    value_to_string(value)
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect()
+}
+
+/// cut `s` to `width`, ending with `…` if anything was cut
+fn truncate(s: &str, width: usize) -> String {
+    if display_width(s) <= width {
+        return s.to_string();
+    }
+    let mut truncated = String::new();
+    let mut used = 0;
+    for grapheme in s.graphemes(true) {
+        let grapheme_width = display_width(grapheme);
+        if used + grapheme_width + 1 > width {
+            break;
+        }
+        truncated.push_str(grapheme);
+        used += grapheme_width;
+    }
+    if width > 0 {
+        truncated.push('…');
+    }
+    truncated
+}
+
+fn pad(s: &str, width: usize) -> String {
+    format!(
+        "{}{:pad$}",
+        s,
+        "",
+        pad = width.saturating_sub(display_width(s))
+    )
+}
+
+/// split `line` into lines of up to `width`
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    let mut used = 0;
+    for grapheme in line.graphemes(true) {
+        let grapheme_width = display_width(grapheme);
+        if used + grapheme_width > width && used > 0 {
+            lines.push(String::new());
+            used = 0;
+        }
+        lines.last_mut().unwrap().push_str(grapheme);
+        used += grapheme_width;
+    }
+    lines
+}
+
+/// the terminal in raw mode on its alternate screen, restored when dropped
+struct Terminal(ratatui::Terminal<CrosstermBackend<File>>);
+
+impl Terminal {
+    fn enter() -> Result<Self> {
+        let tty = File::options()
+            .read(true)
+            .write(true)
+            .open(TTY)
+            .map_err(|e| anyhow!("--tui needs a terminal: {}", e))?;
+        let mut backend = CrosstermBackend::new(tty);
+        terminal::enable_raw_mode()?;
+        if let Err(e) = execute!(backend, EnterAlternateScreen) {
+            let _ = terminal::disable_raw_mode();
+            return Err(e.into());
+        }
+        let mut terminal = Self(ratatui::Terminal::new(backend)?);
+        terminal.0.hide_cursor()?;
+        Ok(terminal)
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = execute!(self.0.backend_mut(), LeaveAlternateScreen);
+        let _ = self.0.show_cursor();
+        let _ = terminal::disable_raw_mode();
+    }
+}
@@ -0,0 +1,78 @@
+use anyhow::{anyhow, Error, Result};
+
+use crate::{
+    journal::{Entry, Journal},
+    output,
+    storage::{PartialFailure, ProjectNextStorage},
+};
+
+pub struct Opt {
+    pub journal: Journal,
+    pub owner: String,
+    pub project_number: i64,
+    pub batch_size: usize,
+}
+
+pub struct Undo {
+    opt: Opt,
+    storage: ProjectNextStorage,
+}
+
+impl Undo {
+    pub fn new(opt: Opt, storage: ProjectNextStorage) -> Self {
+        Self { opt, storage }
+    }
+
+    /// revert the latest changes recorded for the project, keeping the ones which failed
+    /// in the journal
+    pub fn run(&mut self) -> Result<()> {
+        let Opt {
+            journal,
+            owner,
+            project_number,
+            batch_size,
+        } = &self.opt;
+        let Some((n, entry)) = journal.last(owner, *project_number)? else {
+            return Err(anyhow!(
+                "No changes of {}/{} are recorded",
+                owner,
+                project_number
+            ));
+        };
+        eprintln!(
+            "Reverting {} change(s) made at {}",
+            entry.changes.len(),
+            entry.at
+        );
+        let results = self.storage.undo(&entry.changes, *batch_size)?;
+        let (mut failed, mut failures) = (vec![], vec![]);
+        for (change, result) in entry.changes.iter().zip(results) {
+            if let Some(e) = result {
+                if output::error_format() == output::ErrorFormat::Text {
+                    eprintln!("Failed: {} {}: {}", change.id, change.field, e);
+                }
+                failed.push(change.clone());
+                failures.push((change.id.clone(), change.field.clone(), e));
+            }
+        }
+        let applied = entry.changes.len() - failed.len();
+        eprintln!("Reverted {} of {} change(s)", applied, entry.changes.len());
+        if failed.is_empty() {
+            return journal.replace(n, None);
+        }
+        let count = failed.len();
+        journal.replace(
+            n,
+            Some(&Entry {
+                changes: failed,
+                ..entry
+            }),
+        )?;
+        Err(
+            Error::new(PartialFailure { applied, failures }).context(format!(
+                "{} change(s) could not be reverted and are kept in the journal",
+                count
+            )),
+        )
+    }
+}
@@ -0,0 +1,92 @@
+#![cfg(all(feature = "mock", unix))]
+
+use std::{fs, path::PathBuf};
+
+use gh_sql::{
+    hook::{self, Hook},
+    sql,
+    storage::{mock::MockProjectStorage, ColumnOrder, OnError, Opt},
+};
+use gluesql::core::prelude::Glue;
+
+fn load() -> Glue<MockProjectStorage> {
+    let opt = Opt {
+        snake_case_columns: false,
+        csv_mounts: vec![],
+        yes: true,
+        interactive: false,
+        force: false,
+        on_error: OnError::Abort,
+        journal: None,
+        audit: None,
+        column_order: ColumnOrder::Api,
+        checkpoints: None,
+        truncate_lists: false,
+    };
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixture.json");
+    Glue::new(MockProjectStorage::load(&fixture, opt).unwrap())
+}
+
+fn run(glue: &mut Glue<MockProjectStorage>, sql: &str) {
+    for statement in sql::parse_all(sql).unwrap() {
+        sql::execute(glue, &statement).unwrap();
+    }
+}
+
+/// the `text` of every summary the hook was given so far
+fn summaries(path: &PathBuf) -> Vec<String> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return vec![];
+    };
+    content
+        .lines()
+        .map(|line| {
+            let summary: serde_json::Value = serde_json::from_str(line).unwrap();
+            summary["text"].as_str().unwrap().to_string()
+        })
+        .collect()
+}
+
+/// the hook is set once per process, so this is the only test of this file
+#[test]
+fn hook_runs_on_commit() {
+    let path = std::env::temp_dir().join(format!("gh-sql-{}-hook.jsonl", std::process::id()));
+    let _ = fs::remove_file(&path);
+    let command = format!("cat >> '{}'; echo >> '{}'", path.display(), path.display());
+    hook::set(Hook::Command(command), "octo-org/1".to_string());
+    let mut glue = load();
+
+    run(
+        &mut glue,
+        "UPDATE items SET Status = 'Done' WHERE id = 'I1';",
+    );
+    assert_eq!(
+        summaries(&path),
+        ["UPDATE of 1 row(s) of items in octo-org/1"]
+    );
+
+    // scratch tables aren't the project's
+    run(
+        &mut glue,
+        "CREATE TABLE notes (text TEXT); INSERT INTO notes VALUES ('a');",
+    );
+    assert_eq!(summaries(&path).len(), 1);
+
+    run(
+        &mut glue,
+        "BEGIN; DELETE FROM items WHERE id = 'I3'; ROLLBACK;",
+    );
+    assert_eq!(summaries(&path).len(), 1);
+
+    run(
+        &mut glue,
+        "BEGIN; UPDATE items SET Estimate = 2 WHERE Estimate IS NOT NULL;",
+    );
+    assert_eq!(summaries(&path).len(), 1);
+    run(&mut glue, "COMMIT;");
+    assert_eq!(
+        summaries(&path)[1..],
+        ["UPDATE of 2 row(s) of items in octo-org/1"]
+    );
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,208 @@
+#![cfg(feature = "mock")]
+
+use std::{fs, path::PathBuf};
+
+use gh_sql::{
+    output::error_to_string,
+    prompt::Session,
+    sql,
+    storage::{mock::MockProjectStorage, ColumnOrder, OnError, Opt},
+};
+use gluesql::core::prelude::{Glue, Value};
+
+fn load() -> Glue<MockProjectStorage> {
+    let opt = Opt {
+        snake_case_columns: false,
+        csv_mounts: vec![],
+        yes: true,
+        interactive: false,
+        force: false,
+        on_error: OnError::Abort,
+        journal: None,
+        audit: None,
+        column_order: ColumnOrder::Api,
+        checkpoints: None,
+        truncate_lists: false,
+    };
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixture.json");
+    Glue::new(MockProjectStorage::load(&fixture, opt).unwrap())
+}
+
+/// run every statement in `sql`, failing with the error of the first which fails
+fn run(glue: &mut Glue<MockProjectStorage>, sql: &str) -> Result<(), String> {
+    for statement in sql::parse_all(sql).map_err(error_to_string)? {
+        sql::execute(glue, &statement).map_err(error_to_string)?;
+    }
+    Ok(())
+}
+
+/// the rows of a query, their values as strings
+fn select(glue: &mut Glue<MockProjectStorage>, sql: &str) -> Vec<Vec<String>> {
+    let (_, rows) = sql::select(glue, sql).unwrap();
+    rows.into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|value| match value {
+                    Value::Str(s) => s,
+                    Value::Null => "NULL".to_string(),
+                    value => format!("{:?}", value),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn ids(glue: &mut Glue<MockProjectStorage>, condition: &str) -> Vec<String> {
+    let sql = format!("SELECT id FROM items WHERE {} ORDER BY id", condition);
+    select(glue, &sql).into_iter().flatten().collect()
+}
+
+/// a path in the temporary directory which doesn't exist yet
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("gh-sql-{}-{}", std::process::id(), name));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn update_items() {
+    let mut glue = load();
+    run(
+        &mut glue,
+        "UPDATE items SET Status = 'Done', Estimate = 5 WHERE id = 'I1';",
+    )
+    .unwrap();
+    assert_eq!(
+        select(&mut glue, "SELECT Status FROM items WHERE id = 'I1'"),
+        [["Done"]]
+    );
+    let error = run(
+        &mut glue,
+        "UPDATE items SET Status = 'Doing' WHERE id = 'I1';",
+    );
+    assert!(error.is_err());
+}
+
+#[test]
+fn gh_filter() {
+    let mut glue = load();
+    assert_eq!(ids(&mut glue, "GH_FILTER('status:Todo')"), ["I1"]);
+    assert_eq!(ids(&mut glue, "GH_FILTER('-status:Todo')"), ["I2", "I3"]);
+    assert_eq!(
+        ids(&mut glue, "GH_FILTER('assignee:alice label:docs')"),
+        ["I2"]
+    );
+    assert_eq!(ids(&mut glue, "GH_FILTER('is:draft')"), ["I3"]);
+    assert_eq!(ids(&mut glue, "GH_FILTER('no:status')"), ["I3"]);
+    assert_eq!(ids(&mut glue, "GH_FILTER('login')"), ["I1"]);
+}
+
+#[test]
+fn list_functions() {
+    let mut glue = load();
+    assert_eq!(ids(&mut glue, "HAS(Assignees, 'alice')"), ["I1", "I2"]);
+    assert_eq!(ids(&mut glue, "HAS(Assignees, 'bob')"), ["I2"]);
+    assert_eq!(ids(&mut glue, "ANY_MATCH(Labels, '^f')"), ["I1"]);
+    assert!(ids(&mut glue, "ANY_MATCH(Labels, '^x')").is_empty());
+    assert_eq!(ids(&mut glue, "LIST_LEN(Labels) = 2"), ["I1"]);
+    let error = run(
+        &mut glue,
+        "SELECT id FROM items WHERE ANY_MATCH(Labels, Title);",
+    );
+    assert!(error.unwrap_err().contains("string literal pattern"));
+}
+
+#[test]
+fn copy_round_trip() {
+    let mut glue = load();
+    let path = temp_path("copy_round_trip.csv");
+    let copy_to = format!(
+        "COPY (SELECT id, Title FROM items ORDER BY id) TO '{}';",
+        path.display()
+    );
+    run(&mut glue, &copy_to).unwrap();
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "id,Title\nI1,Fix login\nI2,Write docs\nI3,Idea: dark mode\n"
+    );
+    // the file is kept without OVERWRITE
+    let error = run(&mut glue, &copy_to).unwrap_err();
+    assert!(error.contains("OVERWRITE"));
+
+    fs::write(&path, "Title\nFirst draft\n\"Second, draft\"\n").unwrap();
+    run(
+        &mut glue,
+        &format!("COPY items (Title) FROM '{}';", path.display()),
+    )
+    .unwrap();
+    assert_eq!(
+        select(
+            &mut glue,
+            "SELECT Title FROM items WHERE Repository IS NULL ORDER BY Title"
+        ),
+        [["First draft"], ["Idea: dark mode"], ["Second, draft"]]
+    );
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn copy_to_needs_a_query() {
+    let mut glue = load();
+    let path = temp_path("copy_to_needs_a_query.csv");
+    let error = run(
+        &mut glue,
+        &format!(
+            "COPY (DELETE FROM items WHERE id = 'I1') TO '{}';",
+            path.display()
+        ),
+    )
+    .unwrap_err();
+    assert!(error.contains("COPY TO expects a query"));
+    assert!(!path.exists());
+}
+
+#[test]
+fn rollback() {
+    let mut glue = load();
+    run(&mut glue, "BEGIN;").unwrap();
+    assert!(glue.storage.in_transaction());
+    run(
+        &mut glue,
+        "UPDATE items SET Status = 'Done' WHERE id = 'I1';
+         DELETE FROM items WHERE id = 'I2';
+         CREATE TABLE notes (text TEXT);
+         INSERT INTO notes VALUES ('gone');",
+    )
+    .unwrap();
+    assert_eq!(ids(&mut glue, "Status = 'Done'"), ["I1"]);
+    run(&mut glue, "ROLLBACK;").unwrap();
+    assert!(!glue.storage.in_transaction());
+    assert_eq!(ids(&mut glue, "Status = 'Done'"), ["I2"]);
+    assert!(run(&mut glue, "SELECT * FROM notes;").is_err());
+}
+
+#[test]
+fn commit() {
+    let mut glue = load();
+    run(
+        &mut glue,
+        "BEGIN;
+         UPDATE items SET Estimate = 8 WHERE id = 'I1';
+         DELETE FROM items WHERE id = 'I3';
+         COMMIT;",
+    )
+    .unwrap();
+    assert!(!glue.storage.in_transaction());
+    assert_eq!(ids(&mut glue, "Estimate = 8"), ["I1"]);
+    assert_eq!(ids(&mut glue, "TRUE"), ["I1", "I2"]);
+    // there is nothing to roll back after COMMIT
+    assert!(run(&mut glue, "ROLLBACK;").is_err());
+}
+
+#[test]
+fn nested_begin() {
+    let mut glue = load();
+    run(&mut glue, "BEGIN;").unwrap();
+    let error = run(&mut glue, "BEGIN;").unwrap_err();
+    assert!(error.contains("already in progress"));
+}